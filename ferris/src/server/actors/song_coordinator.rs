@@ -1,12 +1,39 @@
-use std::{collections::VecDeque, fmt::Display, sync::Arc, usize};
-use strum::Display;
+use rand::seq::SliceRandom;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    path::Path,
+    sync::Arc,
+    usize,
+};
+use strum::{Display, IntoStaticStr};
 use thiserror::Error;
 
-use tokio::sync::{self, mpsc, oneshot};
-use tracing::{error, warn};
+use tokio::{
+    sync::{self, mpsc, oneshot},
+    time::{sleep_until, Duration, Instant},
+};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::server::routes::sse::SseEvent;
+use crate::server::{
+    actors::video_downloader::{CachedVideo, DownloadedVideo, VideoDetails, VideoDlActorHandle},
+    playlist_library::PlaylistLibrary,
+    routes::sse::SseEvent,
+    utils::{
+        clock::{unix_timestamp, unix_timestamp_millis},
+        idle_mode::{IdleMode, IdleSong, IdleSource},
+        key_memory::KeyMemory,
+        play_history::PlayedSong,
+        playback::{PlaybackState, PlaybackStatus},
+        progress::{DownloadUpdate, ProcessingProgress, ProgressReporter},
+        snapshot_store::SnapshotStore,
+        stage::{ScheduledBreak, Stage, StagePhase, StageState, UpNext},
+        undo_history::UndoHistory,
+        video_id::{extract_video_id, video_folder},
+        yt_downloader::VideoProcessError,
+    },
+};
 
 fn serialize_uuid<S>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -15,29 +42,228 @@ where
     serializer.serialize_str(uuid.to_string().as_str())
 }
 
-#[derive(Clone, serde::Serialize, PartialEq, Display)]
+fn deserialize_uuid<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let uuid = <String as serde::Deserialize>::deserialize(deserializer)?;
+    Uuid::parse_str(&uuid).map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Display)]
 pub enum QueuedSongStatus {
     InProgress,
     Failed,
     Success,
 }
 
+/// How the coordinator orders upcoming songs. `Fair` interleaves singers round-robin so
+/// nobody gets a second turn before everyone else has had their first.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Display)]
+pub enum QueueOrdering {
+    #[default]
+    Fifo,
+    Fair,
+}
+
+/// When a song that has already been played may be queued again. It is let back in
+/// once either limit has been reached; with neither set, only songs that are still in
+/// the queue count as duplicates.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct RepeatPolicy {
+    pub min_songs_between: Option<u32>,
+    pub min_minutes_between: Option<u64>,
+}
+
+/// Host-configured caps on what gets into the queue. Unset limits aren't enforced.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct QueueLimits {
+    pub max_queue_length: Option<u32>,
+    pub max_songs_per_requester: Option<u32>,
+    pub max_requests_per_hour: Option<u32>,
+}
+
+/// How long the "up next" countdown between songs runs. Zero turns it off, and songs
+/// follow each other straight away.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Interstitial {
+    pub seconds: u32,
+}
+
+/// How many of the room's most played songs [`IdleSource::Popular`] picks from.
+const POPULAR_IDLE_SONGS: usize = 10;
+
+/// The longest break the host can schedule, in minutes.
+const MAX_BREAK_MINUTES: u32 = 240;
+
+/// What happens to a failed download that comes up next: dropped from the queue, or
+/// parked on the side where the host can still see it.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Display)]
+pub enum FailedSongPolicy {
+    #[default]
+    Drop,
+    Park,
+}
+
+/// Where a song that was accepted by `queue_song` went.
+#[derive(Clone, Copy, Debug, serde::Serialize, PartialEq, Display)]
+pub enum QueueOutcome {
+    Queued,
+    /// Moderation is on, so the song waits for the host in the pending list.
+    Pending,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, PartialEq, Display)]
+pub enum RequestDecision {
+    Approved,
+    Rejected,
+}
+
+/// Why a song was passed over when the queue advanced.
+#[derive(Clone, Copy, Debug, serde::Serialize, PartialEq, Display)]
+pub enum SkipReason {
+    DroppedFailed,
+    ParkedFailed,
+    /// Still downloading, so a ready song went ahead of it. It keeps its place otherwise.
+    StillDownloading,
+}
+
 #[derive(Clone, serde::Serialize)]
+pub struct SkippedSong {
+    pub song: Song,
+    pub reason: SkipReason,
+}
+
+/// How many skip votes it takes to move past the current song: a fixed number of votes,
+/// or a percentage of the clients connected to the room (displays included).
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum SkipThreshold {
+    Votes(u32),
+    Percent(u8),
+}
+
+impl Default for SkipThreshold {
+    fn default() -> Self {
+        SkipThreshold::Percent(50)
+    }
+}
+
+impl SkipThreshold {
+    fn votes_needed(&self, connected_clients: usize) -> u32 {
+        let needed = match *self {
+            SkipThreshold::Votes(votes) => votes,
+            SkipThreshold::Percent(percent) => {
+                (connected_clients as u32 * percent.min(100) as u32).div_ceil(100)
+            }
+        };
+        needed.max(1)
+    }
+}
+
+/// The songs a phone has a hand in, by UUID. Phones can't see who queued or joined
+/// a song, so this is how they find their own.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct OwnSongs {
+    /// Queued by the phone, whether they're in the queue, pending or parked.
+    pub requested: Vec<String>,
+    /// Queued by someone else, with the phone singing along.
+    pub joined: Vec<String>,
+}
+
+/// The running skip vote on the current song.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct SkipVotes {
+    pub song_uuid: Option<String>,
+    pub votes: u32,
+    pub needed: u32,
+}
+
+/// Which of the [`QueueLimits`] a request ran into. Serialized as-is for the phone, so
+/// it can tell the user what to wait for.
+#[derive(Error, Debug, Clone, serde::Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum QueueLimitViolation {
+    #[error("the queue is full ({max_queue_length} songs)")]
+    MaxQueueLength { max_queue_length: u32 },
+
+    #[error("you already have {max_songs_per_requester} songs in the queue")]
+    MaxSongsPerRequester { max_songs_per_requester: u32 },
+
+    #[error(
+        "you can request {max_requests_per_hour} songs an hour, try again in {retry_after_seconds} seconds"
+    )]
+    MaxRequestsPerHour {
+        max_requests_per_hour: u32,
+        retry_after_seconds: u64,
+    },
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Song {
     pub name: String,
-    #[serde(serialize_with = "serialize_uuid")]
+    #[serde(
+        serialize_with = "serialize_uuid",
+        deserialize_with = "deserialize_uuid"
+    )]
     pub uuid: Uuid,
     pub yt_link: String,
     pub status: QueuedSongStatus,
     pub is_key_changeable: bool,
+    #[serde(default)]
+    pub singer: Option<String>,
+    #[serde(default)]
+    pub video_id: Option<String>,
+    /// The cache folder the video is processed into and streamed from, see
+    /// [`video_folder`].
+    #[serde(default)]
+    pub folder: String,
+    /// The phone that queued the song, see [`crate::server::requester::Requester`]. Never
+    /// sent out, so phones can't tell who queued what; they ask for [`OwnSongs`]
+    /// instead. Snapshots keep it on the side.
+    #[serde(default, skip_serializing)]
+    pub requester: Option<String>,
+    /// Known once the video has been downloaded, like the rest of the metadata below.
+    #[serde(default)]
+    pub duration_seconds: Option<f64>,
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    /// The channel that uploaded the video.
+    #[serde(default)]
+    pub uploader: Option<String>,
+    /// Everyone who joined in after the song was queued, for duets and group songs.
+    #[serde(default)]
+    pub joined: Vec<Performer>,
+    /// Attempts the download has taken so far, across retries and fallback formats.
+    #[serde(default)]
+    pub download_attempts: u32,
+    /// Why the last failed attempt failed. Kept when a later attempt works, so the host
+    /// can tell why the song took a while.
+    #[serde(default)]
+    pub download_error: Option<String>,
 }
 
+/// Someone singing along on a song another phone queued.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Performer {
+    pub name: String,
+    /// Never sent out, the same as [`Song::requester`].
+    #[serde(default, skip_serializing)]
+    pub requester: String,
+}
+
+/// How many people can sing one song, counting whoever queued it.
+const MAX_PERFORMERS: usize = 4;
+
 impl Display for Song {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Song: {{ name: {}, uuid: {}, yt_link: {}, status: {} }}",
-            self.name, self.uuid, self.yt_link, self.status
+            "Song: {{ name: {}, uuid: {}, yt_link: {}, status: {}, singer: {} }}",
+            self.name,
+            self.uuid,
+            self.yt_link,
+            self.status,
+            self.singer.as_deref().unwrap_or("-")
         )
     }
 }
@@ -48,20 +274,282 @@ impl Song {
         yt_link: String,
         status: QueuedSongStatus,
         is_key_changeable: bool,
+        singer: Option<String>,
+        requester: Option<String>,
     ) -> Self {
         Song {
             name: name.to_string(),
             uuid: Uuid::new_v4(),
+            video_id: extract_video_id(&yt_link),
+            folder: video_folder(&yt_link, is_key_changeable),
             yt_link,
             status,
             is_key_changeable,
+            singer,
+            requester,
+            duration_seconds: None,
+            thumbnail_url: None,
+            uploader: None,
+            joined: Vec::new(),
+            download_attempts: 0,
+            download_error: None,
         }
     }
+
+    /// Fills in what the download found out about the video. Anything it couldn't find
+    /// out keeps whatever we already had.
+    fn apply_details(&mut self, details: VideoDetails) {
+        if details.video_id.is_some() {
+            self.video_id = details.video_id;
+        }
+        if details.duration_seconds.is_some() {
+            self.duration_seconds = details.duration_seconds;
+        }
+        if details.thumbnail_url.is_some() {
+            self.thumbnail_url = details.thumbnail_url;
+        }
+        if details.uploader.is_some() {
+            self.uploader = details.uploader;
+        }
+    }
+
+    /// What duplicate detection compares: the YouTube video ID, or the raw link for
+    /// anything we can't pull an ID out of.
+    fn canonical_id(&self) -> &str {
+        self.video_id.as_deref().unwrap_or(&self.yt_link)
+    }
+
+    /// Songs without a singer are all treated as coming from the same anonymous guest,
+    /// so a queue of unnamed requests keeps its FIFO order in fair mode.
+    fn singer_key(&self) -> &str {
+        self.singer.as_deref().unwrap_or_default()
+    }
+
+    /// The fairness keys of everyone on the song. A duet is a turn for each of them.
+    fn singer_keys(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.singer_key())
+            .chain(self.joined.iter().map(|performer| performer.name.as_str()))
+    }
+
+    fn is_performed_by(&self, requester: &str) -> bool {
+        self.requester.as_deref() == Some(requester)
+            || self
+                .joined
+                .iter()
+                .any(|performer| performer.requester == requester)
+    }
+
+    fn performer_names(&self) -> Vec<String> {
+        self.singer
+            .iter()
+            .cloned()
+            .chain(self.joined.iter().map(|performer| performer.name.clone()))
+            .collect()
+    }
 }
 
 impl PartialEq for Song {
     fn eq(&self, other: &Self) -> bool {
-        self.uuid == other.uuid || self.name == other.name
+        self.uuid == other.uuid
+    }
+}
+
+/// A song as the queue is shown to phones and displays.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct QueueEntry {
+    #[serde(flatten)]
+    pub song: Song,
+    /// Roughly when the song should start, in unix milliseconds like
+    /// [`PlaybackState::updated_at`], going by the playback position and the durations
+    /// of the songs ahead of it. While the current song is paused, these are as of the
+    /// pause and slip by however long it goes on. `None` when a song ahead hasn't
+    /// finished downloading, or this one has failed.
+    pub starts_at: Option<u64>,
+    /// Names of everyone singing, whoever queued the song first.
+    pub performers: Vec<String>,
+    /// How far along the download is, while the song is still in progress.
+    pub progress: Option<ProcessingProgress>,
+}
+
+/// The queue along with its revision, which goes up by one whenever songs are added,
+/// removed or moved. Phones send back the revision they last saw so changes made from a
+/// stale view of the queue can be turned down instead of applied to songs that have
+/// since moved.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct QueueState {
+    pub revision: u64,
+    pub queue: Vec<QueueEntry>,
+}
+
+/// How long past the end of a song the server waits for the display before moving on by
+/// itself. Covers buffering and displays whose clock runs a little behind ours.
+const AUTO_ADVANCE_GRACE: Duration = Duration::from_secs(15);
+
+/// A reported position this close to the song's duration counts as having finished it.
+const END_OF_SONG_TOLERANCE_SECONDS: f64 = 1.0;
+
+/// Bumped whenever the layout of [`QueueSnapshot`] changes. Snapshots written with a
+/// different version are discarded at startup rather than half-parsed.
+const QUEUE_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct QueueSnapshot {
+    version: u32,
+    current_key: i8,
+    song_deque: VecDeque<Song>,
+    #[serde(default)]
+    ordering: QueueOrdering,
+    #[serde(default)]
+    turns_taken: HashMap<String, u32>,
+    #[serde(default)]
+    repeat_policy: RepeatPolicy,
+    #[serde(default)]
+    queue_limits: QueueLimits,
+    #[serde(default)]
+    skip_threshold: SkipThreshold,
+    #[serde(default)]
+    failed_song_policy: FailedSongPolicy,
+    #[serde(default)]
+    parked_songs: Vec<Song>,
+    #[serde(default)]
+    moderation: bool,
+    #[serde(default)]
+    pending_songs: Vec<Song>,
+    #[serde(default)]
+    revision: u64,
+    #[serde(default)]
+    interstitial: Interstitial,
+    #[serde(default)]
+    scheduled_breaks: Vec<ScheduledBreak>,
+    #[serde(default)]
+    idle_mode: IdleMode,
+    /// Who queued each song, by song UUID, as [`Song::requester`] isn't serialized.
+    #[serde(default)]
+    requesters: HashMap<String, String>,
+    /// Who joined each song, by song UUID, in the order of [`Song::joined`].
+    #[serde(default)]
+    joined_requesters: HashMap<String, Vec<String>>,
+    /// The current song's UUID and when the display first reported it playing, so a
+    /// restart mid-song doesn't lose the time already sung.
+    #[serde(default)]
+    singing_since: Option<(String, u64)>,
+}
+
+impl QueueSnapshot {
+    fn empty() -> Self {
+        QueueSnapshot {
+            version: QUEUE_SNAPSHOT_VERSION,
+            current_key: 0,
+            song_deque: VecDeque::new(),
+            ordering: QueueOrdering::default(),
+            turns_taken: HashMap::new(),
+            repeat_policy: RepeatPolicy::default(),
+            queue_limits: QueueLimits::default(),
+            skip_threshold: SkipThreshold::default(),
+            failed_song_policy: FailedSongPolicy::default(),
+            parked_songs: Vec::new(),
+            moderation: false,
+            pending_songs: Vec::new(),
+            revision: 0,
+            interstitial: Interstitial::default(),
+            scheduled_breaks: Vec::new(),
+            idle_mode: IdleMode::default(),
+            requesters: HashMap::new(),
+            joined_requesters: HashMap::new(),
+            singing_since: None,
+        }
+    }
+
+    fn load(snapshot_store: &SnapshotStore) -> Self {
+        match snapshot_store.load::<QueueSnapshot>() {
+            Some(mut snapshot) if snapshot.version == QUEUE_SNAPSHOT_VERSION => {
+                // songs saved before video IDs were tracked
+                for song in snapshot.song_deque.iter_mut() {
+                    if song.video_id.is_none() {
+                        song.video_id = extract_video_id(&song.yt_link);
+                    }
+                }
+                // songs saved while folders were named after the song. The ones that
+                // are done stay where they were processed, the rest download again.
+                for song in snapshot
+                    .song_deque
+                    .iter_mut()
+                    .chain(snapshot.pending_songs.iter_mut())
+                    .chain(snapshot.parked_songs.iter_mut())
+                    .filter(|song| song.folder.is_empty())
+                {
+                    song.folder = match song.status {
+                        QueuedSongStatus::Success => song.name.clone(),
+                        _ => video_folder(&song.yt_link, song.is_key_changeable),
+                    };
+                }
+                for song in snapshot
+                    .song_deque
+                    .iter_mut()
+                    .chain(snapshot.pending_songs.iter_mut())
+                    .chain(snapshot.parked_songs.iter_mut())
+                {
+                    let uuid = song.uuid.to_string();
+                    if let Some(requester) = snapshot.requesters.remove(&uuid) {
+                        song.requester = Some(requester);
+                    }
+                    if let Some(requesters) = snapshot.joined_requesters.remove(&uuid) {
+                        for (performer, requester) in song.joined.iter_mut().zip(requesters) {
+                            performer.requester = requester;
+                        }
+                    }
+                }
+                snapshot
+            }
+            Some(snapshot) => {
+                warn!(
+                    "discarding queue snapshot with version {}, expected {}",
+                    snapshot.version, QUEUE_SNAPSHOT_VERSION
+                );
+                QueueSnapshot::empty()
+            }
+            None => QueueSnapshot::empty(),
+        }
+    }
+}
+
+/// How many queue operations `/undo` can walk back.
+const UNDO_LIMIT: usize = 50;
+
+/// A queue change that can be undone. Songs are tracked by UUID rather than position so
+/// the operation still makes sense after other changes have happened in between.
+enum QueueOperation {
+    Removed {
+        song: Song,
+        index: usize,
+        /// The song right before it, used to put it back next to the same neighbour.
+        after: Option<Uuid>,
+    },
+    Repositioned {
+        song_uuid: Uuid,
+        from: usize,
+        to: usize,
+    },
+    Popped {
+        song: Song,
+        key: i8,
+    },
+    Cleared {
+        songs: Vec<Song>,
+    },
+}
+
+impl QueueOperation {
+    /// The songs the operation took out of the queue, or put back when undone.
+    fn songs(&self) -> impl Iterator<Item = &Song> {
+        let songs = match self {
+            QueueOperation::Removed { song, .. } | QueueOperation::Popped { song, .. } => {
+                std::slice::from_ref(song)
+            }
+            QueueOperation::Cleared { songs } => songs.as_slice(),
+            QueueOperation::Repositioned { .. } => &[],
+        };
+        songs.iter()
     }
 }
 
@@ -69,31 +557,96 @@ struct SongActor {
     receiver: mpsc::Receiver<SongActorMessage>,
     song_deque: VecDeque<Song>,
     current_key: i8,
+    ordering: QueueOrdering,
+    /// Number of songs each singer has finished, keyed by [`Song::singer_key`].
+    turns_taken: HashMap<String, u32>,
+    repeat_policy: RepeatPolicy,
+    queue_limits: QueueLimits,
+    /// When each requester's accepted requests came in over the last hour, oldest first.
+    recent_requests: HashMap<String, VecDeque<u64>>,
+    skip_threshold: SkipThreshold,
+    failed_song_policy: FailedSongPolicy,
+    /// Failed downloads taken out of the queue under [`FailedSongPolicy::Park`].
+    parked_songs: Vec<Song>,
+    /// When set, requests wait in `pending_songs` until the host approves them.
+    moderation: bool,
+    pending_songs: Vec<Song>,
+    /// Bumped every time the queue changes, see [`QueueState`].
+    revision: u64,
+    interstitial: Interstitial,
+    /// Breaks that haven't started yet, soonest first.
+    scheduled_breaks: Vec<ScheduledBreak>,
+    stage: Stage,
+    idle_mode: IdleMode,
+    /// The idle song playing and when it started.
+    idle_song: Option<(IdleSong, Instant)>,
+    /// Since when the queue has been empty, `None` while it isn't.
+    idle_since: Option<Instant>,
+    /// Requesters who voted to skip the current song.
+    skip_votes: HashSet<String>,
+    /// The song at the front of the deque, and when the display first reported it
+    /// playing. Waiting on the download or the countdown doesn't count as singing.
+    current_started: Option<(Uuid, Option<u64>)>,
+    /// Playing/paused and position of the current song, as last commanded or reported.
+    playback: PlaybackState,
+    /// The latest progress of each song that's still downloading.
+    download_progress: HashMap<Uuid, ProcessingProgress>,
+    history: Vec<PlayedSong>,
+    key_memory: KeyMemory,
+    undo_history: UndoHistory<QueueOperation>,
     sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+    /// Where idle mode finds its songs, and what cancels downloads of songs that leave
+    /// the queue before they're ready.
+    videodl_actor_handle: Arc<VideoDlActorHandle>,
+    playlist_library: Arc<PlaylistLibrary>,
+    /// Who this room's songs are claimed as in the media cache.
+    media_cache_owner: Uuid,
+    snapshot_store: SnapshotStore,
+    history_store: SnapshotStore,
+    key_memory_store: SnapshotStore,
+    /// Set once the room is closed so late messages (e.g. a download finishing) don't
+    /// write the snapshot back to disk.
+    closed: bool,
 }
 
 pub enum SongActorMessage {
     QueueSong {
         song: Song,
-        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+        respond_to: oneshot::Sender<Result<QueueOutcome, SongCoordinatorError>>,
     },
     RemoveSong {
         song_uuid: Uuid,
-        respond_to: oneshot::Sender<()>,
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    ClearQueue {
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    /// Responds with the songs put back that have to be downloaded again.
+    Undo {
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<Vec<Song>, SongCoordinatorError>>,
+    },
+    Redo {
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
     },
     PopSong {
+        expected_current: Option<Uuid>,
         respond_to: oneshot::Sender<Option<Song>>,
     },
     Reposition {
         song_uuid: Uuid,
         position: usize,
+        expected_revision: Option<u64>,
         respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
     },
     Current {
         respond_to: oneshot::Sender<Result<Option<Song>, SongCoordinatorError>>,
     },
     GetQueue {
-        respond_to: oneshot::Sender<Result<VecDeque<Song>, SongCoordinatorError>>,
+        respond_to: oneshot::Sender<Result<QueueState, SongCoordinatorError>>,
     },
     KeyUp {
         respond_to: oneshot::Sender<Result<i8, SongCoordinatorError>>,
@@ -104,248 +657,2881 @@ pub enum SongActorMessage {
     GetKey {
         respond_to: oneshot::Sender<Result<i8, SongCoordinatorError>>,
     },
+    SetOrdering {
+        ordering: QueueOrdering,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetOrdering {
+        respond_to: oneshot::Sender<Result<QueueOrdering, SongCoordinatorError>>,
+    },
+    GetHistory {
+        respond_to: oneshot::Sender<Result<Vec<PlayedSong>, SongCoordinatorError>>,
+    },
+    ClearHistory {
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    SetRepeatPolicy {
+        repeat_policy: RepeatPolicy,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetRepeatPolicy {
+        respond_to: oneshot::Sender<Result<RepeatPolicy, SongCoordinatorError>>,
+    },
+    SetQueueLimits {
+        queue_limits: QueueLimits,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetQueueLimits {
+        respond_to: oneshot::Sender<Result<QueueLimits, SongCoordinatorError>>,
+    },
+    VoteSkip {
+        requester: String,
+        respond_to: oneshot::Sender<Result<SkipVotes, SongCoordinatorError>>,
+    },
+    GetSkipVotes {
+        respond_to: oneshot::Sender<Result<SkipVotes, SongCoordinatorError>>,
+    },
+    GetOwnSongs {
+        requester: String,
+        respond_to: oneshot::Sender<Result<OwnSongs, SongCoordinatorError>>,
+    },
+    SetSkipThreshold {
+        skip_threshold: SkipThreshold,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetSkipThreshold {
+        respond_to: oneshot::Sender<Result<SkipThreshold, SongCoordinatorError>>,
+    },
+    SetFailedSongPolicy {
+        failed_song_policy: FailedSongPolicy,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetFailedSongPolicy {
+        respond_to: oneshot::Sender<Result<FailedSongPolicy, SongCoordinatorError>>,
+    },
+    GetParkedSongs {
+        respond_to: oneshot::Sender<Result<Vec<Song>, SongCoordinatorError>>,
+    },
+    /// Responds with the song, which has to be downloaded again.
+    RetrySong {
+        song_uuid: Uuid,
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<Song, SongCoordinatorError>>,
+    },
+    SetModeration {
+        enabled: bool,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetModeration {
+        respond_to: oneshot::Sender<Result<bool, SongCoordinatorError>>,
+    },
+    GetPendingSongs {
+        respond_to: oneshot::Sender<Result<Vec<Song>, SongCoordinatorError>>,
+    },
+    DecideRequest {
+        song_uuid: Uuid,
+        decision: RequestDecision,
+        position: Option<usize>,
+        expected_revision: Option<u64>,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetStage {
+        respond_to: oneshot::Sender<Result<StageState, SongCoordinatorError>>,
+    },
+    SkipStage {
+        respond_to: oneshot::Sender<Result<StageState, SongCoordinatorError>>,
+    },
+    ExtendStage {
+        seconds: u64,
+        respond_to: oneshot::Sender<Result<StageState, SongCoordinatorError>>,
+    },
+    SetInterstitial {
+        interstitial: Interstitial,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetInterstitial {
+        respond_to: oneshot::Sender<Result<Interstitial, SongCoordinatorError>>,
+    },
+    GetBreaks {
+        respond_to: oneshot::Sender<Result<Vec<ScheduledBreak>, SongCoordinatorError>>,
+    },
+    ScheduleBreak {
+        starts_at: Option<u64>,
+        minutes: u32,
+        respond_to: oneshot::Sender<Result<ScheduledBreak, SongCoordinatorError>>,
+    },
+    CancelBreak {
+        id: String,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetIdleMode {
+        respond_to: oneshot::Sender<Result<IdleMode, SongCoordinatorError>>,
+    },
+    SetIdleMode {
+        idle_mode: IdleMode,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetIdleSong {
+        respond_to: oneshot::Sender<Result<Option<IdleSong>, SongCoordinatorError>>,
+    },
+    NextIdleSong {
+        /// The idle song the display just finished, so a late report doesn't skip the
+        /// song after it.
+        expected_current: Option<Uuid>,
+        respond_to: oneshot::Sender<Result<Option<IdleSong>, SongCoordinatorError>>,
+    },
+    JoinSong {
+        song_uuid: Uuid,
+        performer: Performer,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    LeaveSong {
+        song_uuid: Uuid,
+        requester: String,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    Close {
+        respond_to: oneshot::Sender<()>,
+    },
     UpdateSongStatus {
         song_uuid: Uuid,
         status: QueuedSongStatus,
+        details: Option<VideoDetails>,
+        error: Option<String>,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    ReportProgress {
+        song_uuid: Uuid,
+        progress: ProcessingProgress,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    ReportRetry {
+        song_uuid: Uuid,
+        attempt: u32,
+        reason: String,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetPlayback {
+        respond_to: oneshot::Sender<Result<PlaybackState, SongCoordinatorError>>,
+    },
+    SetPlaybackStatus {
+        status: PlaybackStatus,
+        respond_to: oneshot::Sender<Result<PlaybackState, SongCoordinatorError>>,
+    },
+    ReportPlayback {
+        song_uuid: Uuid,
+        status: PlaybackStatus,
+        position_seconds: f64,
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    RestartPlayback {
+        respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
+    },
+    GetKeyMemory {
+        respond_to: oneshot::Sender<Result<KeyMemory, SongCoordinatorError>>,
+    },
+    ForgetKeys {
+        song_id: Option<String>,
         respond_to: oneshot::Sender<Result<(), SongCoordinatorError>>,
     },
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum SongCoordinatorError {
-    #[error("unable to queue song: {uuid}")]
-    QueueSongFailed { uuid: Uuid },
-
     #[error("song already queued: {name}")]
     SongAlreadyQueued { name: String },
 
+    #[error("song played too recently: {name}")]
+    SongPlayedTooRecently { name: String },
+
+    #[error(transparent)]
+    QueueLimitExceeded(#[from] QueueLimitViolation),
+
     #[error("unable to remove song: {uuid}")]
     RemoveSongFailed { uuid: Uuid },
 
-    #[error("unable to pop song")]
-    PopSongFailed,
-
     #[error("unable to reposition song: {uuid}")]
     RepositionSongFailed { uuid: Uuid },
 
-    #[error("unable to get current song")]
-    GetCurrentSongFailed,
-
-    #[error("unable to get queue")]
-    GetQueueFailed,
-
-    #[error("unable to key up")]
+    #[error("the key can't go any higher")]
     KeyUpFailed,
 
-    #[error("unable to key down")]
+    #[error("the key can't go any lower")]
     KeyDownFailed,
 
     #[error("unable to update song status for: {uuid}")]
     UpdateSongStatusFailed { uuid: Uuid },
 
-    #[error("failed to broadcast SSE event")]
-    SseBroadcastFailed,
+    #[error("nothing to undo")]
+    NothingToUndo,
+
+    #[error("nothing to redo")]
+    NothingToRedo,
+
+    #[error("unable to undo, the queue has changed too much")]
+    UndoFailed,
+
+    #[error("unable to redo, the queue has changed too much")]
+    RedoFailed,
+
+    #[error("no key remembered for: {song_id}")]
+    KeyNotRemembered { song_id: String },
+
+    #[error("nothing is playing")]
+    NothingPlaying,
+
+    #[error("no pending request for song: {uuid}")]
+    PendingSongNotFound { uuid: Uuid },
+
+    #[error("already voted to skip this song")]
+    AlreadyVotedToSkip,
+
+    #[error("playback report is for a song that is no longer current: {uuid}")]
+    StalePlaybackReport { uuid: Uuid },
+
+    #[error("unable to join song: {uuid}")]
+    JoinSongFailed { uuid: Uuid },
+
+    #[error("already performing song: {uuid}")]
+    AlreadyPerforming { uuid: Uuid },
+
+    #[error("no room for more performers, a song can have at most {max_performers}")]
+    TooManyPerformers { max_performers: usize },
+
+    #[error("not performing song: {uuid}")]
+    LeaveSongFailed { uuid: Uuid },
+
+    #[error("no failed song to retry: {uuid}")]
+    RetrySongFailed { uuid: Uuid },
+
+    #[error("song hasn't failed, there's nothing to retry: {uuid}")]
+    SongNotFailed { uuid: Uuid },
+
+    #[error("there's no countdown or break going on")]
+    NotBetweenSongs,
+
+    #[error("a break has to last between 1 and {max_minutes} minutes")]
+    InvalidBreakLength { max_minutes: u32 },
+
+    #[error("no scheduled break with id: {id}")]
+    BreakNotFound { id: String },
+
+    #[error("the queue has changed since revision {expected}, now at {}", current.revision)]
+    StaleQueueRevision { expected: u64, current: QueueState },
 }
 
 impl SongActor {
     fn new(
         receiver: mpsc::Receiver<SongActorMessage>,
         sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+        videodl_actor_handle: Arc<VideoDlActorHandle>,
+        playlist_library: Arc<PlaylistLibrary>,
+        data_dir: &Path,
+        snapshot_store: SnapshotStore,
+        snapshot: QueueSnapshot,
     ) -> Self {
-        SongActor {
+        let history_store = SnapshotStore::new(data_dir.join("play_history.json"));
+        let key_memory_store = SnapshotStore::new(data_dir.join("key_memory.json"));
+        let history = history_store.load().unwrap_or_default();
+        let key_memory = key_memory_store.load().unwrap_or_default();
+        let current_started = snapshot.song_deque.front().map(|song| {
+            let singing_since = snapshot
+                .singing_since
+                .as_ref()
+                .filter(|(uuid, _)| *uuid == song.uuid.to_string())
+                .map(|(_, started_at)| *started_at);
+            (song.uuid, singing_since)
+        });
+        let playback = PlaybackState::new(current_started.map(|(uuid, _)| uuid));
+        let idle_since = snapshot.song_deque.is_empty().then(Instant::now);
+
+        let song_actor = SongActor {
             receiver,
             sse_broadcaster,
-            song_deque: VecDeque::new(),
-            current_key: 0,
-        }
+            videodl_actor_handle,
+            playlist_library,
+            song_deque: snapshot.song_deque,
+            current_key: snapshot.current_key,
+            ordering: snapshot.ordering,
+            turns_taken: snapshot.turns_taken,
+            repeat_policy: snapshot.repeat_policy,
+            queue_limits: snapshot.queue_limits,
+            recent_requests: HashMap::new(),
+            skip_threshold: snapshot.skip_threshold,
+            skip_votes: HashSet::new(),
+            failed_song_policy: snapshot.failed_song_policy,
+            parked_songs: snapshot.parked_songs,
+            moderation: snapshot.moderation,
+            pending_songs: snapshot.pending_songs,
+            revision: snapshot.revision,
+            interstitial: snapshot.interstitial,
+            scheduled_breaks: snapshot.scheduled_breaks,
+            stage: Stage::default(),
+            idle_mode: snapshot.idle_mode,
+            idle_song: None,
+            idle_since,
+            current_started,
+            playback,
+            download_progress: HashMap::new(),
+            history,
+            key_memory,
+            undo_history: UndoHistory::new(UNDO_LIMIT),
+            media_cache_owner: Uuid::new_v4(),
+            snapshot_store,
+            history_store,
+            key_memory_store,
+            closed: false,
+        };
+        song_actor.claim_cached_songs();
+
+        song_actor
     }
 
-    async fn handle_message(&mut self, msg: SongActorMessage) {
-        match msg {
-            SongActorMessage::QueueSong { song, respond_to } => {
-                if self.song_deque.contains(&song) {
-                    let _ = respond_to.send(Err(SongCoordinatorError::SongAlreadyQueued {
-                        name: song.name,
-                    }));
-                } else {
-                    self.song_deque.push_back(song.clone());
+    /// Keeps the songs that are queued, waiting for approval or playing in idle mode
+    /// from being evicted from the media cache.
+    fn claim_cached_songs(&self) {
+        let names = self
+            .song_deque
+            .iter()
+            .chain(self.pending_songs.iter())
+            .map(|song| song.folder.clone())
+            .chain(self.idle_song.as_ref().map(|(song, _)| song.folder.clone()))
+            .collect();
+        self.videodl_actor_handle
+            .media_cache()
+            .claim(self.media_cache_owner, names);
+    }
 
-                    match self.sse_broadcaster.send(SseEvent::QueueUpdated {
-                        queue: self.song_deque.clone(),
-                    }) {
-                        Ok(_) => {
-                            let _ = respond_to.send(Ok(()));
-                        }
-                        Err(err) => {
-                            // Remove the song since broadcasting failed
-                            warn!("failed to broadcast SSE event for queue update event for song: {} with error: {}", song.uuid, err);
-                            let _ = respond_to.send(Ok(()));
-                        }
-                    }
-                }
+    /// Restarts the clock whenever a different song ends up at the front of the deque.
+    fn track_current(&mut self) {
+        let front = self.song_deque.front().map(|song| song.uuid);
+        if front != self.current_started.map(|(uuid, _)| uuid) {
+            self.current_started = front.map(|uuid| (uuid, None));
+            if let Some(song) = self.song_deque.front() {
+                self.videodl_actor_handle
+                    .media_cache()
+                    .record_played(&song.folder);
             }
-            SongActorMessage::RemoveSong {
-                song_uuid,
-                respond_to,
-            } => {
-                if let Some(index) = self.song_deque.iter().position(|x| x.uuid == song_uuid) {
-                    self.song_deque.remove(index);
-                }
+            self.playback = self.fresh_playback(front);
+            self.broadcast_playback();
+            self.broadcast_stage();
 
-                match self.sse_broadcaster.send(SseEvent::QueueUpdated {
-                    queue: self.song_deque.clone(),
-                }) {
-                    Ok(_) => {
-                        let _ = respond_to.send(());
-                    }
-                    Err(err) => {
-                        warn!(
-                            "failed to broadcast SSE event for queue update event for song: {} with error: {}", 
-                            song_uuid,
-                            err
-                        );
-                        let _ = respond_to.send(());
-                    }
-                }
-            }
-            SongActorMessage::PopSong { respond_to } => {
-                // remove all failed songs while getting the next one
-                let next_song = self.song_deque.pop_front();
+            self.current_key = self.preferred_key();
+            let _ = self.sse_broadcaster.send(SseEvent::KeyChange {
+                current_key: self.current_key,
+            });
 
-                self.current_key = 0;
+            self.skip_votes.clear();
+            let _ = self.sse_broadcaster.send(SseEvent::SkipVotesUpdated {
+                skip_votes: self.skip_tally(),
+            });
+        }
+    }
 
-                match self.sse_broadcaster.send(SseEvent::QueueUpdated {
-                    queue: self.song_deque.clone(),
-                }) {
-                    Ok(_) => {
-                        let _ = respond_to.send(next_song.clone());
-                    }
-                    Err(err) => {
-                        warn!(
-                            "failed to broadcast SSE event for queue update event with error: {}",
-                            err
-                        );
-                        let _ = respond_to.send(next_song.clone());
-                    }
-                }
+    /// Starts the clock on the time sung, the first time the display reports the
+    /// current song playing.
+    fn start_singing(&mut self, song_uuid: Uuid) {
+        if let Some((uuid, started_at @ None)) = &mut self.current_started {
+            if *uuid == song_uuid {
+                *started_at = Some(unix_timestamp());
+                self.persist();
             }
-            SongActorMessage::Reposition {
-                song_uuid,
-                position,
-                respond_to,
-            } => {
-                if let Some(current_index) =
-                    self.song_deque.iter().position(|x| x.uuid == song_uuid)
-                {
-                    let song = self.song_deque.remove(current_index).unwrap();
-                    let new_position = position.min(self.song_deque.len());
-                    self.song_deque.insert(new_position, song);
-
-                    match self.sse_broadcaster.send(SseEvent::QueueUpdated {
-                        queue: self.song_deque.clone(),
-                    }) {
-                        Ok(_) => {
-                            let _ = respond_to.send(Ok(()));
-                        }
-                        Err(err) => {
-                            warn!(
-                                "failed to broadcast SSE event for queue update event for song: {} with error: {}", 
-                                song_uuid,
-                                err
-                            );
-                            let _ = respond_to.send(Ok(()));
-                        }
-                    }
-                } else {
-                    let _ = respond_to.send(Ok(()));
+        }
+    }
+
+    fn record_played(&mut self, song: &Song) {
+        let ended_at = unix_timestamp();
+        // a song skipped before it ever played wasn't sung at all
+        let started_at = match self.current_started {
+            Some((uuid, Some(started_at))) if uuid == song.uuid => started_at,
+            _ => ended_at,
+        };
+
+        self.history.push(PlayedSong {
+            uuid: song.uuid.to_string(),
+            video_id: song.video_id.clone(),
+            name: song.name.clone(),
+            yt_link: song.yt_link.clone(),
+            singer: song.singer.clone(),
+            key: self.current_key,
+            started_at,
+            ended_at,
+        });
+
+        self.persist_history();
+    }
+
+    /// The key the current song was last sung in, by its singer if we know one.
+    fn preferred_key(&self) -> i8 {
+        self.song_deque
+            .front()
+            .filter(|song| song.is_key_changeable)
+            .and_then(|song| {
+                self.key_memory
+                    .preferred(song.canonical_id(), song.singer.as_deref())
+            })
+            .unwrap_or(0)
+    }
+
+    fn remember_key(&mut self) {
+        let Some(song) = self
+            .song_deque
+            .front()
+            .filter(|song| song.is_key_changeable)
+        else {
+            return;
+        };
+
+        self.key_memory.remember(
+            song.canonical_id(),
+            song.singer.as_deref(),
+            self.current_key,
+        );
+        self.persist_key_memory();
+    }
+
+    fn persist_key_memory(&self) {
+        if self.closed {
+            return;
+        }
+
+        if let Err(err) = self.key_memory_store.save(&self.key_memory) {
+            error!("failed to persist key memory with error: {}", err);
+        }
+    }
+
+    fn persist_history(&self) {
+        if self.closed {
+            return;
+        }
+
+        if let Err(err) = self.history_store.save(&self.history) {
+            error!("failed to persist play history with error: {}", err);
+        }
+    }
+
+    /// Moves the queue on to its next revision, saves it and lets everyone know. Only for
+    /// changes to which songs are queued or their order, which is what revisions guard.
+    fn queue_changed(&mut self) {
+        self.revision += 1;
+        self.queue_refreshed();
+        self.track_idle();
+    }
+
+    /// Saves the queue and lets everyone know about a change to its songs, like a
+    /// download finishing, that leaves the order alone. The revision stays put, so
+    /// changes made against it aren't turned down for no reason.
+    fn queue_refreshed(&mut self) {
+        self.persist();
+        self.broadcast_queue();
+    }
+
+    fn broadcast_queue(&self) {
+        if let Err(err) = self.sse_broadcaster.send(SseEvent::QueueUpdated {
+            revision: self.revision,
+            queue: self.queue_entries(),
+        }) {
+            warn!(
+                "failed to broadcast SSE event for queue update event with error: {}",
+                err
+            );
+        }
+    }
+
+    /// Playback for a song that just got to the front. It starts playing unless there's
+    /// a countdown or break to get through first.
+    fn fresh_playback(&self, song_uuid: Option<Uuid>) -> PlaybackState {
+        let mut playback = PlaybackState::new(song_uuid);
+        if self.stage.holds_playback() {
+            playback.set_status(PlaybackStatus::Paused);
+        }
+        playback
+    }
+
+    fn stage_state(&self) -> StageState {
+        let up_next = self
+            .song_deque
+            .front()
+            .filter(|_| self.stage.holds_playback())
+            .map(|song| UpNext {
+                song_uuid: song.uuid.to_string(),
+                name: song.name.clone(),
+                performers: song.performer_names(),
+            });
+
+        StageState {
+            phase: self.stage.phase,
+            ends_at: self.stage.ends_at,
+            up_next,
+        }
+    }
+
+    fn broadcast_stage(&self) {
+        let _ = self.sse_broadcaster.send(SseEvent::StageChanged {
+            stage: self.stage_state(),
+        });
+    }
+
+    /// Takes the first scheduled break whose time has come, if any.
+    fn take_due_break(&mut self) -> Option<ScheduledBreak> {
+        let now = unix_timestamp();
+        if self
+            .scheduled_breaks
+            .first()
+            .is_none_or(|scheduled| scheduled.starts_at > now)
+        {
+            return None;
+        }
+
+        let due = self.scheduled_breaks.remove(0);
+        self.persist();
+        Some(due)
+    }
+
+    fn break_stage(scheduled: ScheduledBreak) -> Stage {
+        info!("starting a {} minute break", scheduled.minutes);
+        Stage::lasting(StagePhase::Break, scheduled.minutes as u64 * 60)
+    }
+
+    /// What comes before the song now at the front: a break if one is due, otherwise
+    /// the "up next" countdown if there's one configured.
+    fn pause_before_next_song(&mut self) -> Stage {
+        if let Some(due) = self.take_due_break() {
+            return Self::break_stage(due);
+        }
+
+        match self.interstitial.seconds {
+            seconds if seconds > 0 && !self.song_deque.is_empty() => {
+                Stage::lasting(StagePhase::UpNext, seconds as u64)
+            }
+            _ => Stage::default(),
+        }
+    }
+
+    /// Brings the current song's playback in line with the stage and tells everyone.
+    fn apply_stage(&mut self) {
+        let status = match self.stage.holds_playback() {
+            true => PlaybackStatus::Paused,
+            false => PlaybackStatus::Playing,
+        };
+        if self.playback.song_uuid.is_some() && self.playback.set_status(status) {
+            self.broadcast_playback();
+        }
+        if self.stage.holds_playback() {
+            self.stop_idle_song();
+        }
+        self.broadcast_stage();
+    }
+
+    /// Ends the countdown or break, or starts a break that has come due while nothing
+    /// was playing. A break is still followed by the countdown, so the singer gets their
+    /// warning either way.
+    fn finish_stage(&mut self) {
+        self.stage = match self.stage.phase {
+            StagePhase::Break => self.pause_before_next_song(),
+            StagePhase::UpNext | StagePhase::Singing => match self.take_due_break() {
+                Some(due) => Self::break_stage(due),
+                None => Stage::default(),
+            },
+        };
+        self.apply_stage();
+    }
+
+    /// When the stage needs looking at again: the end of the current pause, or the start
+    /// of the next break. A break that comes up mid-song waits for [`Self::advance`], so
+    /// it only counts here while nothing is playing or during the countdown.
+    fn stage_deadline(&self) -> Option<Instant> {
+        let next_break = self
+            .scheduled_breaks
+            .first()
+            .map(|scheduled| scheduled.starts_at.saturating_mul(1000));
+
+        let at = match self.stage.phase {
+            StagePhase::Singing => next_break.filter(|_| self.song_deque.is_empty()),
+            StagePhase::UpNext => match (self.stage.ends_at, next_break) {
+                (Some(ends_at), Some(next_break)) => Some(ends_at.min(next_break)),
+                (ends_at, next_break) => ends_at.or(next_break),
+            },
+            StagePhase::Break => self.stage.ends_at,
+        }?;
+
+        // a deadline too far off for an Instant is as good as none
+        let remaining = at.saturating_sub(unix_timestamp_millis());
+        Instant::now().checked_add(Duration::from_millis(remaining))
+    }
+
+    /// Starts the clock on idle mode once the queue runs dry, and stops the idle song as
+    /// soon as there's a real one to get to.
+    fn track_idle(&mut self) {
+        if self.song_deque.is_empty() {
+            self.idle_since.get_or_insert_with(Instant::now);
+        } else {
+            self.idle_since = None;
+            self.stop_idle_song();
+        }
+    }
+
+    fn stop_idle_song(&mut self) {
+        if self.idle_song.take().is_some() {
+            info!("stopping idle mode");
+            self.broadcast_idle_song();
+        }
+    }
+
+    fn idle_song(&self) -> Option<IdleSong> {
+        self.idle_song.as_ref().map(|(song, _)| song.clone())
+    }
+
+    fn broadcast_idle_song(&self) {
+        self.claim_cached_songs();
+        let _ = self.sse_broadcaster.send(SseEvent::IdleSongChanged {
+            song: self.idle_song(),
+        });
+    }
+
+    /// The cached videos idle mode can choose from. Popular falls back on the whole
+    /// cache until something in it has been played.
+    fn idle_candidates(&self) -> Vec<CachedVideo> {
+        let videos = self.videodl_actor_handle.processed_videos();
+
+        match &self.idle_mode.source {
+            IdleSource::Random => videos,
+            IdleSource::Popular => {
+                // by video where we know it, by name for songs from before we did
+                let mut plays: HashMap<&str, usize> = HashMap::new();
+                for played in &self.history {
+                    let id = played.video_id.as_deref().unwrap_or(&played.name);
+                    *plays.entry(id).or_insert(0) += 1;
+                }
+
+                let mut played: Vec<(usize, CachedVideo)> = videos
+                    .iter()
+                    .filter_map(|video| {
+                        let id = video.details.video_id.as_deref().unwrap_or(&video.name);
+                        Some((*plays.get(id)?, video.clone()))
+                    })
+                    .collect();
+                if played.is_empty() {
+                    return videos;
                 }
+
+                played.sort_by_key(|(plays, _)| std::cmp::Reverse(*plays));
+                played
+                    .into_iter()
+                    .take(POPULAR_IDLE_SONGS)
+                    .map(|(_, video)| video)
+                    .collect()
             }
-            SongActorMessage::Current { respond_to } => {
-                let _ = respond_to.send(Ok(self.song_deque.front().cloned()));
+            IdleSource::Playlist(name) => {
+                let playlist = match self.playlist_library.get(name) {
+                    Ok(playlist) => playlist,
+                    Err(err) => {
+                        warn!("unable to pick idle song with error: {}", err);
+                        return Vec::new();
+                    }
+                };
+
+                let video_ids: HashSet<String> = playlist
+                    .entries
+                    .iter()
+                    .filter_map(|entry| extract_video_id(&entry.yt_link))
+                    .collect();
+                let names: HashSet<String> = playlist
+                    .entries
+                    .iter()
+                    .map(|entry| entry.song_name())
+                    .collect();
+
+                videos
+                    .into_iter()
+                    .filter(|video| {
+                        names.contains(&video.name)
+                            || video
+                                .details
+                                .video_id
+                                .as_ref()
+                                .is_some_and(|video_id| video_ids.contains(video_id))
+                    })
+                    .collect()
             }
-            SongActorMessage::GetQueue { respond_to } => {
-                let _ = respond_to.send(Ok(self.song_deque.clone()));
+        }
+    }
+
+    /// Picks a song for idle mode, steering clear of the one that just played if there's
+    /// anything else to choose from.
+    fn pick_idle_song(&self) -> Option<IdleSong> {
+        let mut candidates = self.idle_candidates();
+        if let Some((last, _)) = &self.idle_song {
+            if candidates.len() > 1 {
+                candidates.retain(|video| video.folder != last.folder);
             }
-            SongActorMessage::KeyUp { respond_to } => {
-                if self.current_key >= 3 {
-                    // TODO fix this and grab it from some settings descriptor
-                    let _ = respond_to.send(Err(SongCoordinatorError::KeyUpFailed));
-                } else {
-                    self.current_key += 1;
-                    let _ = self.sse_broadcaster.send(SseEvent::KeyChange {
-                        current_key: self.current_key,
-                    });
+        }
 
-                    let _ = respond_to.send(Ok(self.current_key));
+        let video = candidates.choose(&mut rand::thread_rng())?.clone();
+        Some(IdleSong {
+            uuid: Uuid::new_v4().to_string(),
+            folder: video.folder,
+            name: video.name,
+            is_key_changeable: video.is_key_changeable,
+            video_id: video.details.video_id,
+            duration_seconds: video.details.duration_seconds,
+            thumbnail_url: video.details.thumbnail_url,
+            uploader: video.details.uploader,
+        })
+    }
+
+    /// Moves idle mode on to its next song. With nothing in the cache to play, it waits
+    /// another timeout before looking again.
+    fn next_idle_song(&mut self) {
+        match self.pick_idle_song() {
+            Some(song) => {
+                info!("playing idle song: {}", song.name);
+                self.videodl_actor_handle
+                    .media_cache()
+                    .record_played(&song.folder);
+                self.idle_song = Some((song, Instant::now()));
+            }
+            None => {
+                info!("no cached songs to play in idle mode");
+                self.idle_song = None;
+                self.idle_since = Some(Instant::now());
+            }
+        }
+        self.broadcast_idle_song();
+    }
+
+    /// When idle mode has to act next: the first song once the queue has been empty long
+    /// enough, then the end of each song in case the display never says it's done.
+    fn idle_deadline(&self) -> Option<Instant> {
+        if !self.idle_mode.enabled || !self.song_deque.is_empty() || self.stage.holds_playback() {
+            return None;
+        }
+
+        match &self.idle_song {
+            Some((song, started)) => {
+                let duration = Duration::from_secs_f64(song.duration_seconds?.max(0.0));
+                Some(*started + duration + AUTO_ADVANCE_GRACE)
+            }
+            None => {
+                Some(self.idle_since? + Duration::from_secs(self.idle_mode.after_seconds as u64))
+            }
+        }
+    }
+
+    fn skip_tally(&self) -> SkipVotes {
+        SkipVotes {
+            song_uuid: self.song_deque.front().map(|song| song.uuid.to_string()),
+            votes: self.skip_votes.len() as u32,
+            needed: self
+                .skip_threshold
+                .votes_needed(self.sse_broadcaster.receiver_count()),
+        }
+    }
+
+    fn own_songs(&self, requester: &str) -> OwnSongs {
+        let songs = || {
+            self.song_deque
+                .iter()
+                .chain(self.pending_songs.iter())
+                .chain(self.parked_songs.iter())
+        };
+
+        OwnSongs {
+            requested: songs()
+                .filter(|song| song.requester.as_deref() == Some(requester))
+                .map(|song| song.uuid.to_string())
+                .collect(),
+            joined: songs()
+                .filter(|song| {
+                    song.joined
+                        .iter()
+                        .any(|performer| performer.requester == requester)
+                })
+                .map(|song| song.uuid.to_string())
+                .collect(),
+        }
+    }
+
+    fn broadcast_pending(&self) {
+        let _ = self.sse_broadcaster.send(SseEvent::PendingUpdated {
+            pending: self.pending_songs.clone(),
+        });
+    }
+
+    fn broadcast_playback(&self) {
+        if let Err(err) = self.sse_broadcaster.send(SseEvent::PlaybackChanged {
+            playback: self.playback.current(),
+        }) {
+            warn!(
+                "failed to broadcast SSE event for playback change with error: {}",
+                err
+            );
+        }
+    }
+
+    fn index_of(&self, song_uuid: Uuid) -> Option<usize> {
+        self.song_deque
+            .iter()
+            .position(|song| song.uuid == song_uuid)
+    }
+
+    fn song_order(&self) -> Vec<Uuid> {
+        self.song_deque.iter().map(|song| song.uuid).collect()
+    }
+
+    fn take_song(&mut self, song_uuid: Uuid) -> Option<QueueOperation> {
+        let index = self.index_of(song_uuid)?;
+        let after = index
+            .checked_sub(1)
+            .and_then(|previous| self.song_deque.get(previous))
+            .map(|song| song.uuid);
+        let song = self.song_deque.remove(index)?;
+        self.cancel_download(&song);
+
+        Some(QueueOperation::Removed { song, index, after })
+    }
+
+    /// Stops the download of a song that's no longer queued, so it doesn't keep one of
+    /// the downloader's consumers busy. [`QueueOperation::Removed`] etc. keep the song
+    /// as it was, still downloading, which is how undo knows to start it over.
+    fn cancel_download(&mut self, song: &Song) {
+        if song.status == QueuedSongStatus::InProgress {
+            self.videodl_actor_handle.cancel_download(song.uuid);
+            self.download_progress.remove(&song.uuid);
+        }
+    }
+
+    /// Puts a removed song back right after the song that used to precede it, or at its
+    /// old index if that song is gone as well.
+    fn restore_song(&mut self, song: Song, index: usize, after: Option<Uuid>) {
+        let position = match after {
+            None => 0,
+            Some(after) => match self.index_of(after) {
+                Some(previous) => previous + 1,
+                None => index.min(self.song_deque.len()),
+            },
+        };
+        self.song_deque.insert(position, song);
+    }
+
+    /// Moves a song and returns the index it was moved from.
+    fn move_song(&mut self, song_uuid: Uuid, position: usize) -> Option<usize> {
+        let from = self.index_of(song_uuid)?;
+        let song = self.song_deque.remove(from)?;
+        let new_position = position.min(self.song_deque.len());
+        self.song_deque.insert(new_position, song);
+
+        Some(from)
+    }
+
+    fn pop_current(&mut self) -> Option<Song> {
+        let finished_song = self.song_deque.pop_front();
+
+        if let Some(finished_song) = &finished_song {
+            // skipped before it was even ready
+            self.cancel_download(finished_song);
+            for singer_key in finished_song.singer_keys() {
+                *self.turns_taken.entry(singer_key.to_string()).or_insert(0) += 1;
+            }
+            self.record_played(finished_song);
+        }
+
+        finished_song
+    }
+
+    /// Gets a playable song to the front. Failed downloads in the way are dropped or
+    /// parked; if the songs in the way are still downloading, the first ready song after
+    /// them jumps ahead and they stay where they are.
+    fn skip_unplayable(&mut self) -> Vec<SkippedSong> {
+        let mut skipped = Vec::new();
+
+        let mut index = 0;
+        while let Some(song) = self.song_deque.get(index) {
+            match song.status {
+                QueuedSongStatus::Success => break,
+                QueuedSongStatus::InProgress => index += 1,
+                QueuedSongStatus::Failed => {
+                    let Some(song) = self.song_deque.remove(index) else {
+                        break;
+                    };
+                    let reason = match self.failed_song_policy {
+                        FailedSongPolicy::Drop => SkipReason::DroppedFailed,
+                        FailedSongPolicy::Park => {
+                            self.parked_songs.push(song.clone());
+                            SkipReason::ParkedFailed
+                        }
+                    };
+                    skipped.push(SkippedSong { song, reason });
                 }
             }
-            SongActorMessage::KeyDown { respond_to } => {
-                if self.current_key <= -3 {
-                    // TODO fix this and grab it from some settings descriptor
-                    let _ = respond_to.send(Err(SongCoordinatorError::KeyDownFailed));
-                } else {
-                    self.current_key -= 1;
-                    let _ = self.sse_broadcaster.send(SseEvent::KeyChange {
-                        current_key: self.current_key,
-                    });
+        }
 
-                    let _ = respond_to.send(Ok(self.current_key));
+        // nothing ready yet, so the first download stays up front and the TV waits on it
+        if index == 0 || index >= self.song_deque.len() {
+            return skipped;
+        }
+
+        skipped.extend(
+            self.song_deque
+                .iter()
+                .take(index)
+                .cloned()
+                .map(|song| SkippedSong {
+                    song,
+                    reason: SkipReason::StillDownloading,
+                }),
+        );
+        if let Some(ready_song) = self.song_deque.remove(index) {
+            self.song_deque.push_front(ready_song);
+        }
+
+        skipped
+    }
+
+    /// [`Self::skip_unplayable`], telling everyone which songs got moved out of the way.
+    /// Returns whether anything was skipped.
+    fn skip_unplayable_and_report(&mut self) -> bool {
+        let skipped = self.skip_unplayable();
+        if skipped.is_empty() {
+            return false;
+        }
+
+        for skipped_song in &skipped {
+            info!("skipped {} ({})", skipped_song.song, skipped_song.reason);
+        }
+        let _ = self
+            .sse_broadcaster
+            .send(SseEvent::SongsSkipped { skipped });
+        true
+    }
+
+    /// Finishes the current song and moves on to the next one, the same way whether the
+    /// display asked for it or the audience voted it off.
+    fn advance(&mut self) -> Option<Song> {
+        let key = self.current_key;
+        let finished_song = self.pop_current();
+
+        if let Some(song) = &finished_song {
+            self.undo_history.record(QueueOperation::Popped {
+                song: song.clone(),
+                key,
+            });
+        }
+
+        self.skip_unplayable_and_report();
+
+        self.stage = self.pause_before_next_song();
+        self.track_current();
+        self.apply_stage();
+        self.queue_changed();
+
+        finished_song
+    }
+
+    fn current_duration(&self) -> Option<f64> {
+        self.song_deque
+            .front()
+            .filter(|song| song.status == QueuedSongStatus::Success)
+            .and_then(|song| song.duration_seconds)
+    }
+
+    fn queue_entries(&self) -> Vec<QueueEntry> {
+        // when the current song started going by its last known position, which stays
+        // put while it's paused
+        let current_started_at = match self.song_deque.front() {
+            Some(song) if self.playback.belongs_to(song.uuid) => {
+                self.playback.updated_at as f64 - self.playback.position_seconds * 1000.0
+            }
+            _ => unix_timestamp_millis() as f64,
+        };
+
+        let mut starts_at = Some(current_started_at);
+        self.song_deque
+            .iter()
+            .enumerate()
+            .map(|(index, song)| {
+                let failed = song.status == QueuedSongStatus::Failed;
+                let entry = QueueEntry {
+                    song: song.clone(),
+                    starts_at: starts_at
+                        .filter(|_| !failed)
+                        .map(|starts_at| starts_at.max(0.0) as u64),
+                    performers: song.performer_names(),
+                    progress: self.download_progress.get(&song.uuid).copied(),
+                };
+
+                // failed songs get skipped, so they don't hold anything up
+                if !failed {
+                    let duration = match index {
+                        0 => self.current_duration(),
+                        _ => song.duration_seconds,
+                    };
+                    starts_at = starts_at
+                        .zip(duration)
+                        .map(|(start, duration)| start + duration * 1000.0);
                 }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn queue_state(&self) -> QueueState {
+        QueueState {
+            revision: self.revision,
+            queue: self.queue_entries(),
+        }
+    }
+
+    /// Turns a change down if it was made against a different revision of the queue than
+    /// the current one. Changes that don't say which revision they expect always go ahead.
+    fn check_revision(&self, expected_revision: Option<u64>) -> Result<(), SongCoordinatorError> {
+        match expected_revision {
+            Some(expected) if expected != self.revision => {
+                Err(SongCoordinatorError::StaleQueueRevision {
+                    expected,
+                    current: self.queue_state(),
+                })
             }
-            SongActorMessage::GetKey { respond_to } => {
-                let _ = respond_to.send(Ok(self.current_key));
+            _ => Ok(()),
+        }
+    }
+
+    fn reached_end_of_song(&self) -> bool {
+        self.current_duration().is_some_and(|duration| {
+            self.playback.position_now() >= duration - END_OF_SONG_TOLERANCE_SECONDS
+        })
+    }
+
+    /// When to give up on the display and advance by ourselves: the end of the current
+    /// song plus a grace period, going by the last known position. `None` while paused
+    /// or when we don't know how long the song is.
+    fn auto_advance_deadline(&self) -> Option<Instant> {
+        let duration = self.current_duration()?;
+        if self.playback.status != PlaybackStatus::Playing {
+            return None;
+        }
+
+        // recomputed after every message, so the grace period has to come off an
+        // overrun instead of being granted afresh each time
+        let remaining = duration - self.playback.position_now() + AUTO_ADVANCE_GRACE.as_secs_f64();
+        Some(Instant::now() + Duration::from_secs_f64(remaining.max(0.0)))
+    }
+
+    fn undo_operation(
+        &mut self,
+        operation: QueueOperation,
+    ) -> Result<QueueOperation, SongCoordinatorError> {
+        match operation {
+            QueueOperation::Removed { song, index, after } => {
+                if self.index_of(song.uuid).is_some() {
+                    return Err(SongCoordinatorError::UndoFailed);
+                }
+
+                self.restore_song(song.clone(), index, after);
+                Ok(QueueOperation::Removed { song, index, after })
             }
-            SongActorMessage::UpdateSongStatus {
+            QueueOperation::Repositioned {
                 song_uuid,
-                status,
-                respond_to,
+                from,
+                to,
             } => {
-                if let Some(song) = self
-                    .song_deque
-                    .iter_mut()
-                    .find(|song| song.uuid == song_uuid)
+                self.move_song(song_uuid, from)
+                    .ok_or(SongCoordinatorError::UndoFailed)?;
+                Ok(QueueOperation::Repositioned {
+                    song_uuid,
+                    from,
+                    to,
+                })
+            }
+            QueueOperation::Popped { song, key } => {
+                if self.index_of(song.uuid).is_some() {
+                    return Err(SongCoordinatorError::UndoFailed);
+                }
+
+                self.song_deque.push_front(song.clone());
+                self.current_key = key;
+
+                // the skipped song shouldn't count as a turn or show up as played
+                for singer_key in song.singer_keys() {
+                    if let Some(turns) = self.turns_taken.get_mut(singer_key) {
+                        *turns = turns.saturating_sub(1);
+                    }
+                }
+                if self
+                    .history
+                    .last()
+                    .is_some_and(|played| played.uuid == song.uuid.to_string())
                 {
-                    song.status = status;
+                    self.history.pop();
+                    self.persist_history();
+                }
+
+                Ok(QueueOperation::Popped { song, key })
+            }
+            QueueOperation::Cleared { songs } => {
+                let songs: Vec<Song> = songs
+                    .into_iter()
+                    .filter(|song| self.index_of(song.uuid).is_none())
+                    .collect();
+                if songs.is_empty() {
+                    return Err(SongCoordinatorError::UndoFailed);
+                }
+
+                // back in right behind the current song, ahead of anything queued since
+                let position = self.song_deque.len().min(1);
+                for (offset, song) in songs.iter().cloned().enumerate() {
+                    self.song_deque.insert(position + offset, song);
+                }
+
+                Ok(QueueOperation::Cleared { songs })
+            }
+        }
+    }
+
+    fn redo_operation(
+        &mut self,
+        operation: QueueOperation,
+    ) -> Result<QueueOperation, SongCoordinatorError> {
+        match operation {
+            QueueOperation::Removed { song, .. } => self
+                .take_song(song.uuid)
+                .ok_or(SongCoordinatorError::RedoFailed),
+            QueueOperation::Repositioned { song_uuid, to, .. } => {
+                let from = self
+                    .move_song(song_uuid, to)
+                    .ok_or(SongCoordinatorError::RedoFailed)?;
+                Ok(QueueOperation::Repositioned {
+                    song_uuid,
+                    from,
+                    to,
+                })
+            }
+            QueueOperation::Popped { song, .. } => {
+                if self.song_deque.front().map(|current| current.uuid) != Some(song.uuid) {
+                    return Err(SongCoordinatorError::RedoFailed);
+                }
+
+                let key = self.current_key;
+                let song = self.pop_current().ok_or(SongCoordinatorError::RedoFailed)?;
+                Ok(QueueOperation::Popped { song, key })
+            }
+            QueueOperation::Cleared { songs } => {
+                let songs: Vec<Song> = songs
+                    .into_iter()
+                    .filter_map(|song| match self.take_song(song.uuid) {
+                        Some(QueueOperation::Removed { song, .. }) => Some(song),
+                        _ => None,
+                    })
+                    .collect();
+                if songs.is_empty() {
+                    return Err(SongCoordinatorError::RedoFailed);
+                }
+
+                Ok(QueueOperation::Cleared { songs })
+            }
+        }
+    }
+
+    /// Turns a singer has had so far, counting the song that is playing right now.
+    fn turns_of(&self, singer_key: &str) -> u32 {
+        let finished = self.turns_taken.get(singer_key).copied().unwrap_or(0);
+        let singing_now = self
+            .song_deque
+            .front()
+            .is_some_and(|song| song.singer_keys().any(|key| key == singer_key));
+
+        finished + singing_now as u32
+    }
+
+    /// The round each upcoming song is due in under fair ordering: a singer's next song
+    /// goes in the round after all the turns they've already had. A shared song waits
+    /// for whichever of its singers is due last.
+    fn fair_rounds(&self) -> Vec<u32> {
+        let mut upcoming_per_singer: HashMap<&str, u32> = HashMap::new();
+
+        self.song_deque
+            .iter()
+            .skip(1)
+            .map(|song| {
+                let round = song
+                    .singer_keys()
+                    .map(|key| {
+                        self.turns_of(key) + upcoming_per_singer.get(key).copied().unwrap_or(0)
+                    })
+                    .max()
+                    .unwrap_or(0);
+                for key in song.singer_keys() {
+                    *upcoming_per_singer.entry(key).or_insert(0) += 1;
+                }
+                round
+            })
+            .collect()
+    }
+
+    fn is_queued(&self, song: &Song) -> bool {
+        self.song_deque
+            .iter()
+            .chain(self.pending_songs.iter())
+            .any(|queued| queued.canonical_id() == song.canonical_id())
+    }
+
+    /// Checks the song against the repeat policy, looking at when it last finished.
+    fn played_too_recently(&self, song: &Song) -> bool {
+        let policy = self.repeat_policy;
+        if policy.min_songs_between.is_none() && policy.min_minutes_between.is_none() {
+            return false;
+        }
+
+        let Some(last_played) = self.history.iter().rposition(|played| {
+            played.video_id.as_deref().unwrap_or(&played.yt_link) == song.canonical_id()
+        }) else {
+            return false;
+        };
+
+        let songs_since = (self.history.len() - last_played - 1) as u32;
+        let minutes_since =
+            unix_timestamp().saturating_sub(self.history[last_played].ended_at) / 60;
+
+        let enough_songs = policy
+            .min_songs_between
+            .is_some_and(|min_songs| songs_since >= min_songs);
+        let enough_minutes = policy
+            .min_minutes_between
+            .is_some_and(|min_minutes| minutes_since >= min_minutes);
+
+        !(enough_songs || enough_minutes)
+    }
+
+    fn check_queue_limits(&mut self, song: &Song) -> Result<(), QueueLimitViolation> {
+        let limits = self.queue_limits;
+
+        if let Some(max_queue_length) = limits.max_queue_length {
+            if self.song_deque.len() >= max_queue_length as usize {
+                return Err(QueueLimitViolation::MaxQueueLength { max_queue_length });
+            }
+        }
+
+        let Some(requester) = song.requester.as_deref() else {
+            return Ok(());
+        };
+
+        self.check_songs_per_requester(requester)?;
+
+        if let Some(max_requests_per_hour) = limits.max_requests_per_hour {
+            let now = unix_timestamp();
+            if let Some(requests) = self.recent_requests.get_mut(requester) {
+                while requests
+                    .front()
+                    .is_some_and(|requested_at| requested_at + 3600 <= now)
+                {
+                    requests.pop_front();
+                }
+
+                if requests.len() >= max_requests_per_hour as usize {
+                    let oldest = requests.front().copied().unwrap_or(now);
+                    return Err(QueueLimitViolation::MaxRequestsPerHour {
+                        max_requests_per_hour,
+                        retry_after_seconds: (oldest + 3600).saturating_sub(now),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Songs someone has joined count against their limit the same as songs they queued,
+    /// since they're on stage for those as well.
+    fn check_songs_per_requester(&self, requester: &str) -> Result<(), QueueLimitViolation> {
+        let Some(max_songs_per_requester) = self.queue_limits.max_songs_per_requester else {
+            return Ok(());
+        };
+
+        let queued = self
+            .song_deque
+            .iter()
+            .chain(self.pending_songs.iter())
+            .filter(|queued| queued.is_performed_by(requester))
+            .count();
+        if queued >= max_songs_per_requester as usize {
+            return Err(QueueLimitViolation::MaxSongsPerRequester {
+                max_songs_per_requester,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn join_song(
+        &mut self,
+        song_uuid: Uuid,
+        performer: Performer,
+    ) -> Result<(), SongCoordinatorError> {
+        let index = self
+            .index_of(song_uuid)
+            .ok_or(SongCoordinatorError::JoinSongFailed { uuid: song_uuid })?;
+
+        let song = &self.song_deque[index];
+        if song.is_performed_by(&performer.requester)
+            || song
+                .performer_names()
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&performer.name))
+        {
+            return Err(SongCoordinatorError::AlreadyPerforming { uuid: song_uuid });
+        }
+        if song.joined.len() + 1 >= MAX_PERFORMERS {
+            return Err(SongCoordinatorError::TooManyPerformers {
+                max_performers: MAX_PERFORMERS,
+            });
+        }
+        self.check_songs_per_requester(&performer.requester)?;
+
+        self.song_deque[index].joined.push(performer);
+        self.reslot_joined(index);
+        Ok(())
+    }
+
+    fn leave_song(&mut self, song_uuid: Uuid, requester: &str) -> Result<(), SongCoordinatorError> {
+        let joined = self
+            .song_deque
+            .iter_mut()
+            .find(|song| song.uuid == song_uuid)
+            .map(|song| &mut song.joined)
+            .ok_or(SongCoordinatorError::LeaveSongFailed { uuid: song_uuid })?;
+
+        let before = joined.len();
+        joined.retain(|performer| performer.requester != requester);
+        if joined.len() == before {
+            return Err(SongCoordinatorError::LeaveSongFailed { uuid: song_uuid });
+        }
+
+        Ok(())
+    }
+
+    /// Puts a failed song back to downloading, from the queue or from the parked songs.
+    fn retry_song(&mut self, song_uuid: Uuid) -> Result<Song, SongCoordinatorError> {
+        let song = match self.index_of(song_uuid) {
+            Some(index) => {
+                let song = &mut self.song_deque[index];
+                if song.status != QueuedSongStatus::Failed {
+                    return Err(SongCoordinatorError::SongNotFailed { uuid: song_uuid });
+                }
+                song
+            }
+            None => {
+                let index = self
+                    .parked_songs
+                    .iter()
+                    .position(|song| song.uuid == song_uuid)
+                    .ok_or(SongCoordinatorError::RetrySongFailed { uuid: song_uuid })?;
+                let song = self.parked_songs.remove(index);
+                self.enqueue(song);
+                let index = self
+                    .index_of(song_uuid)
+                    .ok_or(SongCoordinatorError::RetrySongFailed { uuid: song_uuid })?;
+                &mut self.song_deque[index]
+            }
+        };
+
+        song.status = QueuedSongStatus::InProgress;
+        song.download_attempts = 0;
+        song.download_error = None;
+        Ok(song.clone())
+    }
+
+    fn record_request(&mut self, song: &Song) {
+        if let Some(requester) = &song.requester {
+            self.recent_requests
+                .entry(requester.clone())
+                .or_default()
+                .push_back(unix_timestamp());
+        }
+    }
+
+    fn enqueue(&mut self, song: Song) {
+        if self.ordering == QueueOrdering::Fifo || self.song_deque.is_empty() {
+            self.song_deque.push_back(song);
+            return;
+        }
+
+        let position = self.fair_position(&song);
+        self.song_deque.insert(position, song);
+    }
+
+    /// Where a song not in the queue goes under fair ordering: after everyone else who
+    /// is due in the same round, going by whichever of its singers is due last.
+    fn fair_position(&self, song: &Song) -> usize {
+        let rounds = self.fair_rounds();
+        let round = song
+            .singer_keys()
+            .map(|singer_key| {
+                let upcoming_for_singer = self
+                    .song_deque
+                    .iter()
+                    .skip(1)
+                    .filter(|queued| queued.singer_keys().any(|key| key == singer_key))
+                    .count() as u32;
+                self.turns_of(singer_key) + upcoming_for_singer
+            })
+            .max()
+            .unwrap_or(0);
+
+        match rounds.iter().position(|&queued_round| queued_round > round) {
+            Some(index) => index + 1,
+            None => self.song_deque.len(),
+        }
+    }
+
+    /// Under fair ordering, moves a song back to the round its new singers are due in.
+    /// It never moves ahead, so a song the host put further back stays there.
+    fn reslot_joined(&mut self, index: usize) {
+        if self.ordering != QueueOrdering::Fair || index == 0 {
+            return;
+        }
+
+        let Some(song) = self.song_deque.remove(index) else {
+            return;
+        };
+        let position = self.fair_position(&song).max(index);
+        self.song_deque.insert(position, song);
+    }
+
+    /// Re-sorts everything behind the current song into round-robin order. The sort is
+    /// stable, so songs due in the same round keep their relative order.
+    fn apply_fair_order(&mut self) {
+        if self.song_deque.len() < 2 {
+            return;
+        }
+
+        let rounds = self.fair_rounds();
+        let current = self.song_deque.pop_front();
+        let mut upcoming: Vec<(u32, Song)> =
+            rounds.into_iter().zip(self.song_deque.drain(..)).collect();
+        upcoming.sort_by_key(|(round, _)| *round);
+
+        self.song_deque = current
+            .into_iter()
+            .chain(upcoming.into_iter().map(|(_, song)| song))
+            .collect();
+    }
+
+    fn persist(&self) {
+        if self.closed {
+            return;
+        }
+
+        // every change to the queue or the pending songs ends up here
+        self.claim_cached_songs();
+
+        let snapshot = QueueSnapshot {
+            version: QUEUE_SNAPSHOT_VERSION,
+            current_key: self.current_key,
+            song_deque: self.song_deque.clone(),
+            ordering: self.ordering,
+            turns_taken: self.turns_taken.clone(),
+            repeat_policy: self.repeat_policy,
+            queue_limits: self.queue_limits,
+            skip_threshold: self.skip_threshold,
+            failed_song_policy: self.failed_song_policy,
+            parked_songs: self.parked_songs.clone(),
+            moderation: self.moderation,
+            pending_songs: self.pending_songs.clone(),
+            revision: self.revision,
+            interstitial: self.interstitial,
+            scheduled_breaks: self.scheduled_breaks.clone(),
+            idle_mode: self.idle_mode.clone(),
+            singing_since: self.current_started.and_then(|(uuid, started_at)| {
+                started_at.map(|started_at| (uuid.to_string(), started_at))
+            }),
+            requesters: self
+                .song_deque
+                .iter()
+                .chain(self.pending_songs.iter())
+                .chain(self.parked_songs.iter())
+                .filter_map(|song| {
+                    let requester = song.requester.clone()?;
+                    Some((song.uuid.to_string(), requester))
+                })
+                .collect(),
+            joined_requesters: self
+                .song_deque
+                .iter()
+                .chain(self.pending_songs.iter())
+                .chain(self.parked_songs.iter())
+                .filter(|song| !song.joined.is_empty())
+                .map(|song| {
+                    let requesters = song
+                        .joined
+                        .iter()
+                        .map(|performer| performer.requester.clone())
+                        .collect();
+                    (song.uuid.to_string(), requesters)
+                })
+                .collect(),
+        };
+
+        if let Err(err) = self.snapshot_store.save(&snapshot) {
+            error!("failed to persist song queue with error: {}", err);
+        }
+    }
+
+    async fn handle_message(&mut self, msg: SongActorMessage) {
+        match msg {
+            SongActorMessage::QueueSong { song, respond_to } => {
+                if self.is_queued(&song) {
+                    let _ = respond_to.send(Err(SongCoordinatorError::SongAlreadyQueued {
+                        name: song.name,
+                    }));
+                } else if self.played_too_recently(&song) {
+                    let _ = respond_to.send(Err(SongCoordinatorError::SongPlayedTooRecently {
+                        name: song.name,
+                    }));
+                } else if let Err(violation) = self.check_queue_limits(&song) {
+                    let _ = respond_to.send(Err(violation.into()));
+                } else if self.moderation {
+                    self.record_request(&song);
+                    self.pending_songs.push(song);
+                    self.persist();
+                    self.broadcast_pending();
+                    let _ = respond_to.send(Ok(QueueOutcome::Pending));
+                } else {
+                    self.record_request(&song);
+                    self.enqueue(song);
+                    self.track_current();
+                    self.queue_changed();
+                    let _ = respond_to.send(Ok(QueueOutcome::Queued));
+                }
+            }
+            SongActorMessage::RemoveSong {
+                song_uuid,
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                let Some(operation) = self.take_song(song_uuid) else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::RemoveSongFailed {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                };
+
+                self.undo_history.record(operation);
+                self.track_current();
+                self.queue_changed();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::PopSong {
+                expected_current,
+                respond_to,
+            } => {
+                // the server may already have moved on by itself, in which case the
+                // display's late request must not skip the song after it
+                if expected_current
+                    .is_some_and(|uuid| self.song_deque.front().map(|song| song.uuid) != Some(uuid))
+                {
+                    let _ = respond_to.send(None);
+                    return;
+                }
+
+                let next_song = self.advance();
+                let _ = respond_to.send(next_song);
+            }
+            SongActorMessage::Reposition {
+                song_uuid,
+                position,
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                let Some(from) = self.move_song(song_uuid, position) else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::RepositionSongFailed {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                };
+
+                self.undo_history.record(QueueOperation::Repositioned {
+                    song_uuid,
+                    from,
+                    to: position,
+                });
+                self.track_current();
+                self.queue_changed();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::ClearQueue {
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                // the song that's playing stays, everything waiting behind it goes
+                if self.song_deque.len() > 1 {
+                    let songs: Vec<Song> = self.song_deque.drain(1..).collect();
+                    for song in &songs {
+                        self.cancel_download(song);
+                    }
+                    self.undo_history.record(QueueOperation::Cleared { songs });
+                    self.queue_changed();
+                }
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::Undo {
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                let Some(operation) = self.undo_history.pop_undo() else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingToUndo));
+                    return;
+                };
+
+                match self.undo_operation(operation) {
+                    Ok(operation) => {
+                        // their downloads were cancelled when they left the queue
+                        let restarted = operation
+                            .songs()
+                            .filter(|song| song.status == QueuedSongStatus::InProgress)
+                            .cloned()
+                            .collect();
+                        self.undo_history.push_redo(operation);
+                        self.track_current();
+                        self.queue_changed();
+                        let _ = respond_to.send(Ok(restarted));
+                    }
+                    Err(err) => {
+                        let _ = respond_to.send(Err(err));
+                    }
+                }
+            }
+            SongActorMessage::Redo {
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                let Some(operation) = self.undo_history.pop_redo() else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingToRedo));
+                    return;
+                };
+
+                match self.redo_operation(operation) {
+                    Ok(operation) => {
+                        self.undo_history.push_undo(operation);
+                        self.track_current();
+                        self.queue_changed();
+                        let _ = respond_to.send(Ok(()));
+                    }
+                    Err(err) => {
+                        let _ = respond_to.send(Err(err));
+                    }
+                }
+            }
+            SongActorMessage::Current { respond_to } => {
+                let _ = respond_to.send(Ok(self.song_deque.front().cloned()));
+            }
+            SongActorMessage::GetQueue { respond_to } => {
+                let _ = respond_to.send(Ok(self.queue_state()));
+            }
+            SongActorMessage::KeyUp { respond_to } => {
+                if self.current_key >= 3 {
+                    // TODO fix this and grab it from some settings descriptor
+                    let _ = respond_to.send(Err(SongCoordinatorError::KeyUpFailed));
+                } else {
+                    self.current_key += 1;
+                    self.remember_key();
+                    self.persist();
+                    let _ = self.sse_broadcaster.send(SseEvent::KeyChange {
+                        current_key: self.current_key,
+                    });
+
+                    let _ = respond_to.send(Ok(self.current_key));
+                }
+            }
+            SongActorMessage::KeyDown { respond_to } => {
+                if self.current_key <= -3 {
+                    // TODO fix this and grab it from some settings descriptor
+                    let _ = respond_to.send(Err(SongCoordinatorError::KeyDownFailed));
+                } else {
+                    self.current_key -= 1;
+                    self.remember_key();
+                    self.persist();
+                    let _ = self.sse_broadcaster.send(SseEvent::KeyChange {
+                        current_key: self.current_key,
+                    });
+
+                    let _ = respond_to.send(Ok(self.current_key));
+                }
+            }
+            SongActorMessage::GetKey { respond_to } => {
+                let _ = respond_to.send(Ok(self.current_key));
+            }
+            SongActorMessage::SetOrdering {
+                ordering,
+                respond_to,
+            } => {
+                let order_before = self.song_order();
+                self.ordering = ordering;
+                if self.ordering == QueueOrdering::Fair {
+                    self.apply_fair_order();
+                }
+
+                // going back to FIFO leaves the songs where they are, and so can a queue
+                // that was already in round-robin order
+                if self.song_order() == order_before {
+                    self.queue_refreshed();
+                } else {
+                    self.queue_changed();
+                }
+
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetOrdering { respond_to } => {
+                let _ = respond_to.send(Ok(self.ordering));
+            }
+            SongActorMessage::GetHistory { respond_to } => {
+                let _ = respond_to.send(Ok(self.history.clone()));
+            }
+            SongActorMessage::ClearHistory { respond_to } => {
+                self.history.clear();
+                self.persist_history();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::SetRepeatPolicy {
+                repeat_policy,
+                respond_to,
+            } => {
+                self.repeat_policy = repeat_policy;
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetRepeatPolicy { respond_to } => {
+                let _ = respond_to.send(Ok(self.repeat_policy));
+            }
+            SongActorMessage::SetQueueLimits {
+                queue_limits,
+                respond_to,
+            } => {
+                self.queue_limits = queue_limits;
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetQueueLimits { respond_to } => {
+                let _ = respond_to.send(Ok(self.queue_limits));
+            }
+            SongActorMessage::GetStage { respond_to } => {
+                let _ = respond_to.send(Ok(self.stage_state()));
+            }
+            SongActorMessage::SkipStage { respond_to } => {
+                if !self.stage.holds_playback() {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NotBetweenSongs));
+                    return;
+                }
+
+                self.finish_stage();
+                let _ = respond_to.send(Ok(self.stage_state()));
+            }
+            SongActorMessage::ExtendStage {
+                seconds,
+                respond_to,
+            } => {
+                if !self.stage.extend(seconds) {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NotBetweenSongs));
+                    return;
+                }
+
+                self.broadcast_stage();
+                let _ = respond_to.send(Ok(self.stage_state()));
+            }
+            SongActorMessage::SetInterstitial {
+                interstitial,
+                respond_to,
+            } => {
+                self.interstitial = interstitial;
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetInterstitial { respond_to } => {
+                let _ = respond_to.send(Ok(self.interstitial));
+            }
+            SongActorMessage::GetBreaks { respond_to } => {
+                let _ = respond_to.send(Ok(self.scheduled_breaks.clone()));
+            }
+            SongActorMessage::ScheduleBreak {
+                starts_at,
+                minutes,
+                respond_to,
+            } => {
+                if minutes == 0 || minutes > MAX_BREAK_MINUTES {
+                    let _ = respond_to.send(Err(SongCoordinatorError::InvalidBreakLength {
+                        max_minutes: MAX_BREAK_MINUTES,
+                    }));
+                    return;
+                }
+
+                let scheduled = ScheduledBreak {
+                    id: Uuid::new_v4().to_string(),
+                    starts_at: starts_at.unwrap_or_else(unix_timestamp),
+                    minutes,
+                };
+                let index = self
+                    .scheduled_breaks
+                    .partition_point(|other| other.starts_at <= scheduled.starts_at);
+                self.scheduled_breaks.insert(index, scheduled.clone());
+                self.persist();
+
+                let _ = respond_to.send(Ok(scheduled));
+            }
+            SongActorMessage::CancelBreak { id, respond_to } => {
+                let before = self.scheduled_breaks.len();
+                self.scheduled_breaks.retain(|scheduled| scheduled.id != id);
+                if self.scheduled_breaks.len() == before {
+                    let _ = respond_to.send(Err(SongCoordinatorError::BreakNotFound { id }));
+                    return;
+                }
+
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetIdleMode { respond_to } => {
+                let _ = respond_to.send(Ok(self.idle_mode.clone()));
+            }
+            SongActorMessage::SetIdleMode {
+                idle_mode,
+                respond_to,
+            } => {
+                self.idle_mode = idle_mode;
+                self.persist();
+                if !self.idle_mode.enabled {
+                    self.stop_idle_song();
+                }
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetIdleSong { respond_to } => {
+                let _ = respond_to.send(Ok(self.idle_song()));
+            }
+            SongActorMessage::NextIdleSong {
+                expected_current,
+                respond_to,
+            } => {
+                let Some((current, _)) = &self.idle_song else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingPlaying));
+                    return;
+                };
+
+                let is_expected =
+                    expected_current.is_none_or(|expected| expected.to_string() == current.uuid);
+                if is_expected {
+                    self.next_idle_song();
+                }
+                let _ = respond_to.send(Ok(self.idle_song()));
+            }
+            SongActorMessage::JoinSong {
+                song_uuid,
+                performer,
+                respond_to,
+            } => {
+                let index = self.index_of(song_uuid);
+                let response = self.join_song(song_uuid, performer);
+                if response.is_ok() {
+                    // fair ordering can move the song back for its new singers
+                    if self.index_of(song_uuid) == index {
+                        self.queue_refreshed();
+                    } else {
+                        self.queue_changed();
+                    }
+                }
+                let _ = respond_to.send(response);
+            }
+            SongActorMessage::LeaveSong {
+                song_uuid,
+                requester,
+                respond_to,
+            } => {
+                let response = self.leave_song(song_uuid, &requester);
+                if response.is_ok() {
+                    self.queue_refreshed();
+                }
+                let _ = respond_to.send(response);
+            }
+            SongActorMessage::Close { respond_to } => {
+                self.closed = true;
+                self.videodl_actor_handle
+                    .media_cache()
+                    .release(self.media_cache_owner);
+                let songs: Vec<Song> = self
+                    .song_deque
+                    .drain(..)
+                    .chain(self.pending_songs.drain(..))
+                    .collect();
+                for song in &songs {
+                    self.cancel_download(song);
+                }
+                self.parked_songs.clear();
+                self.pending_songs.clear();
+                self.history.clear();
+                self.key_memory.clear();
+                self.undo_history.clear();
+
+                for store in [
+                    &self.snapshot_store,
+                    &self.history_store,
+                    &self.key_memory_store,
+                ] {
+                    if let Err(err) = store.remove() {
+                        error!(
+                            "failed to remove snapshot of closed room with error: {}",
+                            err
+                        );
+                    }
+                }
+
+                let _ = respond_to.send(());
+            }
+            SongActorMessage::UpdateSongStatus {
+                song_uuid,
+                status,
+                details,
+                error,
+                respond_to,
+            } => {
+                self.download_progress.remove(&song_uuid);
+                if let Some(song) = self
+                    .song_deque
+                    .iter_mut()
+                    .chain(self.pending_songs.iter_mut())
+                    .find(|song| song.uuid == song_uuid)
+                {
+                    song.status = status;
+                    if let Some(details) = details {
+                        song.apply_details(details);
+                    }
+                    // retries are reported as they start, the first attempt isn't
+                    song.download_attempts = song.download_attempts.max(1);
+                    if error.is_some() {
+                        song.download_error = error;
+                    }
+
+                    // the display only starts the current song once it's ready, so
+                    // that's when its clock starts
+                    if song.status == QueuedSongStatus::Success
+                        && self.playback.belongs_to(song_uuid)
+                    {
+                        self.playback = self.fresh_playback(Some(song_uuid));
+                        self.broadcast_playback();
+                    }
+
+                    // the front song failing, or a later one becoming ready while the
+                    // front is still downloading, shouldn't leave the display waiting
+                    if self.skip_unplayable_and_report() {
+                        self.track_current();
+                        self.queue_changed();
+                    } else {
+                        self.queue_refreshed();
+                    }
+
+                    let _ = respond_to.send(Ok(()));
+                } else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::UpdateSongStatusFailed {
+                        uuid: (song_uuid),
+                    }));
+                }
+            }
+            SongActorMessage::ReportProgress {
+                song_uuid,
+                progress,
+                respond_to,
+            } => {
+                // progress can trail behind the download finishing or the song going away
+                let downloading =
+                    self.song_deque
+                        .iter()
+                        .chain(self.pending_songs.iter())
+                        .any(|song| {
+                            song.uuid == song_uuid && song.status == QueuedSongStatus::InProgress
+                        });
+                if !downloading {
+                    let _ = respond_to.send(Err(SongCoordinatorError::UpdateSongStatusFailed {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                }
+
+                self.download_progress.insert(song_uuid, progress);
+                let _ = self.sse_broadcaster.send(SseEvent::DownloadProgress {
+                    song_uuid: song_uuid.to_string(),
+                    phase: progress.phase,
+                    percent: progress.percent,
+                });
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::ReportRetry {
+                song_uuid,
+                attempt,
+                reason,
+                respond_to,
+            } => {
+                let Some(song) = self
+                    .song_deque
+                    .iter_mut()
+                    .chain(self.pending_songs.iter_mut())
+                    .find(|song| {
+                        song.uuid == song_uuid && song.status == QueuedSongStatus::InProgress
+                    })
+                else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::UpdateSongStatusFailed {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                };
+
+                info!(
+                    "retrying download for song: {}, attempt {} after: {}",
+                    song_uuid, attempt, reason
+                );
+                song.download_attempts = attempt;
+                song.download_error = Some(reason);
+                self.download_progress.remove(&song_uuid);
+                self.queue_refreshed();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::VoteSkip {
+                requester,
+                respond_to,
+            } => {
+                if self.song_deque.is_empty() {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingPlaying));
+                    return;
+                }
+                if !self.skip_votes.insert(requester) {
+                    let _ = respond_to.send(Err(SongCoordinatorError::AlreadyVotedToSkip));
+                    return;
+                }
+
+                let tally = self.skip_tally();
+                info!("skip votes: {}/{}", tally.votes, tally.needed);
+                let _ = self.sse_broadcaster.send(SseEvent::SkipVotesUpdated {
+                    skip_votes: tally.clone(),
+                });
+
+                if tally.votes >= tally.needed {
+                    if let Some(song) = self.advance() {
+                        info!("audience voted to skip song: {}", song);
+                    }
+                }
+                let _ = respond_to.send(Ok(tally));
+            }
+            SongActorMessage::GetSkipVotes { respond_to } => {
+                let _ = respond_to.send(Ok(self.skip_tally()));
+            }
+            SongActorMessage::GetOwnSongs {
+                requester,
+                respond_to,
+            } => {
+                let _ = respond_to.send(Ok(self.own_songs(&requester)));
+            }
+            SongActorMessage::SetSkipThreshold {
+                skip_threshold,
+                respond_to,
+            } => {
+                self.skip_threshold = skip_threshold;
+                self.persist();
+                let _ = self.sse_broadcaster.send(SseEvent::SkipVotesUpdated {
+                    skip_votes: self.skip_tally(),
+                });
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetSkipThreshold { respond_to } => {
+                let _ = respond_to.send(Ok(self.skip_threshold));
+            }
+            SongActorMessage::SetFailedSongPolicy {
+                failed_song_policy,
+                respond_to,
+            } => {
+                self.failed_song_policy = failed_song_policy;
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetFailedSongPolicy { respond_to } => {
+                let _ = respond_to.send(Ok(self.failed_song_policy));
+            }
+            SongActorMessage::GetParkedSongs { respond_to } => {
+                let _ = respond_to.send(Ok(self.parked_songs.clone()));
+            }
+            SongActorMessage::RetrySong {
+                song_uuid,
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                // only a parked song comes back into the queue, the rest stay put
+                let parked = self.index_of(song_uuid).is_none();
+                let response = self.retry_song(song_uuid);
+                if response.is_ok() {
+                    self.track_current();
+                    if parked {
+                        self.queue_changed();
+                    } else {
+                        self.queue_refreshed();
+                    }
+                }
+                let _ = respond_to.send(response);
+            }
+            SongActorMessage::SetModeration {
+                enabled,
+                respond_to,
+            } => {
+                self.moderation = enabled;
+                self.persist();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetModeration { respond_to } => {
+                let _ = respond_to.send(Ok(self.moderation));
+            }
+            SongActorMessage::GetPendingSongs { respond_to } => {
+                let _ = respond_to.send(Ok(self.pending_songs.clone()));
+            }
+            SongActorMessage::DecideRequest {
+                song_uuid,
+                decision,
+                position,
+                expected_revision,
+                respond_to,
+            } => {
+                if let Err(err) = self.check_revision(expected_revision) {
+                    let _ = respond_to.send(Err(err));
+                    return;
+                }
+
+                let Some(index) = self
+                    .pending_songs
+                    .iter()
+                    .position(|song| song.uuid == song_uuid)
+                else {
+                    let _ = respond_to.send(Err(SongCoordinatorError::PendingSongNotFound {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                };
+                let song = self.pending_songs.remove(index);
+                if decision == RequestDecision::Rejected {
+                    self.cancel_download(&song);
+                }
+
+                if decision == RequestDecision::Approved {
+                    match position {
+                        Some(position) => {
+                            let position = position.min(self.song_deque.len());
+                            self.song_deque.insert(position, song.clone());
+                        }
+                        None => self.enqueue(song.clone()),
+                    }
+                    self.track_current();
+                    self.queue_changed();
+                } else {
+                    self.persist();
+                }
+
+                info!("request {} for song: {}", decision, song);
+                self.broadcast_pending();
+                let _ = self
+                    .sse_broadcaster
+                    .send(SseEvent::RequestDecided { song, decision });
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetKeyMemory { respond_to } => {
+                let _ = respond_to.send(Ok(self.key_memory.clone()));
+            }
+            SongActorMessage::ForgetKeys {
+                song_id,
+                respond_to,
+            } => {
+                match song_id {
+                    Some(song_id) => {
+                        if !self.key_memory.forget(&song_id) {
+                            let _ = respond_to
+                                .send(Err(SongCoordinatorError::KeyNotRemembered { song_id }));
+                            return;
+                        }
+                    }
+                    None => self.key_memory.clear(),
+                }
+
+                self.persist_key_memory();
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::GetPlayback { respond_to } => {
+                let _ = respond_to.send(Ok(self.playback.current()));
+            }
+            SongActorMessage::SetPlaybackStatus { status, respond_to } => {
+                if self.song_deque.is_empty() {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingPlaying));
+                    return;
+                }
+
+                // playing during the countdown or a break means the host wants to get on
+                // with it
+                if status == PlaybackStatus::Playing && self.stage.holds_playback() {
+                    self.stage = Stage::default();
+                    self.apply_stage();
+                } else if self.playback.set_status(status) {
+                    self.broadcast_playback();
+                }
+                let _ = respond_to.send(Ok(self.playback.current()));
+            }
+            SongActorMessage::ReportPlayback {
+                song_uuid,
+                status,
+                position_seconds,
+                respond_to,
+            } => {
+                // a display that hasn't caught up with a skip yet would otherwise
+                // clobber the new song's state with the old one's
+                if !self.playback.belongs_to(song_uuid) {
+                    let _ = respond_to.send(Err(SongCoordinatorError::StalePlaybackReport {
+                        uuid: song_uuid,
+                    }));
+                    return;
+                }
+
+                // a display that started the song by itself gets told to wait its turn
+                let held = status == PlaybackStatus::Playing && self.stage.holds_playback();
+                let status = match held {
+                    true => PlaybackStatus::Paused,
+                    false => status,
+                };
+                if self.playback.report(status, position_seconds) || held {
+                    self.broadcast_playback();
+                }
+                if status == PlaybackStatus::Playing {
+                    self.start_singing(song_uuid);
+                }
+                if self.reached_end_of_song() {
+                    info!("display reported the end of the current song, moving on");
+                    self.advance();
+                }
+                let _ = respond_to.send(Ok(()));
+            }
+            SongActorMessage::RestartPlayback { respond_to } => {
+                if self.song_deque.is_empty() {
+                    let _ = respond_to.send(Err(SongCoordinatorError::NothingPlaying));
+                    return;
+                }
+
+                self.playback.restart();
+                if self.stage.holds_playback() {
+                    self.playback.set_status(PlaybackStatus::Paused);
+                }
+                let _ = self.sse_broadcaster.send(SseEvent::RestartSong);
+                self.broadcast_playback();
+                let _ = respond_to.send(Ok(()));
+            }
+        }
+    }
+}
+
+async fn run_song_actor(mut actor: SongActor) {
+    loop {
+        let deadline = actor.auto_advance_deadline();
+        let stage_deadline = actor.stage_deadline();
+        let idle_deadline = actor.idle_deadline();
+
+        tokio::select! {
+            msg = actor.receiver.recv() => match msg {
+                Some(msg) => actor.handle_message(msg).await,
+                None => break,
+            },
+            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                if let Some(song) = actor.advance() {
+                    warn!("display never finished song: {}, advancing without it", song);
+                }
+            }
+            _ = sleep_until(stage_deadline.unwrap_or_else(Instant::now)), if stage_deadline.is_some() => {
+                actor.finish_stage();
+            }
+            _ = sleep_until(idle_deadline.unwrap_or_else(Instant::now)), if idle_deadline.is_some() => {
+                actor.next_idle_song();
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SongActorHandle {
+    sender: mpsc::Sender<SongActorMessage>,
+    videodl_actor_handle: Arc<VideoDlActorHandle>,
+}
+
+impl SongActorHandle {
+    pub fn new(
+        sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+        videodl_actor_handle: Arc<VideoDlActorHandle>,
+        playlist_library: Arc<PlaylistLibrary>,
+        data_dir: &Path,
+    ) -> Self {
+        let snapshot_store = SnapshotStore::new(data_dir.join("song_queue.json"));
+
+        let snapshot = QueueSnapshot::load(&snapshot_store);
+        let interrupted_songs: Vec<Song> = snapshot
+            .song_deque
+            .iter()
+            .chain(snapshot.pending_songs.iter())
+            .filter(|song| song.status == QueuedSongStatus::InProgress)
+            .cloned()
+            .collect();
+
+        let (sender, receiver) = mpsc::channel(8);
+        let song_actor = SongActor::new(
+            receiver,
+            sse_broadcaster,
+            videodl_actor_handle.clone(),
+            playlist_library,
+            data_dir,
+            snapshot_store,
+            snapshot,
+        );
+        tokio::spawn(run_song_actor(song_actor));
+
+        let handle = Self {
+            sender,
+            videodl_actor_handle,
+        };
+
+        // downloads that were running when we went down have to start over
+        for song in interrupted_songs {
+            info!("requeueing interrupted download for song: {}", song);
+            handle.download_song(song);
+        }
+
+        handle
+    }
+
+    /// Downloads and processes a queued song in the background, updating its status
+    /// once the video downloader is done with it.
+    pub fn download_song(&self, song: Song) {
+        let song_actor_handle = self.clone();
+
+        let (update_sender, mut update_receiver) = mpsc::unbounded_channel();
+        let download = self.videodl_actor_handle.download_video(
+            song.uuid,
+            song.yt_link.clone(),
+            song.folder.clone(),
+            song.name.clone(),
+            song.is_key_changeable,
+            ProgressReporter::new(update_sender),
+        );
+        let progress_handle = self.clone();
+        let song_uuid = song.uuid;
+        tokio::spawn(async move {
+            while let Some(update) = update_receiver.recv().await {
+                let _ = match update {
+                    DownloadUpdate::Progress(progress) => {
+                        progress_handle.report_progress(song_uuid, progress).await
+                    }
+                    DownloadUpdate::Retrying { attempt, reason } => {
+                        progress_handle
+                            .report_retry(song_uuid, attempt, reason)
+                            .await
+                    }
+                };
+            }
+        });
+
+        tokio::spawn(async move {
+            match download.await {
+                Ok(DownloadedVideo {
+                    source_path,
+                    details,
+                }) => {
+                    info!("successfully downloaded video for song: {}", song.uuid);
+
+                    song_actor_handle
+                        .finish_download(song.uuid, QueuedSongStatus::Success, Some(details), None)
+                        .await;
+
+                    if let Some(video_file_path) = source_path {
+                        std::fs::remove_file(&video_file_path).unwrap_or_else(|err| {
+                            error!(
+                                "unable to delete file {} with error: {}",
+                                &video_file_path, err
+                            );
+                        });
+
+                        // only measured now the download it was processed from is gone
+                        let media_cache =
+                            song_actor_handle.videodl_actor_handle.media_cache().clone();
+                        let folder = song.folder.clone();
+                        let _ = tokio::task::spawn_blocking(move || {
+                            media_cache.record_processed(&folder)
+                        })
+                        .await;
+                    }
+                }
+                // the song left the queue, there's nothing to update
+                Err(VideoProcessError::Cancelled) => {
+                    info!("cancelled download for song: {}", song.uuid);
+                }
+                Err(err) => {
+                    error!(
+                        "could not download video for song: {} with error: {}",
+                        song.uuid, err
+                    );
+
+                    song_actor_handle
+                        .finish_download(
+                            song.uuid,
+                            QueuedSongStatus::Failed,
+                            None,
+                            Some(err.to_string()),
+                        )
+                        .await;
+                }
+            }
+        });
+    }
+
+    async fn finish_download(
+        &self,
+        song_uuid: Uuid,
+        status: QueuedSongStatus,
+        details: Option<VideoDetails>,
+        error: Option<String>,
+    ) {
+        match self
+            .update_song_status(song_uuid, status.clone(), details, error)
+            .await
+        {
+            Ok(_) => {
+                info!(
+                    "successfully updated song: {} with status: {}",
+                    song_uuid, status
+                );
+            }
+            Err(err) => {
+                error!(
+                    "unable to update status for song: {} with error: {}",
+                    song_uuid, err
+                );
+            }
+        }
+    }
+
+    pub async fn queue_song(&self, song: Song) -> Result<QueueOutcome, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::QueueSong {
+            song,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn update_song_status(
+        &self,
+        song_uuid: Uuid,
+        new_status: QueuedSongStatus,
+        details: Option<VideoDetails>,
+        error: Option<String>,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::UpdateSongStatus {
+            song_uuid,
+            status: new_status,
+            details,
+            error,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    async fn report_progress(
+        &self,
+        song_uuid: Uuid,
+        progress: ProcessingProgress,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ReportProgress {
+            song_uuid,
+            progress,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    async fn report_retry(
+        &self,
+        song_uuid: Uuid,
+        attempt: u32,
+        reason: String,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ReportRetry {
+            song_uuid,
+            attempt,
+            reason,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn remove_song(
+        &self,
+        song_uuid: Uuid,
+        expected_revision: Option<u64>,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::RemoveSong {
+            song_uuid,
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn clear_queue(
+        &self,
+        expected_revision: Option<u64>,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ClearQueue {
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn undo(&self, expected_revision: Option<u64>) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::Undo {
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        let restarted = recv.await.expect("Actor task has been killed")?;
+        for song in restarted {
+            self.download_song(song);
+        }
+        Ok(())
+    }
+
+    pub async fn redo(&self, expected_revision: Option<u64>) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::Redo {
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Finishes the current song. With `expected_current` set, only does so if that
+    /// song is still the current one.
+    pub async fn pop_song(&self, expected_current: Option<Uuid>) -> Option<Song> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::PopSong {
+            expected_current,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn reposition_song(
+        &self,
+        song_uuid: Uuid,
+        position: usize,
+        expected_revision: Option<u64>,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::Reposition {
+            song_uuid,
+            position,
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Adds a performer to a song that is already queued, so they can sing it together.
+    pub async fn join_song(
+        &self,
+        song_uuid: Uuid,
+        performer: Performer,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::JoinSong {
+            song_uuid,
+            performer,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn leave_song(
+        &self,
+        song_uuid: Uuid,
+        requester: String,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::LeaveSong {
+            song_uuid,
+            requester,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn current_song(&self) -> Result<Option<Song>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::Current { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_queue(&self) -> Result<QueueState, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetQueue { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn key_up(&self) -> Result<i8, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::KeyUp { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn key_down(&self) -> Result<i8, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::KeyDown { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_key(&self) -> Result<i8, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetKey { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn set_ordering(&self, ordering: QueueOrdering) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetOrdering {
+            ordering,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_ordering(&self) -> Result<QueueOrdering, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetOrdering { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_history(&self) -> Result<Vec<PlayedSong>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetHistory { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Starts a new session: forgets what's been played, which also resets the stats,
+    /// the repeat policy and the popular idle songs.
+    pub async fn clear_history(&self) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ClearHistory { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn set_repeat_policy(
+        &self,
+        repeat_policy: RepeatPolicy,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetRepeatPolicy {
+            repeat_policy,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_repeat_policy(&self) -> Result<RepeatPolicy, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetRepeatPolicy { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Counts a skip vote from `requester` against the current song, advancing the queue
+    /// once the threshold is reached. Returns the tally including this vote.
+    pub async fn vote_skip(&self, requester: String) -> Result<SkipVotes, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::VoteSkip {
+            requester,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_skip_votes(&self) -> Result<SkipVotes, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetSkipVotes { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_own_songs(&self, requester: String) -> Result<OwnSongs, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetOwnSongs {
+            requester,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
 
-                    let _ = self.sse_broadcaster.send(SseEvent::QueueUpdated {
-                        queue: self.song_deque.clone(),
-                    });
+    pub async fn set_skip_threshold(
+        &self,
+        skip_threshold: SkipThreshold,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetSkipThreshold {
+            skip_threshold,
+            respond_to: send,
+        };
 
-                    let _ = respond_to.send(Ok(()));
-                } else {
-                    let _ = respond_to.send(Err(SongCoordinatorError::UpdateSongStatusFailed {
-                        uuid: (song_uuid),
-                    }));
-                }
-            }
-        }
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
     }
-}
 
-async fn run_song_actor(mut actor: SongActor) {
-    while let Some(msg) = actor.receiver.recv().await {
-        actor.handle_message(msg).await;
+    pub async fn get_skip_threshold(&self) -> Result<SkipThreshold, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetSkipThreshold { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
     }
-}
 
-#[derive(Clone)]
-pub struct SongActorHandle {
-    sender: mpsc::Sender<SongActorMessage>,
-}
+    pub async fn set_failed_song_policy(
+        &self,
+        failed_song_policy: FailedSongPolicy,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetFailedSongPolicy {
+            failed_song_policy,
+            respond_to: send,
+        };
 
-impl SongActorHandle {
-    pub fn new(sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>) -> Self {
-        let (sender, receiver) = mpsc::channel(8);
-        let song_actor = SongActor::new(receiver, sse_broadcaster);
-        tokio::spawn(run_song_actor(song_actor));
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_failed_song_policy(&self) -> Result<FailedSongPolicy, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetFailedSongPolicy { respond_to: send };
 
-        Self { sender }
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn queue_song(&self, song: Song) -> Result<(), SongCoordinatorError> {
+    pub async fn get_parked_songs(&self) -> Result<Vec<Song>, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::QueueSong {
-            song,
+        let msg = SongActorMessage::GetParkedSongs { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Downloads a failed song again, bringing it back into the queue if it was parked.
+    pub async fn retry_song(
+        &self,
+        song_uuid: Uuid,
+        expected_revision: Option<u64>,
+    ) -> Result<Song, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::RetrySong {
+            song_uuid,
+            expected_revision,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        let song = recv.await.expect("Actor task has been killed")?;
+        self.download_song(song.clone());
+        Ok(song)
+    }
+
+    pub async fn set_moderation(&self, enabled: bool) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetModeration {
+            enabled,
             respond_to: send,
         };
 
@@ -353,15 +3539,36 @@ impl SongActorHandle {
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn update_song_status(
+    pub async fn get_moderation(&self) -> Result<bool, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetModeration { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_pending_songs(&self) -> Result<Vec<Song>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetPendingSongs { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Approves a pending request into the queue, at `position` if given, or rejects it.
+    pub async fn decide_request(
         &self,
         song_uuid: Uuid,
-        new_status: QueuedSongStatus,
+        decision: RequestDecision,
+        position: Option<usize>,
+        expected_revision: Option<u64>,
     ) -> Result<(), SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::UpdateSongStatus {
+        let msg = SongActorMessage::DecideRequest {
             song_uuid,
-            status: new_status,
+            decision,
+            position,
+            expected_revision,
             respond_to: send,
         };
 
@@ -369,10 +3576,20 @@ impl SongActorHandle {
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn remove_song(&self, song_uuid: Uuid) {
+    pub async fn get_key_memory(&self) -> Result<KeyMemory, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::RemoveSong {
-            song_uuid,
+        let msg = SongActorMessage::GetKeyMemory { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Forgets the remembered key of one song (by canonical ID), or of every song when
+    /// `song_id` is `None`.
+    pub async fn forget_keys(&self, song_id: Option<String>) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ForgetKeys {
+            song_id,
             respond_to: send,
         };
 
@@ -380,23 +3597,41 @@ impl SongActorHandle {
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn pop_song(&self) -> Option<Song> {
+    pub async fn get_playback(&self) -> Result<PlaybackState, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::PopSong { respond_to: send };
+        let msg = SongActorMessage::GetPlayback { respond_to: send };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn reposition_song(
+    /// Plays or pauses the current song. Asking for the state it is already in is a
+    /// no-op, so the command is safe to repeat.
+    pub async fn set_playback_status(
+        &self,
+        status: PlaybackStatus,
+    ) -> Result<PlaybackState, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetPlaybackStatus {
+            status,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn report_playback(
         &self,
         song_uuid: Uuid,
-        position: usize,
+        status: PlaybackStatus,
+        position_seconds: f64,
     ) -> Result<(), SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::Reposition {
+        let msg = SongActorMessage::ReportPlayback {
             song_uuid,
-            position,
+            status,
+            position_seconds,
             respond_to: send,
         };
 
@@ -404,43 +3639,410 @@ impl SongActorHandle {
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn current_song(&self) -> Result<Option<Song>, SongCoordinatorError> {
+    pub async fn restart_playback(&self) -> Result<(), SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::Current { respond_to: send };
+        let msg = SongActorMessage::RestartPlayback { respond_to: send };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn get_queue(&self) -> Result<VecDeque<Song>, SongCoordinatorError> {
+    pub async fn get_stage(&self) -> Result<StageState, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::GetQueue { respond_to: send };
+        let msg = SongActorMessage::GetStage { respond_to: send };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn key_up(&self) -> Result<i8, SongCoordinatorError> {
+    /// Ends the "up next" countdown or the break right away.
+    pub async fn skip_stage(&self) -> Result<StageState, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::KeyUp { respond_to: send };
+        let msg = SongActorMessage::SkipStage { respond_to: send };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn key_down(&self) -> Result<i8, SongCoordinatorError> {
+    pub async fn extend_stage(&self, seconds: u64) -> Result<StageState, SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::KeyDown { respond_to: send };
+        let msg = SongActorMessage::ExtendStage {
+            seconds,
+            respond_to: send,
+        };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
 
-    pub async fn get_key(&self) -> Result<i8, SongCoordinatorError> {
+    pub async fn set_interstitial(
+        &self,
+        interstitial: Interstitial,
+    ) -> Result<(), SongCoordinatorError> {
         let (send, recv) = oneshot::channel();
-        let msg = SongActorMessage::GetKey { respond_to: send };
+        let msg = SongActorMessage::SetInterstitial {
+            interstitial,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_interstitial(&self) -> Result<Interstitial, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetInterstitial { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_breaks(&self) -> Result<Vec<ScheduledBreak>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetBreaks { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Schedules a break for `starts_at` (unix seconds), or as soon as the current song
+    /// is over when that's left out.
+    pub async fn schedule_break(
+        &self,
+        starts_at: Option<u64>,
+        minutes: u32,
+    ) -> Result<ScheduledBreak, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::ScheduleBreak {
+            starts_at,
+            minutes,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn cancel_break(&self, id: String) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::CancelBreak {
+            id,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_idle_mode(&self) -> Result<IdleMode, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetIdleMode { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn set_idle_mode(&self, idle_mode: IdleMode) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetIdleMode {
+            idle_mode,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_idle_song(&self) -> Result<Option<IdleSong>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetIdleSong { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    /// Moves idle mode on to another song, e.g. once the display has finished one.
+    pub async fn next_idle_song(
+        &self,
+        expected_current: Option<Uuid>,
+    ) -> Result<Option<IdleSong>, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::NextIdleSong {
+            expected_current,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn set_queue_limits(
+        &self,
+        queue_limits: QueueLimits,
+    ) -> Result<(), SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::SetQueueLimits {
+            queue_limits,
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+
+    pub async fn get_queue_limits(&self) -> Result<QueueLimits, SongCoordinatorError> {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::GetQueueLimits { respond_to: send };
 
         let _ = self.sender.send(msg).await;
         recv.await.expect("Actor task has been killed")
     }
+
+    /// Empties the coordinator and deletes everything it saved to disk.
+    pub async fn close(&self) {
+        let (send, recv) = oneshot::channel();
+        let msg = SongActorMessage::Close { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.expect("Actor task has been killed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::server::{media_cache::MediaCache, utils::yt_downloader::YtDownloader};
+
+    /// A coordinator that's driven by calling into it directly, with its data dir
+    /// cleaned up afterwards.
+    struct TestRoom {
+        actor: SongActor,
+        data_dir: PathBuf,
+    }
+
+    impl TestRoom {
+        /// Needs a runtime, as the video downloader it's given starts its consumers.
+        fn new(ordering: QueueOrdering) -> Self {
+            let data_dir = std::env::temp_dir().join(format!("juicebox-test-{}", Uuid::new_v4()));
+            let assets_dir = data_dir.join("assets");
+            let media_cache = Arc::new(MediaCache::new(assets_dir.clone(), &data_dir));
+            let videodl_actor_handle = Arc::new(VideoDlActorHandle::new(
+                assets_dir.to_string_lossy().to_string(),
+                Arc::new(YtDownloader {}),
+                media_cache,
+            ));
+            let (sse_broadcaster, _) = sync::broadcast::channel(16);
+            let (_, receiver) = mpsc::channel(1);
+
+            let mut actor = SongActor::new(
+                receiver,
+                Arc::new(sse_broadcaster),
+                videodl_actor_handle,
+                Arc::new(PlaylistLibrary::new(&data_dir)),
+                &data_dir,
+                SnapshotStore::new(data_dir.join("song_queue.json")),
+                QueueSnapshot::empty(),
+            );
+            actor.ordering = ordering;
+
+            TestRoom { actor, data_dir }
+        }
+
+        fn queue(&mut self, singer: &str) -> Uuid {
+            let song = song(singer);
+            let uuid = song.uuid;
+            self.actor.enqueue(song);
+            uuid
+        }
+
+        fn singers(&self) -> Vec<&str> {
+            self.actor
+                .song_deque
+                .iter()
+                .map(|song| song.name.as_str())
+                .collect()
+        }
+
+        async fn set_ordering(&mut self, ordering: QueueOrdering) {
+            let (respond_to, response) = oneshot::channel();
+            self.actor
+                .handle_message(SongActorMessage::SetOrdering {
+                    ordering,
+                    respond_to,
+                })
+                .await;
+            response.await.unwrap().unwrap();
+        }
+    }
+
+    impl Drop for TestRoom {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.data_dir);
+        }
+    }
+
+    /// A song named after its singer, queued from the singer's own phone.
+    fn song(singer: &str) -> Song {
+        Song::new(
+            singer.to_string(),
+            format!("https://example.com/{}", Uuid::new_v4()),
+            QueuedSongStatus::Success,
+            false,
+            Some(singer.to_string()),
+            Some(format!("{}-phone", singer)),
+        )
+    }
+
+    #[tokio::test]
+    async fn fair_ordering_puts_singers_who_had_a_turn_behind_those_who_havent() {
+        let mut room = TestRoom::new(QueueOrdering::Fair);
+
+        for singer in ["alice", "alice", "bob", "carol", "bob"] {
+            room.queue(singer);
+        }
+
+        assert_eq!(room.singers(), ["alice", "bob", "carol", "alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn fair_ordering_counts_turns_already_taken() {
+        let mut room = TestRoom::new(QueueOrdering::Fair);
+        room.actor.turns_taken.insert("bob".to_string(), 2);
+
+        for singer in ["alice", "bob", "carol", "alice"] {
+            room.queue(singer);
+        }
+
+        assert_eq!(room.singers(), ["alice", "carol", "alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn switching_orderings_only_bumps_the_revision_when_songs_move() {
+        let mut room = TestRoom::new(QueueOrdering::Fifo);
+        for singer in ["alice", "alice", "alice", "bob"] {
+            room.queue(singer);
+        }
+
+        room.set_ordering(QueueOrdering::Fair).await;
+        assert_eq!(room.singers(), ["alice", "bob", "alice", "alice"]);
+        assert_eq!(room.actor.revision, 1);
+
+        room.set_ordering(QueueOrdering::Fifo).await;
+        assert_eq!(room.singers(), ["alice", "bob", "alice", "alice"]);
+        assert_eq!(room.actor.revision, 1);
+
+        room.queue("alice");
+        room.queue("carol");
+        assert_eq!(
+            room.singers(),
+            ["alice", "bob", "alice", "alice", "alice", "carol"]
+        );
+
+        room.set_ordering(QueueOrdering::Fair).await;
+        assert_eq!(
+            room.singers(),
+            ["alice", "bob", "carol", "alice", "alice", "alice"]
+        );
+        assert_eq!(room.actor.revision, 2);
+
+        room.set_ordering(QueueOrdering::Fair).await;
+        assert_eq!(room.actor.revision, 2);
+    }
+
+    #[tokio::test]
+    async fn joining_moves_a_song_back_but_never_ahead() {
+        let mut room = TestRoom::new(QueueOrdering::Fair);
+        room.queue("alice");
+        room.queue("alice");
+        room.queue("bob");
+        let carol = room.queue("carol");
+        assert_eq!(room.singers(), ["alice", "bob", "carol", "alice"]);
+
+        // alice already has a song coming up, so her duet with carol waits behind it
+        let alice = Performer {
+            name: "alice".to_string(),
+            requester: "alice-phone".to_string(),
+        };
+        room.actor.join_song(carol, alice).unwrap();
+        assert_eq!(room.singers(), ["alice", "bob", "alice", "carol"]);
+
+        // bob was put at the back by hand, and dave joining doesn't bring him forward
+        let bob = room.actor.song_deque[1].uuid;
+        room.actor.move_song(bob, 3);
+        let dave = Performer {
+            name: "dave".to_string(),
+            requester: "dave-phone".to_string(),
+        };
+        room.actor.join_song(bob, dave).unwrap();
+        assert_eq!(room.singers(), ["alice", "alice", "carol", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn turns_down_changes_made_against_another_revision() {
+        let mut room = TestRoom::new(QueueOrdering::Fifo);
+        room.actor.revision = 5;
+
+        assert!(room.actor.check_revision(None).is_ok());
+        assert!(room.actor.check_revision(Some(5)).is_ok());
+        assert!(matches!(
+            room.actor.check_revision(Some(4)),
+            Err(SongCoordinatorError::StaleQueueRevision { expected: 4, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn limits_songs_per_requester_counting_joined_songs() {
+        let mut room = TestRoom::new(QueueOrdering::Fifo);
+        room.actor.queue_limits.max_songs_per_requester = Some(2);
+
+        room.queue("alice");
+        let bob = room.queue("bob");
+        assert!(room.actor.check_queue_limits(&song("alice")).is_ok());
+        room.queue("alice");
+        assert!(matches!(
+            room.actor.check_queue_limits(&song("alice")),
+            Err(QueueLimitViolation::MaxSongsPerRequester {
+                max_songs_per_requester: 2
+            })
+        ));
+
+        let carol = Performer {
+            name: "carol".to_string(),
+            requester: "carol-phone".to_string(),
+        };
+        room.actor.join_song(bob, carol).unwrap();
+        assert!(room.actor.check_queue_limits(&song("carol")).is_ok());
+        room.queue("carol");
+        assert!(room.actor.check_queue_limits(&song("carol")).is_err());
+    }
+
+    #[tokio::test]
+    async fn limits_queue_length_and_requests_per_hour() {
+        let mut room = TestRoom::new(QueueOrdering::Fifo);
+        room.actor.queue_limits.max_queue_length = Some(2);
+        room.actor.queue_limits.max_requests_per_hour = Some(1);
+
+        let first = song("alice");
+        assert!(room.actor.check_queue_limits(&first).is_ok());
+        room.actor.record_request(&first);
+        assert!(matches!(
+            room.actor.check_queue_limits(&song("alice")),
+            Err(QueueLimitViolation::MaxRequestsPerHour {
+                max_requests_per_hour: 1,
+                ..
+            })
+        ));
+        assert!(room.actor.check_queue_limits(&song("bob")).is_ok());
+
+        room.queue("bob");
+        room.queue("carol");
+        assert!(matches!(
+            room.actor.check_queue_limits(&song("dave")),
+            Err(QueueLimitViolation::MaxQueueLength {
+                max_queue_length: 2
+            })
+        ));
+    }
 }
@@ -1,30 +1,199 @@
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fs::{self, File},
+    future::Future,
     io::BufReader,
     path::Path,
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 use tokio::sync::oneshot;
-use tracing::{debug, error, info, trace};
-
-use crate::server::utils::{
-    dash_processor::{DashProcessor, ProcessingMode},
-    yt_downloader::{VideoProcessError, YtDownloader},
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, trace, warn};
+use uuid::Uuid;
+
+use crate::server::{
+    media_cache::MediaCache,
+    utils::{
+        dash_processor::{DashProcessor, ProcessingMode},
+        progress::ProgressReporter,
+        video_id::extract_video_id,
+        yt_downloader::{DownloadFormat, Retry, VideoProcessError, YtDownloader, DOWNLOAD_FORMATS},
+    },
 };
 
+/// How long to wait before retrying after an error that may go away on its own. Doubles
+/// with every retry of the same format.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Retries of one format before moving on to the next.
+const MAX_RETRIES_PER_FORMAT: u32 = 3;
+
+/// Attempts a download gets in all, across formats.
+const MAX_ATTEMPTS: u32 = 8;
+
+/// How long each DASH segment is, in seconds.
+const SEGMENT_SECONDS: u32 = 4;
+
 #[derive(Serialize, Deserialize)]
 struct VideoStatus {
     segments: u32,
     is_key_changeable: bool,
+    /// The name of the song it was downloaded for. Folders processed before they were
+    /// keyed by video were named after the song instead, and don't have one.
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(flatten)]
+    details: VideoDetails,
+}
+
+/// What yt-dlp told us about a video. Kept in status.json so cached videos have it too;
+/// videos processed before a field was tracked just don't have it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VideoDetails {
+    #[serde(default)]
+    pub video_id: Option<String>,
+    #[serde(default)]
+    pub duration_seconds: Option<f64>,
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    #[serde(default)]
+    pub uploader: Option<String>,
+}
+
+/// What a download request ended up with.
+#[derive(Debug)]
+pub struct DownloadedVideo {
+    /// The file yt-dlp downloaded, which can go once the DASH output exists. `None` when
+    /// the video was already processed and nothing was downloaded.
+    pub source_path: Option<String>,
+    pub details: VideoDetails,
+}
+
+/// A video that has been fully processed and sits in the DASH cache, ready to play.
+#[derive(Clone, Debug)]
+pub struct CachedVideo {
+    /// The folder it lives in, see [`crate::server::utils::video_id::video_folder`].
+    pub folder: String,
+    /// The name of the song it was downloaded for.
+    pub name: String,
+    pub is_key_changeable: bool,
+    pub details: VideoDetails,
+}
+
+/// Reads the status of the video processed into `base_path`, as long as processing got
+/// all the way to the last segment.
+fn processed_video(base_path: &str) -> Option<VideoStatus> {
+    let status_path = format!("{}/status.json", base_path);
+
+    // Check if status.json exists
+    if !Path::new(&status_path).exists() {
+        trace!("status.json not found at {}", status_path);
+        return None;
+    }
+
+    // Read and parse status.json
+    let file = match File::open(&status_path) {
+        Ok(file) => file,
+        Err(e) => {
+            trace!("Failed to open status.json at {}: {}", status_path, e);
+            return None;
+        }
+    };
+
+    let status: VideoStatus = match serde_json::from_reader(BufReader::new(file)) {
+        Ok(status) => status,
+        Err(e) => {
+            trace!("Failed to parse status.json at {}: {}", status_path, e);
+            return None;
+        }
+    };
+
+    // Check if corresponding chunk file exists
+    let chunk_path = format!("{}/chunk-stream1-{:05}.m4s", base_path, status.segments);
+
+    debug!("chunk_path: {}", chunk_path);
+
+    let chunk_exists = Path::new(&chunk_path).exists();
+
+    trace!(
+        "Checking for chunk file: {} - {}",
+        chunk_path,
+        if chunk_exists { "found" } else { "not found" }
+    );
+
+    chunk_exists.then_some(status)
+}
+
+/// Whether `name` can be used as a folder in the cache: a single path segment that
+/// can't be mistaken for the cache dir itself or its parent.
+fn is_folder_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// The video in `folder` under `base_dir`, as long as it got all the way through
+/// processing.
+pub fn cached_video(base_dir: &str, folder: &str) -> Option<CachedVideo> {
+    let status = processed_video(&format!("{}/{}", base_dir, folder))?;
+    Some(CachedVideo {
+        folder: folder.to_string(),
+        name: status.title.unwrap_or_else(|| folder.to_string()),
+        is_key_changeable: status.is_key_changeable,
+        details: status.details,
+    })
+}
+
+/// One lock per cache folder that has a job running or waiting on it.
+type FolderLocks = Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>;
+
+/// Held by the job working on a folder. The same song queued in two rooms maps to the
+/// same folder, so without it two consumers could be clearing and writing it at once.
+struct FolderLock {
+    folder: String,
+    locks: FolderLocks,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl FolderLock {
+    async fn acquire(locks: &FolderLocks, folder: &str) -> Self {
+        let lock = locks
+            .lock()
+            .expect("folder locks lock poisoned")
+            .entry(folder.to_string())
+            .or_default()
+            .clone();
+
+        FolderLock {
+            folder: folder.to_string(),
+            locks: locks.clone(),
+            _guard: lock.lock_owned().await,
+        }
+    }
+}
+
+impl Drop for FolderLock {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock().expect("folder locks lock poisoned");
+        // only the map and this guard are left, so nobody is waiting on it
+        if locks
+            .get(&self.folder)
+            .is_some_and(|lock| Arc::strong_count(lock) == 2)
+        {
+            locks.remove(&self.folder);
+        }
+    }
 }
 
 pub enum VideoDlActorMessage {
     DownloadVideo {
         yt_link: String,
-        name: String,
+        folder: String,
+        title: String,
         is_key_changeable: bool,
-        respond_to: oneshot::Sender<Result<String, VideoProcessError>>,
+        progress: ProgressReporter,
+        cancel: CancellationToken,
+        respond_to: oneshot::Sender<Result<DownloadedVideo, VideoProcessError>>,
     },
 }
 
@@ -32,6 +201,7 @@ struct VideoDlActor {
     receiver: async_channel::Receiver<VideoDlActorMessage>,
     downloader: Arc<YtDownloader>,
     base_dir: String,
+    folder_locks: FolderLocks,
     consumer_id: u8,
 }
 
@@ -40,6 +210,7 @@ impl VideoDlActor {
         receiver: async_channel::Receiver<VideoDlActorMessage>,
         base_dir: String,
         video_downloader: Arc<YtDownloader>,
+        folder_locks: FolderLocks,
         consumer_id: u8,
     ) -> Self {
         trace!("Initializing VideoDlActor consumer {}", consumer_id);
@@ -47,6 +218,7 @@ impl VideoDlActor {
             receiver,
             base_dir,
             downloader: video_downloader,
+            folder_locks,
             consumer_id,
         }
     }
@@ -60,51 +232,97 @@ impl VideoDlActor {
         match msg {
             VideoDlActorMessage::DownloadVideo {
                 yt_link,
-                name,
+                folder,
+                title,
                 is_key_changeable,
+                mut progress,
+                cancel,
                 respond_to,
             } => {
+                // removed from the queue while it was still waiting for a consumer
+                if cancel.is_cancelled() {
+                    info!(
+                        "Consumer {} skipping cancelled download of {}",
+                        self.consumer_id, yt_link
+                    );
+                    let _ = respond_to.send(Err(VideoProcessError::Cancelled));
+                    return;
+                }
+
+                // anything else would have the folder clearing take out the whole cache
+                if !is_folder_name(&folder) {
+                    error!(
+                        "Consumer {} refusing to process video from {} into folder {:?}",
+                        self.consumer_id, yt_link, folder
+                    );
+                    let _ = respond_to.send(Err(VideoProcessError::InvalidFolderName(folder)));
+                    return;
+                }
+
+                // another job on the same folder, e.g. for the same song in another
+                // room, finishes first; it's usually a cache hit after that
+                let _folder_lock = tokio::select! {
+                    folder_lock = FolderLock::acquire(&self.folder_locks, &folder) => folder_lock,
+                    _ = cancel.cancelled() => {
+                        info!(
+                            "Consumer {} cancelled download of {} while waiting on folder {}",
+                            self.consumer_id, yt_link, folder
+                        );
+                        let _ = respond_to.send(Err(VideoProcessError::Cancelled));
+                        return;
+                    }
+                };
+
                 info!(
                     "Consumer {} starting to process video from {} to path {}",
-                    self.consumer_id, yt_link, name
+                    self.consumer_id, yt_link, folder
                 );
 
-                let video_path = format!("{}/{}", self.base_dir, name);
+                let video_path = format!("{}/{}", self.base_dir, folder);
+                let video_id = extract_video_id(&yt_link);
 
-                info!(
-                    "video exists: {}",
-                    self.video_exists(&video_path, is_key_changeable)
-                );
-                if Path::new(&video_path).exists()
-                    && self.video_exists(&video_path, is_key_changeable)
-                {
+                let cached_video =
+                    self.cached_video(&video_path, is_key_changeable, video_id.as_deref());
+                info!("video exists: {}", cached_video.is_some());
+                if let Some(status) = cached_video.filter(|_| Path::new(&video_path).exists()) {
                     info!(
                         "Consumer {} found existing processed video {} in path {}/{}",
-                        self.consumer_id, yt_link, self.base_dir, name
+                        self.consumer_id, yt_link, self.base_dir, folder
                     );
-                    let _ = respond_to.send(Ok(String::from("success")));
+                    let _ = respond_to.send(Ok(DownloadedVideo {
+                        source_path: None,
+                        details: status.details,
+                    }));
                 } else {
-                    if Path::new(&video_path).exists() {
-                        trace!(
-                            "Consumer {} clearing existing folder at {}",
-                            self.consumer_id,
-                            video_path
-                        );
-                        if let Err(e) = std::fs::remove_dir_all(&video_path) {
-                            error!(
-                                "Consumer {} failed to clear folder {}: {}",
-                                self.consumer_id, video_path, e
+                    let result = tokio::select! {
+                        result = self.download_with_retries(
+                            &yt_link,
+                            &folder,
+                            &title,
+                            is_key_changeable,
+                            &video_path,
+                            &mut progress,
+                        ) => result,
+                        // dropping process_video kills whatever yt-dlp or ffmpeg was running.
+                        // Nothing else touches the folder while we hold its lock, and it
+                        // held no finished video or we wouldn't be downloading, so all
+                        // that's in it is this job's output.
+                        _ = cancel.cancelled() => {
+                            info!(
+                                "Consumer {} cancelled download of {}, clearing {}",
+                                self.consumer_id, yt_link, video_path
                             );
-                            let _ = respond_to.send(Err(VideoProcessError::PitchShiftError(
-                                format!("Failed to clear existing folder: {}", e),
-                            )));
-                            return;
+                            if let Err(e) = fs::remove_dir_all(&video_path) {
+                                if e.kind() != std::io::ErrorKind::NotFound {
+                                    error!(
+                                        "Consumer {} failed to clear folder {}: {}",
+                                        self.consumer_id, video_path, e
+                                    );
+                                }
+                            }
+                            Err(VideoProcessError::Cancelled)
                         }
-                    }
-
-                    let result = self
-                        .process_video(&yt_link, &self.base_dir, &name, &is_key_changeable, &4)
-                        .await;
+                    };
                     info!(
                         "Consumer {} finished processing video from {}: {:?}",
                         self.consumer_id,
@@ -117,43 +335,93 @@ impl VideoDlActor {
         }
     }
 
-    fn video_exists(&self, base_path: &str, is_key_changeable: bool) -> bool {
-        let status_path = format!("{}/status.json", base_path);
-
-        // Check if status.json exists
-        if !Path::new(&status_path).exists() {
-            trace!(
-                "Consumer {} - status.json not found at {}",
-                self.consumer_id,
-                status_path
-            );
-            return false;
-        }
+    /// Works through [`DOWNLOAD_FORMATS`] until one can be downloaded and processed.
+    /// Errors that may go away on their own get the same format again after a backoff,
+    /// anything else moves on to the next format right away.
+    async fn download_with_retries(
+        &self,
+        yt_link: &str,
+        folder: &str,
+        title: &str,
+        is_key_changeable: bool,
+        video_path: &str,
+        progress: &mut ProgressReporter,
+    ) -> Result<DownloadedVideo, VideoProcessError> {
+        let mut attempt = 1;
+        for (index, format) in DOWNLOAD_FORMATS.iter().enumerate() {
+            let is_last_format = index + 1 == DOWNLOAD_FORMATS.len();
+            let mut backoff = INITIAL_BACKOFF;
+            let mut retries = 0;
+
+            loop {
+                self.clear_folder(video_path)?;
+                let err = match self
+                    .process_video(yt_link, folder, title, &is_key_changeable, format, progress)
+                    .await
+                {
+                    Ok(video) => return Ok(video),
+                    Err(err) => err,
+                };
+
+                let retry = err.retry();
+                let retry_later = retry == Retry::Later && retries < MAX_RETRIES_PER_FORMAT;
+                if retry == Retry::Never
+                    || attempt >= MAX_ATTEMPTS
+                    || (!retry_later && is_last_format)
+                {
+                    return Err(err);
+                }
 
-        // Read and parse status.json
-        let file = match File::open(&status_path) {
-            Ok(file) => file,
-            Err(e) => {
-                trace!(
-                    "Consumer {} - Failed to open status.json: {}",
-                    self.consumer_id,
-                    e
+                warn!(
+                    "Consumer {} attempt {} at {} with format {} failed: {}",
+                    self.consumer_id, attempt, yt_link, format.selector, err
                 );
-                return false;
-            }
-        };
+                attempt += 1;
+                progress.retrying(attempt, err.to_string());
 
-        let status: VideoStatus = match serde_json::from_reader(BufReader::new(file)) {
-            Ok(status) => status,
-            Err(e) => {
-                trace!(
-                    "Consumer {} - Failed to parse status.json: {}",
-                    self.consumer_id,
-                    e
-                );
-                return false;
+                if !retry_later {
+                    break;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                retries += 1;
             }
-        };
+        }
+
+        Err(VideoProcessError::DownloadError(
+            "no formats left to try".to_string(),
+        ))
+    }
+
+    /// Clears what an earlier attempt left behind.
+    fn clear_folder(&self, video_path: &str) -> Result<(), VideoProcessError> {
+        if !Path::new(video_path).exists() {
+            return Ok(());
+        }
+
+        trace!(
+            "Consumer {} clearing existing folder at {}",
+            self.consumer_id,
+            video_path
+        );
+        fs::remove_dir_all(video_path).map_err(|e| {
+            error!(
+                "Consumer {} failed to clear folder {}: {}",
+                self.consumer_id, video_path, e
+            );
+            VideoProcessError::PitchShiftError(format!("Failed to clear existing folder: {}", e))
+        })
+    }
+
+    /// Returns the status of an already processed video at `base_path`, if it is usable
+    /// for this request.
+    fn cached_video(
+        &self,
+        base_path: &str,
+        is_key_changeable: bool,
+        video_id: Option<&str>,
+    ) -> Option<VideoStatus> {
+        let status = processed_video(base_path)?;
 
         // Check key_changeable compatibility
         if is_key_changeable && !status.is_key_changeable {
@@ -161,46 +429,59 @@ impl VideoDlActor {
                 "Consumer {} - Key change requested but existing file doesn't support it",
                 self.consumer_id
             );
-            return false;
+            return None;
         }
 
-        // Check if corresponding chunk file exists
-        let chunk_path = format!("{}/chunk-stream1-{:05}.m4s", base_path, status.segments);
-
-        debug!("chunk_path: {}", chunk_path);
-
-        let chunk_exists = Path::new(&chunk_path).exists();
-
-        trace!(
-            "Consumer {} - Checking for chunk file: {} - {}",
-            self.consumer_id,
-            chunk_path,
-            if chunk_exists { "found" } else { "not found" }
-        );
+        // Folders named after the song from before they were keyed by video can hold
+        // any video, so make sure this one holds the right one
+        if let (Some(wanted), Some(cached)) = (video_id, status.details.video_id.as_deref()) {
+            if wanted != cached {
+                trace!(
+                    "Consumer {} - Existing folder holds video {} instead of {}",
+                    self.consumer_id,
+                    cached,
+                    wanted
+                );
+                return None;
+            }
+        }
 
-        chunk_exists
+        Some(status)
     }
 
     async fn process_video(
         &self,
         yt_link: &str,
-        base_dir: &str,
-        name: &str,
+        folder: &str,
+        title: &str,
         is_key_changeable: &bool,
-        segment_duration: &u32,
-    ) -> Result<String, VideoProcessError> {
+        format: &DownloadFormat,
+        progress: &mut ProgressReporter,
+    ) -> Result<DownloadedVideo, VideoProcessError> {
         trace!(
-            "Consumer {} starting download of {}",
+            "Consumer {} starting download of {} with format {}",
             self.consumer_id,
-            yt_link
+            yt_link,
+            format.selector
         );
-        let video_metadata = self.downloader.download(yt_link, base_dir, name).await?;
+        let video_metadata = self
+            .downloader
+            .download(yt_link, &self.base_dir, folder, format, progress)
+            .await?;
         let (dir, file_name, extension, duration_seconds) = (
             video_metadata.directory,
             video_metadata.filename,
             video_metadata.extension,
             video_metadata.duration_seconds,
         );
+        let details = VideoDetails {
+            video_id: video_metadata
+                .video_id
+                .or_else(|| extract_video_id(yt_link)),
+            duration_seconds: Some(duration_seconds),
+            thumbnail_url: video_metadata.thumbnail_url,
+            uploader: video_metadata.uploader,
+        };
 
         if let Err(e) = fs::create_dir_all(&dir) {
             error!(
@@ -215,8 +496,10 @@ impl VideoDlActor {
 
         let status_file_path = format!("{}/status.json", dir);
         let status = VideoStatus {
-            segments: (duration_seconds / (*segment_duration as f64)).ceil() as u32,
+            segments: (duration_seconds / SEGMENT_SECONDS as f64).ceil() as u32,
             is_key_changeable: *is_key_changeable,
+            title: Some(title.to_string()),
+            details,
         };
 
         match File::create(&status_file_path) {
@@ -260,7 +543,7 @@ impl VideoDlActor {
             extension
         );
 
-        let dash_processor = DashProcessor::new(4);
+        let dash_processor = DashProcessor::new(SEGMENT_SECONDS);
         let mode;
 
         if *is_key_changeable {
@@ -279,18 +562,27 @@ impl VideoDlActor {
             mode = ProcessingMode::Copy;
         }
 
-        match dash_processor.execute(
-            &format!("{}/{}.{}", dir, file_name, extension),
-            &format!("{}/{}.mpd", dir, file_name),
-            &mode,
-        ) {
+        match dash_processor
+            .execute(
+                &format!("{}/{}.{}", dir, file_name, extension),
+                &format!("{}/{}.mpd", dir, file_name),
+                &mode,
+                format.transcode,
+                duration_seconds,
+                progress,
+            )
+            .await
+        {
             Ok(_) => {
                 trace!(
                     "Consumer {} completed pitch shifting for {}",
                     self.consumer_id,
                     file_name
                 );
-                Ok(format!("{}/{}.{}", dir, file_name, extension))
+                Ok(DownloadedVideo {
+                    source_path: Some(format!("{}/{}.{}", dir, file_name, extension)),
+                    details: status.details,
+                })
             }
             Err(e) => {
                 trace!(
@@ -299,10 +591,7 @@ impl VideoDlActor {
                     file_name,
                     e
                 );
-                Err(VideoProcessError::PitchShiftError(format!(
-                    "Pitch shift failed: {}",
-                    e
-                )))
+                Err(e)
             }
         }
     }
@@ -354,10 +643,20 @@ async fn run_video_dl_actor(mut actor: VideoDlActor) {
 #[derive(Clone)]
 pub struct VideoDlActorHandle {
     sender: async_channel::Sender<VideoDlActorMessage>,
+    base_dir: String,
+    media_cache: Arc<MediaCache>,
+    /// Downloads that haven't finished yet, keyed by the UUID of the song they're for.
+    /// Each comes with an ID of its own, since a song can get a new download (e.g. after
+    /// an undo) before a cancelled one has wound down.
+    downloads: Arc<Mutex<HashMap<Uuid, (Uuid, CancellationToken)>>>,
 }
 
 impl VideoDlActorHandle {
-    pub fn new(base_dir: String, yt_downloader: Arc<YtDownloader>) -> Self {
+    pub fn new(
+        base_dir: String,
+        yt_downloader: Arc<YtDownloader>,
+        media_cache: Arc<MediaCache>,
+    ) -> Self {
         trace!("Initializing VideoDlActorHandle");
         let (sender, receiver) = async_channel::bounded(100);
         trace!(
@@ -366,6 +665,7 @@ impl VideoDlActorHandle {
         );
 
         const NUM_CONSUMERS: u8 = 5;
+        let folder_locks = FolderLocks::default();
         trace!("Starting {} consumers", NUM_CONSUMERS);
         for consumer_id in 0..NUM_CONSUMERS {
             trace!("Spawning consumer {}", consumer_id);
@@ -373,6 +673,7 @@ impl VideoDlActorHandle {
                 receiver.clone(),
                 base_dir.clone(),
                 yt_downloader.clone(),
+                folder_locks.clone(),
                 consumer_id,
             );
             tokio::spawn(run_video_dl_actor(actor));
@@ -380,15 +681,100 @@ impl VideoDlActorHandle {
         trace!("All consumers spawned");
         trace!("Total receiver count: {}", receiver.receiver_count());
 
-        Self { sender }
+        Self {
+            sender,
+            base_dir,
+            media_cache,
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Tracks what's in the cache and makes room in it.
+    pub fn media_cache(&self) -> &Arc<MediaCache> {
+        &self.media_cache
+    }
+
+    /// Every video in the cache that finished processing. Half-processed folders, e.g.
+    /// from a download that's still running, are left out.
+    pub fn processed_videos(&self) -> Vec<CachedVideo> {
+        let entries = match fs::read_dir(&self.base_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!(
+                    "unable to read cache dir {} with error: {}",
+                    self.base_dir, err
+                );
+                return Vec::new();
+            }
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| cached_video(&self.base_dir, &entry.file_name().to_string_lossy()))
+            .collect()
+    }
+
+    /// Downloads and processes the video for a song. The download can be cancelled
+    /// with [`Self::cancel_download`] from the moment this returns, before the future
+    /// is first polled.
+    pub fn download_video(
+        &self,
+        song_uuid: Uuid,
+        yt_link: String,
+        folder: String,
+        title: String,
+        pitch_shift: bool,
+        progress: ProgressReporter,
+    ) -> impl Future<Output = Result<DownloadedVideo, VideoProcessError>> {
+        let cancel = CancellationToken::new();
+        let download_id = Uuid::new_v4();
+        self.downloads
+            .lock()
+            .expect("downloads lock poisoned")
+            .insert(song_uuid, (download_id, cancel.clone()));
+
+        let handle = self.clone();
+        async move {
+            let result = handle
+                .run_download(yt_link, folder, title, pitch_shift, progress, cancel)
+                .await;
+
+            // leave alone whatever download took this one's place
+            let mut downloads = handle.downloads.lock().expect("downloads lock poisoned");
+            if downloads
+                .get(&song_uuid)
+                .is_some_and(|(id, _)| *id == download_id)
+            {
+                downloads.remove(&song_uuid);
+            }
+            result
+        }
+    }
+
+    /// Stops the download for a song, killing yt-dlp or ffmpeg if they're running and
+    /// clearing what was written so far. Does nothing once the download is done.
+    pub fn cancel_download(&self, song_uuid: Uuid) {
+        if let Some((_, cancel)) = self
+            .downloads
+            .lock()
+            .expect("downloads lock poisoned")
+            .remove(&song_uuid)
+        {
+            info!("cancelling download for song: {}", song_uuid);
+            cancel.cancel();
+        }
     }
 
-    pub async fn download_video(
+    async fn run_download(
         &self,
         yt_link: String,
-        name: String,
+        folder: String,
+        title: String,
         pitch_shift: bool,
-    ) -> Result<String, VideoProcessError> {
+        progress: ProgressReporter,
+        cancel: CancellationToken,
+    ) -> Result<DownloadedVideo, VideoProcessError> {
         trace!(
             "Requesting video download for {} (channel len: {})",
             yt_link,
@@ -398,8 +784,11 @@ impl VideoDlActorHandle {
         let (send, recv) = oneshot::channel();
         let msg = VideoDlActorMessage::DownloadVideo {
             yt_link: yt_link.clone(),
-            name: name.clone(),
+            folder,
+            title,
             is_key_changeable: pitch_shift.clone(),
+            progress,
+            cancel,
             respond_to: send,
         };
 
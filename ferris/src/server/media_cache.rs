@@ -0,0 +1,470 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoStaticStr;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::server::{
+    actors::video_downloader::cached_video,
+    utils::{clock::unix_timestamp, snapshot_store::SnapshotStore},
+};
+
+/// Where songs on their way out are moved to before they're deleted, so nothing can
+/// pick them up again while that runs. Has no `status.json`, so it's never taken for a
+/// cached song itself.
+const EVICTED_DIR: &str = ".evicted";
+
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum MediaCacheError {
+    #[error("song not in cache: {name}")]
+    CachedSongNotFound { name: String },
+
+    #[error("song is queued or playing and can't be removed: {name}")]
+    CachedSongInUse { name: String },
+
+    #[error("unable to remove cached song {name}: {reason}")]
+    RemoveCachedSongFailed { name: String, reason: String },
+}
+
+/// When the cache starts evicting songs. Unset limits aren't enforced, and none are set
+/// until the host sets them: the disk may be short on space for reasons that have
+/// nothing to do with the cache, and that shouldn't cost them every song they had.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct CacheLimits {
+    /// The most the cached songs may take up together.
+    pub max_cache_bytes: Option<u64>,
+    /// How much space has to be left on the disk the cache is on.
+    pub min_free_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    size_bytes: u64,
+    processed_at: u64,
+    #[serde(default)]
+    last_played_at: Option<u64>,
+}
+
+impl CacheEntry {
+    /// Songs that have never been played count as used when they were processed.
+    fn last_used_at(&self) -> u64 {
+        self.last_played_at.unwrap_or(self.processed_at)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheIndex {
+    #[serde(default)]
+    limits: CacheLimits,
+    /// Keyed by the folder the song was processed into.
+    #[serde(default)]
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Serialize)]
+pub struct CachedSong {
+    /// The folder it's cached in, which is what `DELETE /cache/{folder}` takes.
+    pub name: String,
+    /// The name of the song it was downloaded for, when it's known.
+    pub title: Option<String>,
+    pub size_bytes: u64,
+    pub processed_at: u64,
+    pub last_played_at: Option<u64>,
+    /// Queued or playing in some room, which keeps it from being evicted.
+    pub in_use: bool,
+}
+
+#[derive(Serialize)]
+pub struct CacheSummary {
+    pub limits: CacheLimits,
+    pub used_bytes: u64,
+    /// Free space on the disk the cache is on, when the platform tells us.
+    pub free_bytes: Option<u64>,
+    /// Least recently used first, which is the order they're evicted in.
+    pub songs: Vec<CachedSong>,
+}
+
+struct CacheState {
+    index: CacheIndex,
+    /// The songs each room has queued or playing, keyed by who claimed them.
+    claims: HashMap<Uuid, HashSet<String>>,
+}
+
+impl CacheState {
+    fn in_use(&self, name: &str) -> bool {
+        self.claims.values().any(|names| names.contains(name))
+    }
+
+    fn used_bytes(&self) -> u64 {
+        self.index
+            .entries
+            .values()
+            .map(|entry| entry.size_bytes)
+            .sum()
+    }
+}
+
+/// The processed videos under the assets dir, shared by every room. Keeps track of how
+/// big each one is and when it last played, and evicts the least recently used ones
+/// once the [`CacheLimits`] are crossed. Songs a room has queued or playing are never
+/// evicted; rooms claim them with [`MediaCache::claim`].
+///
+/// Measuring and evicting songs walks and deletes whole folders, so the methods that do
+/// either block and are run through `spawn_blocking`. The lock is only held while the
+/// index is looked at, never while a folder is deleted.
+pub struct MediaCache {
+    base_dir: PathBuf,
+    state: Mutex<CacheState>,
+    index_store: SnapshotStore,
+}
+
+impl MediaCache {
+    pub fn new(base_dir: PathBuf, data_dir: &Path) -> Self {
+        let index_store = SnapshotStore::new(data_dir.join("media_cache.json"));
+        let index: CacheIndex = index_store.load().unwrap_or_default();
+
+        let media_cache = MediaCache {
+            base_dir,
+            state: Mutex::new(CacheState {
+                index,
+                claims: HashMap::new(),
+            }),
+            index_store,
+        };
+        media_cache.reconcile();
+
+        media_cache
+    }
+
+    /// Brings the index in line with what's on disk: folders processed before the cache
+    /// was tracked are added, and ones removed by hand are dropped.
+    fn reconcile(&self) {
+        // left behind by evictions a restart cut short
+        if let Err(err) = remove_dir(&self.base_dir.join(EVICTED_DIR)) {
+            warn!("unable to clear evicted songs with error: {}", err);
+        }
+
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+
+        let names: HashSet<String> = match fs::read_dir(&self.base_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| cached_video(&self.base_dir.to_string_lossy(), name).is_some())
+                .collect(),
+            Err(err) => {
+                warn!(
+                    "unable to read cache dir {} with error: {}",
+                    self.base_dir.display(),
+                    err
+                );
+                HashSet::new()
+            }
+        };
+
+        state.index.entries.retain(|name, _| names.contains(name));
+        for name in names {
+            if state.index.entries.contains_key(&name) {
+                continue;
+            }
+
+            let folder = self.folder(&name);
+            let processed_at = fs::metadata(folder.join("status.json"))
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_secs())
+                .unwrap_or_else(unix_timestamp);
+
+            info!("adding untracked song to cache: {}", name);
+            state.index.entries.insert(
+                name,
+                CacheEntry {
+                    size_bytes: folder_size(&folder),
+                    processed_at,
+                    last_played_at: None,
+                },
+            );
+        }
+
+        self.persist(&state);
+    }
+
+    fn folder(&self, name: &str) -> PathBuf {
+        self.base_dir.join(name)
+    }
+
+    fn persist(&self, state: &CacheState) {
+        if let Err(err) = self.index_store.save(&state.index) {
+            error!("failed to persist media cache with error: {}", err);
+        }
+    }
+
+    /// Adds a song that just finished processing, then makes room for it if that took
+    /// the cache over its limits.
+    pub fn record_processed(&self, name: &str) {
+        let size_bytes = folder_size(&self.folder(name));
+
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+        let last_played_at = state
+            .index
+            .entries
+            .get(name)
+            .and_then(|entry| entry.last_played_at);
+        state.index.entries.insert(
+            name.to_string(),
+            CacheEntry {
+                size_bytes,
+                processed_at: unix_timestamp(),
+                last_played_at,
+            },
+        );
+        info!("cached song: {} ({} bytes)", name, size_bytes);
+        self.persist(&state);
+        drop(state);
+
+        self.evict();
+    }
+
+    pub fn record_played(&self, name: &str) {
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+        if let Some(entry) = state.index.entries.get_mut(name) {
+            entry.last_played_at = Some(unix_timestamp());
+            self.persist(&state);
+        }
+    }
+
+    /// Replaces the songs `owner` has queued or playing, which keeps them in the cache
+    /// until they're released again.
+    pub fn claim(&self, owner: Uuid, names: HashSet<String>) {
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+        state.claims.insert(owner, names);
+    }
+
+    pub fn release(&self, owner: Uuid) {
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+        state.claims.remove(&owner);
+    }
+
+    pub fn limits(&self) -> CacheLimits {
+        self.state
+            .lock()
+            .expect("media cache lock poisoned")
+            .index
+            .limits
+    }
+
+    pub fn set_limits(&self, limits: CacheLimits) -> CacheLimits {
+        let mut state = self.state.lock().expect("media cache lock poisoned");
+        state.index.limits = limits;
+        self.persist(&state);
+        drop(state);
+
+        self.evict();
+        limits
+    }
+
+    /// Evicts whatever it takes to get back under the limits, e.g. once the rooms have
+    /// claimed their songs at startup.
+    pub fn enforce_limits(&self) {
+        self.evict();
+    }
+
+    pub fn summary(&self) -> CacheSummary {
+        let (limits, songs) = {
+            let state = self.state.lock().expect("media cache lock poisoned");
+            let songs: Vec<CachedSong> = state
+                .index
+                .entries
+                .iter()
+                .map(|(name, entry)| CachedSong {
+                    name: name.clone(),
+                    title: None,
+                    size_bytes: entry.size_bytes,
+                    processed_at: entry.processed_at,
+                    last_played_at: entry.last_played_at,
+                    in_use: state.in_use(name),
+                })
+                .collect();
+            (state.index.limits, songs)
+        };
+
+        let base_dir = self.base_dir.to_string_lossy();
+        let mut songs: Vec<CachedSong> = songs
+            .into_iter()
+            // a failed download may have cleared the folder since it was processed
+            .filter(|song| self.folder(&song.name).exists())
+            .map(|song| CachedSong {
+                title: cached_video(&base_dir, &song.name).map(|video| video.name),
+                ..song
+            })
+            .collect();
+        songs.sort_by_key(|song| song.last_played_at.unwrap_or(song.processed_at));
+
+        CacheSummary {
+            limits,
+            used_bytes: songs.iter().map(|song| song.size_bytes).sum(),
+            free_bytes: free_space(&self.base_dir),
+            songs,
+        }
+    }
+
+    /// Removes a song from the cache by hand. Songs that are queued or playing stay.
+    pub fn remove(&self, name: &str) -> Result<(), MediaCacheError> {
+        let set_aside = {
+            let mut state = self.state.lock().expect("media cache lock poisoned");
+
+            if !state.index.entries.contains_key(name) {
+                return Err(MediaCacheError::CachedSongNotFound {
+                    name: name.to_string(),
+                });
+            }
+            if state.in_use(name) {
+                return Err(MediaCacheError::CachedSongInUse {
+                    name: name.to_string(),
+                });
+            }
+
+            let set_aside =
+                self.set_aside(name)
+                    .map_err(|err| MediaCacheError::RemoveCachedSongFailed {
+                        name: name.to_string(),
+                        reason: err.to_string(),
+                    })?;
+            state.index.entries.remove(name);
+            self.persist(&state);
+            set_aside
+        };
+
+        // it's out of the index and its folder, so it's only ours to delete from here
+        remove_dir(&set_aside).map_err(|err| MediaCacheError::RemoveCachedSongFailed {
+            name: name.to_string(),
+            reason: err.to_string(),
+        })?;
+
+        info!("removed song from cache: {}", name);
+        Ok(())
+    }
+
+    /// Moves a song's folder out of the way to be deleted without the lock held. Only
+    /// called with the lock held, so the song can't be claimed while it's moved.
+    fn set_aside(&self, name: &str) -> io::Result<PathBuf> {
+        let evicted_dir = self.base_dir.join(EVICTED_DIR);
+        fs::create_dir_all(&evicted_dir)?;
+
+        // the same song can be processed and evicted again before the last one's gone
+        let set_aside = evicted_dir.join(format!("{}-{}", name, Uuid::new_v4()));
+        match fs::rename(self.folder(name), &set_aside) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(set_aside),
+        }
+    }
+
+    fn over_limits(state: &CacheState, free_bytes: Option<u64>) -> bool {
+        let limits = state.index.limits;
+        let over_quota = limits
+            .max_cache_bytes
+            .is_some_and(|max_cache_bytes| state.used_bytes() > max_cache_bytes);
+        let under_floor = limits.min_free_bytes.is_some_and(|min_free_bytes| {
+            free_bytes.is_some_and(|free_bytes| free_bytes < min_free_bytes)
+        });
+        over_quota || under_floor
+    }
+
+    /// Evicts the least recently used songs nobody has claimed until the cache is back
+    /// under its limits or there's nothing left to evict. Each song is taken out of the
+    /// index under the lock and deleted after it's let go.
+    fn evict(&self) {
+        loop {
+            let free_bytes = free_space(&self.base_dir);
+
+            let (name, entry, set_aside) = {
+                let mut state = self.state.lock().expect("media cache lock poisoned");
+                if !Self::over_limits(&state, free_bytes) {
+                    return;
+                }
+
+                let Some(name) = state
+                    .index
+                    .entries
+                    .iter()
+                    .filter(|(name, _)| !state.in_use(name))
+                    .min_by_key(|(_, entry)| entry.last_used_at())
+                    .map(|(name, _)| name.clone())
+                else {
+                    warn!("media cache is over its limits, but every cached song is in use");
+                    return;
+                };
+
+                let set_aside = self.set_aside(&name);
+                let Some(entry) = state.index.entries.remove(&name) else {
+                    return;
+                };
+                self.persist(&state);
+                (name, entry, set_aside)
+            };
+
+            match set_aside.and_then(|set_aside| remove_dir(&set_aside)) {
+                Ok(()) => info!("evicted song: {} ({} bytes)", name, entry.size_bytes),
+                Err(err) => error!("unable to evict song: {} with error: {}", name, err),
+            }
+        }
+    }
+}
+
+/// Deletes `path` and everything under it. It already being gone is fine.
+fn remove_dir(path: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Everything under `path`, in bytes.
+fn folder_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => folder_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Space left for unprivileged users on the disk `path` is on.
+#[cfg(unix)]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs is plain old data, and all zeroes is a valid value for it
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: path is NUL-terminated and outlives the call, stats is valid to write to
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+
+    // the field types differ between platforms, e.g. 32-bit ARM
+    #[allow(clippy::unnecessary_cast)]
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
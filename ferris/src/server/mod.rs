@@ -1,24 +1,45 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use actors::song_coordinator::SongActorHandle;
-use axum::routing::post;
+use axum::middleware;
+use axum::routing::{delete, post};
 use axum::serve;
 use axum::{routing::get, Router};
+use playlist_library::PlaylistLibrary;
+use rooms::RoomRegistry;
 use routes::healthcheck::healthcheck;
 use state::AppState;
 use tokio::net::TcpListener;
-use tokio::sync::{self, oneshot};
+use tokio::sync::oneshot;
 
 use actors::video_downloader::VideoDlActorHandle;
 use actors::video_searcher::VideoSearcherActorHandle;
+use media_cache::MediaCache;
+use requester::{issue_requester, load_or_generate_key};
 use routes::admin::{
-    get_key, key_down, key_up, remove_song, reposition_song, restart_song, toggle_playback,
+    approve_song, breaks, cancel_break, clear_key_memory, clear_queue, extend_stage,
+    get_failed_song_policy, get_idle_mode, get_interstitial, get_key, get_key_memory,
+    get_moderation, get_queue_limits, get_queue_ordering, get_repeat_policy, get_skip_threshold,
+    get_stage, key_down, key_up, parked_songs, pause, pending_songs, play, redo, reject_song,
+    remove_song, reposition_song, restart_song, retry_song, schedule_break, set_failed_song_policy,
+    set_idle_mode, set_interstitial, set_moderation, set_queue_limits, set_queue_ordering,
+    set_repeat_policy, set_skip_threshold, skip_stage, undo,
 };
-use routes::karaoke::{current_song, play_next_song, queue_song, search, song_list};
+use routes::cache::{cache, get_cache_limits, remove_cached_song, set_cache_limits};
+use routes::history::{clear_history, export_history, history, stats};
+use routes::karaoke::{
+    current_song, get_playback, idle_song, join_song, leave_song, next_idle_song, own_songs,
+    play_next_song, queue_song, report_playback, search, skip_votes, song_list, vote_skip,
+};
+use routes::playlists::{
+    delete_playlist, export_playlist, get_playlist, import_playlist, list_playlists,
+    queue_playlist, save_playlist, save_room_playlist,
+};
+use routes::rooms::{close_room, create_room, list_rooms};
 use routes::sse::sse;
 use routes::streaming::serve_dash_file;
-use routes::sys::{server_ip, autoap_status};
+use routes::sys::{autoap_status, server_ip};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{error, info};
@@ -30,6 +51,11 @@ use rust_embed::RustEmbed;
 
 pub mod actors;
 pub mod globals;
+pub mod media_cache;
+pub mod playlist_library;
+pub mod queue_revision;
+pub mod requester;
+pub mod rooms;
 pub mod routes;
 mod state;
 pub mod utils;
@@ -43,45 +69,140 @@ struct GoldieAssets;
 #[folder = "assets/phippy"]
 struct PhippyAssets;
 
+/// Routes that act on a single room's queue. They're mounted both under
+/// `/rooms/{room}` and at the top level, where they act on the default room.
+fn create_room_router() -> Router<AppState> {
+    Router::new()
+        .route("/queue_song", post(queue_song))
+        .route("/play_next", post(play_next_song))
+        .route("/join_song", post(join_song))
+        .route("/leave_song", post(leave_song))
+        .route("/vote_skip", post(vote_skip))
+        .route("/skip_votes", get(skip_votes))
+        .route("/own_songs", get(own_songs))
+        .route("/song_list", get(song_list))
+        .route("/current_song", get(current_song))
+        .route("/sse", get(sse))
+        .route("/playback", get(get_playback).post(report_playback))
+        .route("/play", post(play))
+        .route("/pause", post(pause))
+        .route("/key_up", post(key_up))
+        .route("/key_down", post(key_down))
+        .route("/get_key", get(get_key))
+        .route("/key_memory", get(get_key_memory).delete(clear_key_memory))
+        .route("/reposition_song", post(reposition_song))
+        .route("/remove_song", post(remove_song))
+        .route("/clear_queue", post(clear_queue))
+        .route("/undo", post(undo))
+        .route("/redo", post(redo))
+        .route("/restart", post(restart_song))
+        .route(
+            "/queue_ordering",
+            get(get_queue_ordering).post(set_queue_ordering),
+        )
+        .route(
+            "/repeat_policy",
+            get(get_repeat_policy).post(set_repeat_policy),
+        )
+        .route(
+            "/queue_limits",
+            get(get_queue_limits).post(set_queue_limits),
+        )
+        .route(
+            "/skip_threshold",
+            get(get_skip_threshold).post(set_skip_threshold),
+        )
+        .route(
+            "/failed_song_policy",
+            get(get_failed_song_policy).post(set_failed_song_policy),
+        )
+        .route("/parked_songs", get(parked_songs))
+        .route("/retry_song", post(retry_song))
+        .route("/idle_mode", get(get_idle_mode).post(set_idle_mode))
+        .route("/idle_song", get(idle_song))
+        .route("/idle_song/next", post(next_idle_song))
+        .route("/stage", get(get_stage))
+        .route("/stage/skip", post(skip_stage))
+        .route("/stage/extend", post(extend_stage))
+        .route(
+            "/interstitial",
+            get(get_interstitial).post(set_interstitial),
+        )
+        .route("/breaks", get(breaks).post(schedule_break))
+        .route("/breaks/{break_id}", delete(cancel_break))
+        .route("/moderation", get(get_moderation).post(set_moderation))
+        .route("/pending_songs", get(pending_songs))
+        .route("/approve_song", post(approve_song))
+        .route("/reject_song", post(reject_song))
+        .route("/history", get(history).delete(clear_history))
+        .route("/history/export", get(export_history))
+        .route("/stats", get(stats))
+        .route("/playlists/{playlist}/save", post(save_room_playlist))
+        .route("/playlists/{playlist}/queue", post(queue_playlist))
+}
+
 fn create_api_router() -> Router {
     let yt_downloader = Arc::new(YtDownloader {});
     let yt_searcher = Arc::new(YtSearcher {});
 
-    let (sse_broadcaster, _) = sync::broadcast::channel(10);
-    let sse_broadcaster = Arc::new(sse_broadcaster);
-
-    let song_actor_handle = Arc::new(SongActorHandle::new(sse_broadcaster.clone()));
+    let media_cache = Arc::new(MediaCache::new(
+        PathBuf::from("./assets"),
+        &PathBuf::from("./config"),
+    ));
     let videodl_actor_handle = Arc::new(VideoDlActorHandle::new(
         String::from("./assets"),
         yt_downloader,
+        media_cache.clone(),
+    ));
+    let playlist_library = Arc::new(PlaylistLibrary::new(&PathBuf::from("./config")));
+    let room_registry = Arc::new(RoomRegistry::new(
+        videodl_actor_handle.clone(),
+        playlist_library.clone(),
+        &PathBuf::from("./config"),
     ));
     let videosearcher_actor_handle = Arc::new(VideoSearcherActorHandle::new(yt_searcher));
+    let requester_key = load_or_generate_key(&PathBuf::from("./config"));
+
+    // the rooms have claimed their queued songs by now, so nothing they need goes
+    let startup_media_cache = media_cache.clone();
+    tokio::task::spawn_blocking(move || startup_media_cache.enforce_limits());
 
     let app_state = AppState::new(
-        song_actor_handle,
+        room_registry,
         videodl_actor_handle,
         videosearcher_actor_handle,
-        sse_broadcaster.clone(),
+        playlist_library,
+        media_cache,
+        requester_key,
     );
 
     Router::new()
         .route("/healthcheck", get(healthcheck))
         .route("/server_ip", get(server_ip))
         .route("/autoap_status", get(autoap_status))
-        .route("/queue_song", post(queue_song))
-        .route("/play_next", post(play_next_song))
-        .route("/song_list", get(song_list))
-        .route("/current_song", get(current_song))
         .route("/dash/{song_name}/{file}", get(serve_dash_file))
-        .route("/sse", get(sse))
-        .route("/toggle_playback", post(toggle_playback))
-        .route("/key_up", post(key_up))
-        .route("/key_down", post(key_down))
-        .route("/get_key", get(get_key))
-        .route("/reposition_song", post(reposition_song))
-        .route("/remove_song", post(remove_song))
-        .route("/restart", post(restart_song))
         .route("/search", get(search))
+        .route("/rooms", get(list_rooms).post(create_room))
+        .route("/rooms/{room}", delete(close_room))
+        .route("/playlists", get(list_playlists))
+        .route(
+            "/playlists/{playlist}",
+            get(get_playlist).put(save_playlist).delete(delete_playlist),
+        )
+        .route("/playlists/{playlist}/import", post(import_playlist))
+        .route("/playlists/{playlist}/export", get(export_playlist))
+        .route("/cache", get(cache))
+        .route("/cache/{folder}", delete(remove_cached_song))
+        .route(
+            "/cache_limits",
+            get(get_cache_limits).post(set_cache_limits),
+        )
+        .nest("/rooms/{room}", create_room_router())
+        .merge(create_room_router())
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            issue_requester,
+        ))
         .with_state(app_state)
 }
 
@@ -110,7 +231,12 @@ pub async fn run_server(addr: SocketAddr, ready_tx: oneshot::Sender<()>) {
     // Signal that the server is ready
     let _ = ready_tx.send(());
 
-    match serve(listener, app).await {
+    match serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    {
         Ok(_) => info!("Server shutdown gracefully"),
         Err(e) => error!("Server error: {}", e),
     }
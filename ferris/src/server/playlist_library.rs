@@ -0,0 +1,121 @@
+use std::{collections::BTreeMap, path::Path, sync::RwLock};
+
+use serde::Serialize;
+use strum::IntoStaticStr;
+use thiserror::Error;
+use tracing::error;
+
+use crate::server::utils::{
+    playlist::{Playlist, PlaylistEntry},
+    snapshot_store::SnapshotStore,
+};
+
+const MAX_PLAYLIST_NAME_LEN: usize = 64;
+
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum PlaylistError {
+    #[error("invalid playlist name: {name}")]
+    InvalidPlaylistName { name: String },
+
+    #[error("playlist not found: {name}")]
+    PlaylistNotFound { name: String },
+}
+
+#[derive(Serialize)]
+pub struct PlaylistSummary {
+    pub name: String,
+    pub songs: usize,
+}
+
+/// Named playlists, shared by every room and kept across restarts.
+pub struct PlaylistLibrary {
+    playlists: RwLock<BTreeMap<String, Playlist>>,
+    playlists_store: SnapshotStore,
+}
+
+impl PlaylistLibrary {
+    pub fn new(data_dir: &Path) -> Self {
+        let playlists_store = SnapshotStore::new(data_dir.join("playlists.json"));
+        let playlists: Vec<Playlist> = playlists_store.load().unwrap_or_default();
+
+        PlaylistLibrary {
+            playlists: RwLock::new(
+                playlists
+                    .into_iter()
+                    .map(|playlist| (playlist.name.clone(), playlist))
+                    .collect(),
+            ),
+            playlists_store,
+        }
+    }
+
+    fn persist(&self, playlists: &BTreeMap<String, Playlist>) {
+        let playlists: Vec<&Playlist> = playlists.values().collect();
+        if let Err(err) = self.playlists_store.save(&playlists) {
+            error!("failed to persist playlists with error: {}", err);
+        }
+    }
+
+    pub fn list(&self) -> Vec<PlaylistSummary> {
+        self.playlists
+            .read()
+            .expect("playlist library lock poisoned")
+            .values()
+            .map(|playlist| PlaylistSummary {
+                name: playlist.name.clone(),
+                songs: playlist.entries.len(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Playlist, PlaylistError> {
+        self.playlists
+            .read()
+            .expect("playlist library lock poisoned")
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PlaylistError::PlaylistNotFound {
+                name: name.to_string(),
+            })
+    }
+
+    /// Creates the playlist, or replaces its songs if it already exists.
+    pub fn save(&self, name: &str, entries: Vec<PlaylistEntry>) -> Result<Playlist, PlaylistError> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_PLAYLIST_NAME_LEN {
+            return Err(PlaylistError::InvalidPlaylistName {
+                name: name.to_string(),
+            });
+        }
+
+        let playlist = Playlist {
+            name: name.to_string(),
+            entries,
+        };
+
+        let mut playlists = self
+            .playlists
+            .write()
+            .expect("playlist library lock poisoned");
+        playlists.insert(playlist.name.clone(), playlist.clone());
+        self.persist(&playlists);
+
+        Ok(playlist)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), PlaylistError> {
+        let mut playlists = self
+            .playlists
+            .write()
+            .expect("playlist library lock poisoned");
+        playlists
+            .remove(name)
+            .ok_or_else(|| PlaylistError::PlaylistNotFound {
+                name: name.to_string(),
+            })?;
+        self.persist(&playlists);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+use crate::server::routes::error::ApiError;
+
+/// The ETag `song_list` sends for a revision of the queue. Weak, as download statuses
+/// and the like change without a new revision.
+pub fn revision_etag(revision: u64) -> String {
+    format!("W/\"{}\"", revision)
+}
+
+/// The queue revision a change was made against, taken from the `If-Match` header the
+/// way `song_list` hands it out as an ETag. Changes without one (or with `*`) apply to
+/// whatever the queue looks like by then.
+pub struct ExpectedRevision(pub Option<u64>);
+
+impl<S: Send + Sync> FromRequestParts<S> for ExpectedRevision {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(ExpectedRevision(None));
+        };
+
+        let value = value.to_str().unwrap_or_default().trim();
+        if value == "*" {
+            return Ok(ExpectedRevision(None));
+        }
+
+        value
+            .trim_start_matches("W/")
+            .trim_matches('"')
+            .parse()
+            .map(|revision| ExpectedRevision(Some(revision)))
+            .map_err(|_| ApiError::InvalidQueueRevision(value.to_string()))
+    }
+}
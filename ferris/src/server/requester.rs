@@ -0,0 +1,114 @@
+use std::{convert::Infallible, fs, net::SocketAddr, path::Path};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request},
+    http::request::Parts,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::{
+    cookie::{Cookie, Key, SameSite},
+    SignedCookieJar,
+};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::server::routes::error::ApiError;
+
+/// Cookie the server keeps each phone's ID in. It's signed, so a phone can't make one
+/// up to pass itself off as another phone, and `HttpOnly`, so the page can't read it.
+pub const REQUESTER_COOKIE: &str = "juicebox_requester";
+
+/// Loads the key requester cookies are signed with, or generates one on first run.
+/// Kept across restarts so phones keep their IDs, and the queue its idea of who
+/// queued what.
+pub fn load_or_generate_key(data_dir: &Path) -> Key {
+    let path = data_dir.join("requester_key");
+    match fs::read(&path).map(|bytes| Key::try_from(bytes.as_slice())) {
+        Ok(Ok(key)) => return key,
+        Ok(Err(err)) => warn!("discarding requester key {}: {}", path.display(), err),
+        Err(_) => {}
+    }
+
+    let key = Key::generate();
+    if let Err(err) = fs::create_dir_all(data_dir).and_then(|_| fs::write(&path, key.master())) {
+        error!(
+            "failed to save requester key, phones get new IDs on restart: {}",
+            err
+        );
+    }
+    key
+}
+
+/// What [`issue_requester`] made of the phone behind a request.
+#[derive(Clone)]
+struct Identity {
+    id: String,
+    /// Whether the phone sent back an ID it was given before, rather than getting a new
+    /// one with this request.
+    returning: bool,
+}
+
+/// Identifies the phone behind a request by the ID in its requester cookie, handing
+/// out a new one to phones that don't have one yet.
+pub async fn issue_requester(jar: SignedCookieJar, mut request: Request, next: Next) -> Response {
+    if let Some(cookie) = jar.get(REQUESTER_COOKIE) {
+        request.extensions_mut().insert(Identity {
+            id: cookie.value().to_string(),
+            returning: true,
+        });
+        return next.run(request).await;
+    }
+
+    let id = Uuid::new_v4().to_string();
+    request.extensions_mut().insert(Identity {
+        id: id.clone(),
+        returning: false,
+    });
+    let response = next.run(request).await;
+
+    let cookie = Cookie::build((REQUESTER_COOKIE, id))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .permanent();
+    (jar.add(cookie), response).into_response()
+}
+
+/// Identifies the phone behind a request: the ID [`issue_requester`] found or handed
+/// out, otherwise its IP address.
+pub struct Requester(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for Requester {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(identity) = parts.extensions.get::<Identity>() {
+            return Ok(Requester(identity.id.clone()));
+        }
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+            .unwrap_or_else(|| String::from("unknown"));
+
+        Ok(Requester(ip))
+    }
+}
+
+/// A [`Requester`] whose phone sent back the ID it was given before. Anything the queue
+/// limits or skip votes count per phone asks for one, as a phone that drops its cookie
+/// would otherwise get a fresh allowance with every request.
+pub struct KnownRequester(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for KnownRequester {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Identity>() {
+            Some(identity) if identity.returning => Ok(KnownRequester(identity.id.clone())),
+            _ => Err(ApiError::UnknownRequester),
+        }
+    }
+}
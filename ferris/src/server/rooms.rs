@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    extract::{FromRequestParts, RawPathParams},
+    http::request::Parts,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use strum::IntoStaticStr;
+use thiserror::Error;
+use tokio::sync;
+use tracing::{error, info, warn};
+
+use crate::server::{
+    actors::{song_coordinator::SongActorHandle, video_downloader::VideoDlActorHandle},
+    playlist_library::PlaylistLibrary,
+    routes::{error::ApiError, sse::SseEvent},
+    state::AppState,
+    utils::snapshot_store::SnapshotStore,
+};
+
+/// The room the top-level routes (`/queue_song`, `/sse`, ...) talk to. It always exists
+/// and can't be closed.
+pub const DEFAULT_ROOM: &str = "default";
+
+/// How many events a room's SSE channel holds for a phone that's falling behind. Roomy
+/// enough for download progress from every download at once on top of everything a
+/// song change sends; a phone that still falls further behind gets resynced.
+const SSE_CHANNEL_CAPACITY: usize = 256;
+
+static ROOM_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9_-]{1,32}$").unwrap());
+
+pub struct Room {
+    pub id: String,
+    pub song_actor_handle: Arc<SongActorHandle>,
+    pub sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+}
+
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum RoomError {
+    #[error("invalid room id: {id}")]
+    InvalidRoomId { id: String },
+
+    #[error("room already exists: {id}")]
+    RoomAlreadyExists { id: String },
+
+    #[error("room not found: {id}")]
+    RoomNotFound { id: String },
+
+    #[error("the default room can't be closed")]
+    DefaultRoomNotClosable,
+}
+
+/// Every open room, each with its own song coordinator and SSE channel. All rooms share
+/// the one video downloader, so a song processed for one room is cached for the others.
+pub struct RoomRegistry {
+    rooms: RwLock<HashMap<String, Arc<Room>>>,
+    videodl_actor_handle: Arc<VideoDlActorHandle>,
+    playlist_library: Arc<PlaylistLibrary>,
+    data_dir: PathBuf,
+    rooms_store: SnapshotStore,
+}
+
+impl RoomRegistry {
+    pub fn new(
+        videodl_actor_handle: Arc<VideoDlActorHandle>,
+        playlist_library: Arc<PlaylistLibrary>,
+        data_dir: &Path,
+    ) -> Self {
+        let registry = RoomRegistry {
+            rooms: RwLock::new(HashMap::new()),
+            videodl_actor_handle,
+            playlist_library,
+            data_dir: data_dir.to_path_buf(),
+            rooms_store: SnapshotStore::new(data_dir.join("rooms.json")),
+        };
+
+        registry.open(DEFAULT_ROOM);
+
+        let saved_rooms: Vec<String> = registry.rooms_store.load().unwrap_or_default();
+        for room_id in saved_rooms {
+            if ROOM_ID.is_match(&room_id) && room_id != DEFAULT_ROOM {
+                info!("reopening room: {}", room_id);
+                registry.open(&room_id);
+            } else {
+                warn!("skipping saved room with invalid id: {}", room_id);
+            }
+        }
+
+        registry
+    }
+
+    /// The default room keeps its state directly in the data dir, where it lived before
+    /// there were rooms, every other room gets a folder of its own.
+    fn room_dir(&self, room_id: &str) -> PathBuf {
+        if room_id == DEFAULT_ROOM {
+            self.data_dir.clone()
+        } else {
+            self.data_dir.join("rooms").join(room_id)
+        }
+    }
+
+    fn open(&self, room_id: &str) -> Arc<Room> {
+        let room = self.build(room_id);
+        self.rooms
+            .write()
+            .expect("room registry lock poisoned")
+            .insert(room_id.to_string(), room.clone());
+
+        room
+    }
+
+    /// Starts a room's coordinator without registering it.
+    fn build(&self, room_id: &str) -> Arc<Room> {
+        let (sse_broadcaster, _) = sync::broadcast::channel(SSE_CHANNEL_CAPACITY);
+        let sse_broadcaster = Arc::new(sse_broadcaster);
+
+        Arc::new(Room {
+            id: room_id.to_string(),
+            song_actor_handle: Arc::new(SongActorHandle::new(
+                sse_broadcaster.clone(),
+                self.videodl_actor_handle.clone(),
+                self.playlist_library.clone(),
+                &self.room_dir(room_id),
+            )),
+            sse_broadcaster,
+        })
+    }
+
+    fn persist(&self) {
+        let room_ids: Vec<String> = self
+            .list()
+            .into_iter()
+            .filter(|room_id| room_id != DEFAULT_ROOM)
+            .collect();
+
+        if let Err(err) = self.rooms_store.save(&room_ids) {
+            error!("failed to persist rooms with error: {}", err);
+        }
+    }
+
+    pub fn get(&self, room_id: &str) -> Option<Arc<Room>> {
+        self.rooms
+            .read()
+            .expect("room registry lock poisoned")
+            .get(room_id)
+            .cloned()
+    }
+
+    pub fn list(&self) -> Vec<String> {
+        let mut room_ids: Vec<String> = self
+            .rooms
+            .read()
+            .expect("room registry lock poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        room_ids.sort();
+        room_ids
+    }
+
+    pub fn create(&self, room_id: &str) -> Result<Arc<Room>, RoomError> {
+        if !ROOM_ID.is_match(room_id) {
+            return Err(RoomError::InvalidRoomId {
+                id: room_id.to_string(),
+            });
+        }
+
+        // checked and inserted under one lock, so two requests for the same room can't
+        // both think they created it
+        let room = {
+            let mut rooms = self.rooms.write().expect("room registry lock poisoned");
+            if rooms.contains_key(room_id) {
+                return Err(RoomError::RoomAlreadyExists {
+                    id: room_id.to_string(),
+                });
+            }
+
+            let room = self.build(room_id);
+            rooms.insert(room_id.to_string(), room.clone());
+            room
+        };
+        self.persist();
+        info!("opened room: {}", room_id);
+
+        Ok(room)
+    }
+
+    /// Removes the room and wipes its queue and history. Displays and phones still
+    /// connected to it are told over SSE before their stream ends.
+    pub async fn close(&self, room_id: &str) -> Result<(), RoomError> {
+        if room_id == DEFAULT_ROOM {
+            return Err(RoomError::DefaultRoomNotClosable);
+        }
+
+        let room = self
+            .rooms
+            .write()
+            .expect("room registry lock poisoned")
+            .remove(room_id)
+            .ok_or_else(|| RoomError::RoomNotFound {
+                id: room_id.to_string(),
+            })?;
+        self.persist();
+
+        room.song_actor_handle.close().await;
+        let _ = room.sse_broadcaster.send(SseEvent::RoomClosed);
+
+        // only succeeds once the coordinator's files are gone, which is all we want
+        let _ = fs::remove_dir(self.room_dir(room_id));
+        info!("closed room: {}", room_id);
+
+        Ok(())
+    }
+}
+
+/// The room a request is for: the `{room}` segment of `/rooms/{room}/...`, or the
+/// default room for the top-level routes.
+pub struct CurrentRoom(pub Arc<Room>);
+
+impl FromRequestParts<AppState> for CurrentRoom {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let room_id = match RawPathParams::from_request_parts(parts, state).await {
+            Ok(params) => params
+                .iter()
+                .find(|(key, _)| *key == "room")
+                .map(|(_, value)| value.to_string()),
+            Err(_) => None,
+        }
+        .unwrap_or_else(|| DEFAULT_ROOM.to_string());
+
+        state
+            .room_registry
+            .get(&room_id)
+            .map(CurrentRoom)
+            .ok_or_else(|| RoomError::RoomNotFound { id: room_id }.into())
+    }
+}
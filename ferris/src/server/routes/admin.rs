@@ -1,48 +1,105 @@
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use serde::Deserialize;
-use tokio::sync;
-use uuid::Uuid;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::server::actors::song_coordinator::SongActorHandle;
+use crate::server::{
+    actors::song_coordinator::{
+        FailedSongPolicy, Interstitial, QueueLimits, QueueOrdering, RepeatPolicy, RequestDecision,
+        SkipThreshold,
+    },
+    playlist_library::PlaylistLibrary,
+    queue_revision::ExpectedRevision,
+    rooms::{CurrentRoom, Room},
+    routes::error::{parse_song_uuid, ApiError},
+    utils::{
+        clock::unix_timestamp,
+        idle_mode::{IdleMode, IdleSource},
+        playback::PlaybackStatus,
+        video_id::extract_video_id,
+    },
+};
 
-use super::sse::SseEvent;
+/// The most a single `/stage/extend` can add to a pause, in seconds.
+const MAX_STAGE_EXTENSION_SECONDS: u64 = 60 * 60;
 
-pub async fn toggle_playback(
-    State(sse_broadcaster): State<Arc<sync::broadcast::Sender<SseEvent>>>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let _ = sse_broadcaster.send(SseEvent::TogglePlayback);
-    Ok(StatusCode::ACCEPTED)
+/// How far ahead a break can be scheduled, in hours.
+const MAX_BREAK_LEAD_HOURS: u64 = 7 * 24;
+
+pub async fn play(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    set_playback_status(room, PlaybackStatus::Playing).await
+}
+
+pub async fn pause(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    set_playback_status(room, PlaybackStatus::Paused).await
+}
+
+async fn set_playback_status(
+    room: Arc<Room>,
+    status: PlaybackStatus,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_playback_status(status).await;
+    match song_actor_response {
+        Ok(playback) => Ok((StatusCode::OK, Json(playback))),
+        Err(err) => Err(err.into()),
+    }
 }
 
-pub async fn key_up(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let song_actor_response = song_actor_handle.key_up().await;
+pub async fn key_up(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.key_up().await;
     match song_actor_response {
         Ok(current_key) => Ok((StatusCode::OK, Json(current_key))),
-        Err(_) => Err(StatusCode::NOT_MODIFIED),
+        Err(err) => Err(err.into()),
     }
 }
 
-pub async fn key_down(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let song_actor_response = song_actor_handle.key_down().await;
+pub async fn key_down(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.key_down().await;
     match song_actor_response {
         Ok(current_key) => Ok((StatusCode::OK, Json(current_key))),
-        Err(_) => Err(StatusCode::NOT_MODIFIED),
+        Err(err) => Err(err.into()),
     }
 }
 
-pub async fn get_key(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let song_actor_response = song_actor_handle.get_key().await;
+pub async fn get_key(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_key().await;
     match song_actor_response {
         Ok(current_key) => Ok((StatusCode::OK, Json(current_key))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_key_memory(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_key_memory().await;
+    match song_actor_response {
+        Ok(key_memory) => Ok((StatusCode::OK, Json(key_memory))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ClearKeyMemoryQuery {
+    /// A video ID or YouTube link. Everything is forgotten when it's left out.
+    song: Option<String>,
+}
+
+pub async fn clear_key_memory(
+    CurrentRoom(room): CurrentRoom,
+    Query(query): Query<ClearKeyMemoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_id = query
+        .song
+        .map(|song| extract_video_id(&song).unwrap_or(song));
+
+    let song_actor_response = room.song_actor_handle.forget_keys(song_id).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -53,16 +110,20 @@ pub struct RepositionSongRequest {
 }
 
 pub async fn reposition_song(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
     Json(payload): Json<RepositionSongRequest>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let song_uuid = Uuid::parse_str(&payload.song_uuid).map_err(|_| StatusCode::BAD_REQUEST)?;
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
     let position = payload.position;
 
-    let song_actor_response = song_actor_handle.reposition_song(song_uuid, position).await;
+    let song_actor_response = room
+        .song_actor_handle
+        .reposition_song(song_uuid, position, expected_revision)
+        .await;
     match song_actor_response {
         Ok(_) => Ok(StatusCode::OK),
-        Err(_) => Err(StatusCode::NOT_MODIFIED),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -72,18 +133,425 @@ pub struct DeleteSongRequest {
 }
 
 pub async fn remove_song(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
     Json(payload): Json<DeleteSongRequest>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let song_uuid = Uuid::parse_str(&payload.song_uuid).map_err(|_| StatusCode::BAD_REQUEST)?;
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    let song_actor_response = room
+        .song_actor_handle
+        .remove_song(song_uuid, expected_revision)
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
 
-    song_actor_handle.remove_song(song_uuid).await;
-    Ok(StatusCode::OK)
+pub async fn clear_queue(
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.clear_queue(expected_revision).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn undo(
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.undo(expected_revision).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn redo(
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.redo(expected_revision).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
 }
 
-pub async fn restart_song(
-    State(sse_broadcaster): State<Arc<sync::broadcast::Sender<SseEvent>>>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let _ = sse_broadcaster.send(SseEvent::RestartSong);
-    Ok(StatusCode::ACCEPTED)
+pub async fn restart_song(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.restart_playback().await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::ACCEPTED),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_queue_ordering(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_ordering().await;
+    match song_actor_response {
+        Ok(ordering) => Ok((StatusCode::OK, Json(ordering))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetQueueOrderingRequest {
+    ordering: QueueOrdering,
+}
+
+pub async fn set_queue_ordering(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<SetQueueOrderingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_ordering(payload.ordering).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_repeat_policy(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_repeat_policy().await;
+    match song_actor_response {
+        Ok(repeat_policy) => Ok((StatusCode::OK, Json(repeat_policy))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn set_repeat_policy(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<RepeatPolicy>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_repeat_policy(payload).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_queue_limits(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_queue_limits().await;
+    match song_actor_response {
+        Ok(queue_limits) => Ok((StatusCode::OK, Json(queue_limits))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn set_queue_limits(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<QueueLimits>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_queue_limits(payload).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_skip_threshold(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_skip_threshold().await;
+    match song_actor_response {
+        Ok(skip_threshold) => Ok((StatusCode::OK, Json(skip_threshold))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn set_skip_threshold(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<SkipThreshold>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_skip_threshold(payload).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_failed_song_policy(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_failed_song_policy().await;
+    match song_actor_response {
+        Ok(failed_song_policy) => Ok((StatusCode::OK, Json(failed_song_policy))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetFailedSongPolicyRequest {
+    policy: FailedSongPolicy,
+}
+
+pub async fn set_failed_song_policy(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<SetFailedSongPolicyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room
+        .song_actor_handle
+        .set_failed_song_policy(payload.policy)
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_idle_mode(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_idle_mode().await;
+    match song_actor_response {
+        Ok(idle_mode) => Ok((StatusCode::OK, Json(idle_mode))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn set_idle_mode(
+    CurrentRoom(room): CurrentRoom,
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Json(payload): Json<IdleMode>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let IdleSource::Playlist(name) = &payload.source {
+        playlist_library.get(name)?;
+    }
+
+    let song_actor_response = room.song_actor_handle.set_idle_mode(payload).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RetrySongRequest {
+    song_uuid: String,
+}
+
+/// Downloads a failed song again, whether it's still in the queue or was parked.
+pub async fn retry_song(
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
+    Json(payload): Json<RetrySongRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    let song_actor_response = room
+        .song_actor_handle
+        .retry_song(song_uuid, expected_revision)
+        .await;
+    match song_actor_response {
+        Ok(song) => Ok((StatusCode::OK, Json(song))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn parked_songs(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_parked_songs().await;
+    match song_actor_response {
+        Ok(parked_songs) => Ok((StatusCode::OK, Json(parked_songs))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_moderation(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_moderation().await;
+    match song_actor_response {
+        Ok(enabled) => Ok((StatusCode::OK, Json(ModerationRequest { enabled }))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModerationRequest {
+    enabled: bool,
+}
+
+pub async fn set_moderation(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<ModerationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_moderation(payload.enabled).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn pending_songs(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_pending_songs().await;
+    match song_actor_response {
+        Ok(pending_songs) => Ok((StatusCode::OK, Json(pending_songs))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ApproveSongRequest {
+    song_uuid: String,
+    position: Option<usize>,
+}
+
+pub async fn approve_song(
+    CurrentRoom(room): CurrentRoom,
+    ExpectedRevision(expected_revision): ExpectedRevision,
+    Json(payload): Json<ApproveSongRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    let song_actor_response = room
+        .song_actor_handle
+        .decide_request(
+            song_uuid,
+            RequestDecision::Approved,
+            payload.position,
+            expected_revision,
+        )
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RejectSongRequest {
+    song_uuid: String,
+}
+
+pub async fn reject_song(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<RejectSongRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    let song_actor_response = room
+        .song_actor_handle
+        .decide_request(song_uuid, RequestDecision::Rejected, None, None)
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_stage(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_stage().await;
+    match song_actor_response {
+        Ok(stage) => Ok((StatusCode::OK, Json(stage))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn skip_stage(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.skip_stage().await;
+    match song_actor_response {
+        Ok(stage) => Ok((StatusCode::OK, Json(stage))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ExtendStageRequest {
+    seconds: u64,
+}
+
+pub async fn extend_stage(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<ExtendStageRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if payload.seconds == 0 || payload.seconds > MAX_STAGE_EXTENSION_SECONDS {
+        return Err(ApiError::InvalidStageExtension(MAX_STAGE_EXTENSION_SECONDS));
+    }
+
+    let song_actor_response = room.song_actor_handle.extend_stage(payload.seconds).await;
+    match song_actor_response {
+        Ok(stage) => Ok((StatusCode::OK, Json(stage))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_interstitial(
+    CurrentRoom(room): CurrentRoom,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_interstitial().await;
+    match song_actor_response {
+        Ok(interstitial) => Ok((StatusCode::OK, Json(interstitial))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn set_interstitial(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<Interstitial>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.set_interstitial(payload).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn breaks(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.get_breaks().await;
+    match song_actor_response {
+        Ok(breaks) => Ok((StatusCode::OK, Json(breaks))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleBreakRequest {
+    /// Unix seconds. Left out, the break starts once the current song is over.
+    #[serde(default)]
+    starts_at: Option<u64>,
+    minutes: u32,
+}
+
+pub async fn schedule_break(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<ScheduleBreakRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // a time in the past just means as soon as possible
+    let latest_start = unix_timestamp() + MAX_BREAK_LEAD_HOURS * 60 * 60;
+    if payload
+        .starts_at
+        .is_some_and(|starts_at| starts_at > latest_start)
+    {
+        return Err(ApiError::InvalidBreakStart(MAX_BREAK_LEAD_HOURS));
+    }
+
+    let song_actor_response = room
+        .song_actor_handle
+        .schedule_break(payload.starts_at, payload.minutes)
+        .await;
+    match song_actor_response {
+        Ok(scheduled) => Ok((StatusCode::OK, Json(scheduled))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BreakPath {
+    break_id: String,
+}
+
+pub async fn cancel_break(
+    CurrentRoom(room): CurrentRoom,
+    Path(path): Path<BreakPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_actor_response = room.song_actor_handle.cancel_break(path.break_id).await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
 }
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use tracing::{error, info};
+
+use crate::server::{
+    media_cache::{CacheLimits, MediaCache},
+    routes::error::ApiError,
+};
+
+pub async fn cache(State(media_cache): State<Arc<MediaCache>>) -> impl IntoResponse {
+    let summary = tokio::task::spawn_blocking(move || media_cache.summary())
+        .await
+        .expect("media cache summary panicked");
+    (StatusCode::OK, Json(summary))
+}
+
+pub async fn remove_cached_song(
+    State(media_cache): State<Arc<MediaCache>>,
+    Path(folder): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received remove_cached_song request: {}", folder);
+
+    let removed_folder = folder.clone();
+    let removed = tokio::task::spawn_blocking(move || media_cache.remove(&removed_folder))
+        .await
+        .expect("media cache removal panicked");
+    match removed {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => {
+            error!(
+                "unable to remove cached song: {} with error: {}",
+                folder, err
+            );
+            Err(err.into())
+        }
+    }
+}
+
+pub async fn get_cache_limits(State(media_cache): State<Arc<MediaCache>>) -> impl IntoResponse {
+    (StatusCode::OK, Json(media_cache.limits()))
+}
+
+pub async fn set_cache_limits(
+    State(media_cache): State<Arc<MediaCache>>,
+    Json(payload): Json<CacheLimits>,
+) -> impl IntoResponse {
+    info!("received set_cache_limits request: {:?}", payload);
+
+    let limits = tokio::task::spawn_blocking(move || media_cache.set_limits(payload))
+        .await
+        .expect("media cache eviction panicked");
+    (StatusCode::OK, Json(limits))
+}
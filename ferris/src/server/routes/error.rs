@@ -0,0 +1,258 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::server::{
+    actors::song_coordinator::SongCoordinatorError,
+    media_cache::MediaCacheError,
+    playlist_library::PlaylistError,
+    rooms::RoomError,
+    utils::{yt_downloader::VideoProcessError, yt_searcher::SearchError},
+};
+
+/// Everything a handler can fail with. Responds with a status code that fits the error
+/// and an [`ApiErrorBody`], so phones can show the actual reason.
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error(transparent)]
+    SongCoordinator(#[from] SongCoordinatorError),
+
+    #[error(transparent)]
+    VideoProcess(#[from] VideoProcessError),
+
+    #[error(transparent)]
+    Search(#[from] SearchError),
+
+    #[error(transparent)]
+    Room(#[from] RoomError),
+
+    #[error(transparent)]
+    Playlist(#[from] PlaylistError),
+
+    #[error(transparent)]
+    MediaCache(#[from] MediaCacheError),
+
+    #[error("invalid song uuid: {0}")]
+    InvalidSongUuid(String),
+
+    #[error("invalid queue revision: {0}")]
+    InvalidQueueRevision(String),
+
+    #[error("a name is needed to join a song")]
+    MissingSingerName,
+
+    #[error("this phone isn't recognized yet, reload the page and try again")]
+    UnknownRequester,
+
+    #[error("a pause can be extended by 1 to {0} seconds at a time")]
+    InvalidStageExtension(u64),
+
+    #[error("a break can be scheduled at most {0} hours ahead")]
+    InvalidBreakStart(u64),
+
+    #[error("invalid playlist file: {0}")]
+    InvalidPlaylistFile(String),
+
+    #[error("file not found: {0}")]
+    FileNotFound(String),
+
+    #[error("unable to read file: {0}")]
+    FileUnreadable(#[from] std::io::Error),
+
+    #[error("no network connection")]
+    NoNetwork,
+}
+
+#[derive(Serialize)]
+pub struct ApiErrorBody {
+    /// Stable, machine-readable name of the error, e.g. `song_already_queued`.
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::SongCoordinator(err) => match err {
+                SongCoordinatorError::QueueLimitExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+                SongCoordinatorError::SongAlreadyQueued { .. }
+                | SongCoordinatorError::SongPlayedTooRecently { .. }
+                | SongCoordinatorError::KeyUpFailed
+                | SongCoordinatorError::KeyDownFailed
+                | SongCoordinatorError::NothingToUndo
+                | SongCoordinatorError::NothingToRedo
+                | SongCoordinatorError::UndoFailed
+                | SongCoordinatorError::RedoFailed
+                | SongCoordinatorError::NothingPlaying
+                | SongCoordinatorError::AlreadyVotedToSkip
+                | SongCoordinatorError::StalePlaybackReport { .. }
+                | SongCoordinatorError::AlreadyPerforming { .. }
+                | SongCoordinatorError::TooManyPerformers { .. }
+                | SongCoordinatorError::NotBetweenSongs
+                | SongCoordinatorError::SongNotFailed { .. }
+                | SongCoordinatorError::StaleQueueRevision { .. } => StatusCode::CONFLICT,
+                SongCoordinatorError::RemoveSongFailed { .. }
+                | SongCoordinatorError::RepositionSongFailed { .. }
+                | SongCoordinatorError::UpdateSongStatusFailed { .. }
+                | SongCoordinatorError::KeyNotRemembered { .. }
+                | SongCoordinatorError::PendingSongNotFound { .. }
+                | SongCoordinatorError::JoinSongFailed { .. }
+                | SongCoordinatorError::LeaveSongFailed { .. }
+                | SongCoordinatorError::RetrySongFailed { .. }
+                | SongCoordinatorError::BreakNotFound { .. } => StatusCode::NOT_FOUND,
+                SongCoordinatorError::InvalidBreakLength { .. } => StatusCode::BAD_REQUEST,
+            },
+            // yt-dlp and ffmpeg are the upstream here
+            ApiError::VideoProcess(
+                VideoProcessError::DownloadTimeout(_) | VideoProcessError::ProcessingTimeout(_),
+            )
+            | ApiError::Search(SearchError::SearchTimeout(_)) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::VideoProcess(_) | ApiError::Search(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Room(err) => match err {
+                RoomError::InvalidRoomId { .. } | RoomError::DefaultRoomNotClosable => {
+                    StatusCode::BAD_REQUEST
+                }
+                RoomError::RoomAlreadyExists { .. } => StatusCode::CONFLICT,
+                RoomError::RoomNotFound { .. } => StatusCode::NOT_FOUND,
+            },
+            ApiError::Playlist(err) => match err {
+                PlaylistError::InvalidPlaylistName { .. } => StatusCode::BAD_REQUEST,
+                PlaylistError::PlaylistNotFound { .. } => StatusCode::NOT_FOUND,
+            },
+            ApiError::MediaCache(err) => match err {
+                MediaCacheError::CachedSongNotFound { .. } => StatusCode::NOT_FOUND,
+                MediaCacheError::CachedSongInUse { .. } => StatusCode::CONFLICT,
+                MediaCacheError::RemoveCachedSongFailed { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            ApiError::InvalidSongUuid(_)
+            | ApiError::InvalidQueueRevision(_)
+            | ApiError::MissingSingerName
+            | ApiError::InvalidStageExtension(_)
+            | ApiError::InvalidBreakStart(_)
+            | ApiError::InvalidPlaylistFile(_) => StatusCode::BAD_REQUEST,
+            ApiError::UnknownRequester => StatusCode::FORBIDDEN,
+            ApiError::FileNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::FileUnreadable(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::NoNetwork => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::SongCoordinator(err) => err.into(),
+            ApiError::VideoProcess(err) => err.into(),
+            ApiError::Search(err) => err.into(),
+            ApiError::Room(err) => err.into(),
+            ApiError::Playlist(err) => err.into(),
+            ApiError::MediaCache(err) => err.into(),
+            ApiError::InvalidSongUuid(_) => "invalid_song_uuid",
+            ApiError::InvalidQueueRevision(_) => "invalid_queue_revision",
+            ApiError::MissingSingerName => "missing_singer_name",
+            ApiError::UnknownRequester => "unknown_requester",
+            ApiError::InvalidStageExtension(_) => "invalid_stage_extension",
+            ApiError::InvalidBreakStart(_) => "invalid_break_start",
+            ApiError::InvalidPlaylistFile(_) => "invalid_playlist_file",
+            ApiError::FileNotFound(_) => "file_not_found",
+            ApiError::FileUnreadable(_) => "file_unreadable",
+            ApiError::NoNetwork => "no_network",
+        }
+    }
+
+    /// The values the message was built from, for errors that have any worth acting on.
+    fn details(&self) -> Option<Value> {
+        match self {
+            ApiError::SongCoordinator(err) => match err {
+                SongCoordinatorError::QueueLimitExceeded(violation) => {
+                    serde_json::to_value(violation).ok()
+                }
+                SongCoordinatorError::SongAlreadyQueued { name }
+                | SongCoordinatorError::SongPlayedTooRecently { name } => {
+                    Some(json!({ "name": name }))
+                }
+                SongCoordinatorError::RemoveSongFailed { uuid }
+                | SongCoordinatorError::RepositionSongFailed { uuid }
+                | SongCoordinatorError::UpdateSongStatusFailed { uuid }
+                | SongCoordinatorError::PendingSongNotFound { uuid }
+                | SongCoordinatorError::StalePlaybackReport { uuid }
+                | SongCoordinatorError::JoinSongFailed { uuid }
+                | SongCoordinatorError::AlreadyPerforming { uuid }
+                | SongCoordinatorError::LeaveSongFailed { uuid }
+                | SongCoordinatorError::RetrySongFailed { uuid }
+                | SongCoordinatorError::SongNotFailed { uuid } => {
+                    Some(json!({ "song_uuid": uuid.to_string() }))
+                }
+                SongCoordinatorError::KeyNotRemembered { song_id } => {
+                    Some(json!({ "song_id": song_id }))
+                }
+                SongCoordinatorError::BreakNotFound { id } => Some(json!({ "break": id })),
+                SongCoordinatorError::InvalidBreakLength { max_minutes } => {
+                    Some(json!({ "max_minutes": max_minutes }))
+                }
+                SongCoordinatorError::TooManyPerformers { max_performers } => {
+                    Some(json!({ "max_performers": max_performers }))
+                }
+                // the current queue, so the phone can catch up without asking again
+                SongCoordinatorError::StaleQueueRevision { current, .. } => {
+                    serde_json::to_value(current).ok()
+                }
+                _ => None,
+            },
+            ApiError::VideoProcess(
+                VideoProcessError::DownloadTimeout(seconds)
+                | VideoProcessError::ProcessingTimeout(seconds),
+            )
+            | ApiError::Search(SearchError::SearchTimeout(seconds)) => {
+                Some(json!({ "timeout_seconds": seconds }))
+            }
+            ApiError::Room(
+                RoomError::InvalidRoomId { id }
+                | RoomError::RoomAlreadyExists { id }
+                | RoomError::RoomNotFound { id },
+            ) => Some(json!({ "room": id })),
+            ApiError::Playlist(
+                PlaylistError::InvalidPlaylistName { name }
+                | PlaylistError::PlaylistNotFound { name },
+            ) => Some(json!({ "playlist": name })),
+            ApiError::MediaCache(
+                MediaCacheError::CachedSongNotFound { name }
+                | MediaCacheError::CachedSongInUse { name }
+                | MediaCacheError::RemoveCachedSongFailed { name, .. },
+            ) => Some(json!({ "name": name })),
+            ApiError::InvalidStageExtension(max_seconds) => {
+                Some(json!({ "max_seconds": max_seconds }))
+            }
+            ApiError::InvalidBreakStart(max_hours) => Some(json!({ "max_hours": max_hours })),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            error!("request failed with error: {}", self);
+        }
+
+        let body = ApiErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+pub fn parse_song_uuid(song_uuid: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(song_uuid).map_err(|_| ApiError::InvalidSongUuid(song_uuid.to_string()))
+}
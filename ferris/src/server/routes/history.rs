@@ -0,0 +1,82 @@
+use axum::{
+    extract::Query,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::server::{
+    rooms::CurrentRoom,
+    routes::error::ApiError,
+    utils::play_history::{history_to_csv, SessionStats},
+};
+
+pub async fn history(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_history().await {
+        Ok(history) => Ok((StatusCode::OK, Json(history))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Wipes the room's play history to start a new session.
+pub async fn clear_history(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.clear_history().await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn stats(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_history().await {
+        Ok(history) => Ok((StatusCode::OK, Json(SessionStats::from_history(&history)))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct ExportHistory {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+pub async fn export_history(
+    CurrentRoom(room): CurrentRoom,
+    export_request: Query<ExportHistory>,
+) -> Result<impl IntoResponse, ApiError> {
+    let history = room.song_actor_handle.get_history().await?;
+
+    let response = match export_request.format {
+        ExportFormat::Json => (
+            StatusCode::OK,
+            [(
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"history.json\"",
+            )],
+            Json(history),
+        )
+            .into_response(),
+        ExportFormat::Csv => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/csv"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"history.csv\"",
+                ),
+            ],
+            history_to_csv(&history),
+        )
+            .into_response(),
+    };
+
+    Ok(response)
+}
@@ -2,17 +2,23 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
-use crate::server::actors::{
-    song_coordinator::{QueuedSongStatus, Song, SongActorHandle},
-    video_downloader::VideoDlActorHandle,
-    video_searcher::VideoSearcherActorHandle,
+use crate::server::{
+    actors::{
+        song_coordinator::{Performer, QueueOutcome, QueuedSongStatus, Song},
+        video_searcher::VideoSearcherActorHandle,
+    },
+    queue_revision::revision_etag,
+    requester::{KnownRequester, Requester},
+    rooms::CurrentRoom,
+    routes::error::{parse_song_uuid, ApiError},
+    utils::playback::PlaybackStatus,
 };
 
 #[derive(Deserialize)]
@@ -20,136 +26,242 @@ pub struct QueueSong {
     name: String,
     yt_link: String,
     is_key_changeable: bool,
+    #[serde(default)]
+    singer: Option<String>,
+}
+
+#[derive(Serialize)]
+struct QueueSongAccepted {
+    outcome: QueueOutcome,
 }
 
 pub async fn queue_song(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-    State(videodl_actor_handle): State<Arc<VideoDlActorHandle>>,
+    CurrentRoom(room): CurrentRoom,
+    KnownRequester(requester): KnownRequester,
     Json(payload): Json<QueueSong>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     let queueable_song = Song::new(
         payload.name,
         payload.yt_link,
         QueuedSongStatus::InProgress,
         payload.is_key_changeable,
+        payload
+            .singer
+            .map(|singer| singer.trim().to_string())
+            .filter(|singer| !singer.is_empty()),
+        Some(requester),
     );
     info!("received queue_song request: {}", queueable_song);
 
-    match song_actor_handle.queue_song(queueable_song.clone()).await {
-        Ok(_) => {
-            info!("successfully queued song: {}", queueable_song.uuid);
-
-            tokio::spawn(async move {
-                match videodl_actor_handle
-                    .download_video(
-                        queueable_song.yt_link,
-                        queueable_song.name.to_string(),
-                        queueable_song.is_key_changeable,
-                    )
-                    .await
-                {
-                    Ok(video_file_path) => {
-                        info!("successfully downloaded video in: {}", video_file_path);
-
-                        match song_actor_handle
-                            .update_song_status(queueable_song.uuid, QueuedSongStatus::Success)
-                            .await
-                        {
-                            Ok(_) => {
-                                info!(
-                                    "successfully updated song: {} with status: {}",
-                                    queueable_song.uuid,
-                                    QueuedSongStatus::Success
-                                );
-                            }
-                            Err(err) => {
-                                error!(
-                                    "unable to update status for song: {} with error: {}",
-                                    queueable_song.uuid, err
-                                );
-                            }
-                        }
-
-                        std::fs::remove_file(&video_file_path).unwrap_or_else(|err| {
-                            error!(
-                                "unable to delete file {} with error: {}",
-                                &video_file_path, err
-                            );
-                        });
-                    }
-                    Err(err) => {
-                        error!(
-                            "could not download video for song: {} with error: {}",
-                            queueable_song.uuid, err
-                        );
-
-                        match song_actor_handle
-                            .update_song_status(queueable_song.uuid, QueuedSongStatus::Failed)
-                            .await
-                        {
-                            Ok(_) => {
-                                info!(
-                                    "successfully updated song: {} with status: {}",
-                                    queueable_song.uuid,
-                                    QueuedSongStatus::Failed
-                                );
-                            }
-                            Err(err) => {
-                                error!(
-                                    "unable to update status for song: {} with error: {}",
-                                    queueable_song.uuid, err
-                                );
-                            }
-                        }
-                    }
-                }
-            });
+    match room
+        .song_actor_handle
+        .queue_song(queueable_song.clone())
+        .await
+    {
+        Ok(outcome) => {
+            info!(
+                "successfully queued song: {} ({})",
+                queueable_song.uuid, outcome
+            );
+            // pending songs download too, so they're ready as soon as they're approved
+            room.song_actor_handle.download_song(queueable_song);
+            Ok((StatusCode::ACCEPTED, Json(QueueSongAccepted { outcome })))
         }
         Err(err) => {
             error!(
                 "unable to queue song: {} with error: {}",
                 queueable_song.uuid, err
             );
+            Err(err.into())
         }
     }
+}
 
-    StatusCode::ACCEPTED
+#[derive(Deserialize)]
+pub struct PlayNextSong {
+    /// The song the caller thinks is playing. When given, nothing happens unless it
+    /// still is, so a display finishing a song the server already moved past is harmless.
+    song_uuid: Option<String>,
 }
 
 pub async fn play_next_song(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-) -> impl IntoResponse {
+    CurrentRoom(room): CurrentRoom,
+    Query(query): Query<PlayNextSong>,
+) -> Result<impl IntoResponse, ApiError> {
     info!("received play_next_song request");
 
-    match song_actor_handle.pop_song().await {
+    let expected_current = query
+        .song_uuid
+        .as_deref()
+        .map(parse_song_uuid)
+        .transpose()?;
+
+    match room.song_actor_handle.pop_song(expected_current).await {
         Some(song) => {
             info!("successfully popped song: {}", song);
-            StatusCode::OK
+            Ok(StatusCode::OK)
         }
         None => {
             info!("successfully popped song: {}", "none");
-            StatusCode::OK
+            Ok(StatusCode::OK)
         }
     }
 }
 
-pub async fn song_list(State(song_actor_handle): State<Arc<SongActorHandle>>) -> impl IntoResponse {
-    match song_actor_handle.get_queue().await {
-        Ok(list_of_songs) => (StatusCode::OK, Json(list_of_songs)).into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+pub async fn idle_song(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_idle_song().await {
+        Ok(song) => Ok((StatusCode::OK, Json(song))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Called by the display when it finishes an idle song, with the same `song_uuid`
+/// check as `play_next`.
+pub async fn next_idle_song(
+    CurrentRoom(room): CurrentRoom,
+    Query(query): Query<PlayNextSong>,
+) -> Result<impl IntoResponse, ApiError> {
+    let expected_current = query
+        .song_uuid
+        .as_deref()
+        .map(parse_song_uuid)
+        .transpose()?;
+
+    match room
+        .song_actor_handle
+        .next_idle_song(expected_current)
+        .await
+    {
+        Ok(song) => Ok((StatusCode::OK, Json(song))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn song_list(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_queue().await {
+        Ok(queue_state) => Ok((
+            StatusCode::OK,
+            [(header::ETAG, revision_etag(queue_state.revision))],
+            Json(queue_state),
+        )),
+        Err(err) => Err(err.into()),
     }
 }
 
-pub async fn current_song(
-    State(song_actor_handle): State<Arc<SongActorHandle>>,
-) -> impl IntoResponse {
-    let song_actor_response = song_actor_handle.current_song().await;
+pub async fn current_song(CurrentRoom(room): CurrentRoom) -> Result<Response, ApiError> {
+    let song_actor_response = room.song_actor_handle.current_song().await;
     match song_actor_response {
         Ok(current_song) => match current_song {
-            Some(current_song) => (StatusCode::OK, Json(current_song)).into_response(),
-            None => StatusCode::NO_CONTENT.into_response(),
+            Some(current_song) => Ok((StatusCode::OK, Json(current_song)).into_response()),
+            None => Ok(StatusCode::NO_CONTENT.into_response()),
         },
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn vote_skip(
+    CurrentRoom(room): CurrentRoom,
+    KnownRequester(requester): KnownRequester,
+) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.vote_skip(requester).await {
+        Ok(skip_votes) => Ok((StatusCode::OK, Json(skip_votes))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct JoinSongRequest {
+    song_uuid: String,
+    singer: String,
+}
+
+pub async fn join_song(
+    CurrentRoom(room): CurrentRoom,
+    KnownRequester(requester): KnownRequester,
+    Json(payload): Json<JoinSongRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+    let name = payload.singer.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::MissingSingerName);
+    }
+
+    let song_actor_response = room
+        .song_actor_handle
+        .join_song(song_uuid, Performer { name, requester })
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LeaveSongRequest {
+    song_uuid: String,
+}
+
+pub async fn leave_song(
+    CurrentRoom(room): CurrentRoom,
+    Requester(requester): Requester,
+    Json(payload): Json<LeaveSongRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    let song_actor_response = room
+        .song_actor_handle
+        .leave_song(song_uuid, requester)
+        .await;
+    match song_actor_response {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn own_songs(
+    CurrentRoom(room): CurrentRoom,
+    Requester(requester): Requester,
+) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_own_songs(requester).await {
+        Ok(own_songs) => Ok((StatusCode::OK, Json(own_songs))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn skip_votes(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_skip_votes().await {
+        Ok(skip_votes) => Ok((StatusCode::OK, Json(skip_votes))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn get_playback(CurrentRoom(room): CurrentRoom) -> Result<impl IntoResponse, ApiError> {
+    match room.song_actor_handle.get_playback().await {
+        Ok(playback) => Ok((StatusCode::OK, Json(playback))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReportPlayback {
+    song_uuid: String,
+    status: PlaybackStatus,
+    position_seconds: f64,
+}
+
+pub async fn report_playback(
+    CurrentRoom(room): CurrentRoom,
+    Json(payload): Json<ReportPlayback>,
+) -> Result<impl IntoResponse, ApiError> {
+    let song_uuid = parse_song_uuid(&payload.song_uuid)?;
+
+    match room
+        .song_actor_handle
+        .report_playback(song_uuid, payload.status, payload.position_seconds)
+        .await
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -161,15 +273,15 @@ pub struct SearchSong {
 pub async fn search(
     State(videosearcher_actor_handle): State<Arc<VideoSearcherActorHandle>>,
     search_request: Query<SearchSong>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     match videosearcher_actor_handle
         .search_videos(&search_request.query)
         .await
     {
-        Ok(results) => (StatusCode::OK, Json(results)).into_response(),
-        Err(_) => {
+        Ok(results) => Ok((StatusCode::OK, Json(results))),
+        Err(err) => {
             error!("search failed for {}", search_request.query);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            Err(err.into())
         }
     }
 }
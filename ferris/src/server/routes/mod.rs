@@ -1,6 +1,11 @@
 pub mod admin;
+pub mod cache;
+pub mod error;
 pub mod healthcheck;
+pub mod history;
 pub mod karaoke;
+pub mod playlists;
+pub mod rooms;
 pub mod sse;
 pub mod streaming;
 pub mod sys;
@@ -0,0 +1,348 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::server::{
+    actors::{
+        song_coordinator::{QueueOutcome, QueuedSongStatus, Song},
+        video_searcher::VideoSearcherActorHandle,
+    },
+    playlist_library::PlaylistLibrary,
+    requester::KnownRequester,
+    rooms::CurrentRoom,
+    routes::error::ApiError,
+    utils::playlist::{
+        playlist_from_csv, playlist_from_m3u, playlist_to_csv, playlist_to_m3u, Playlist,
+        PlaylistEntry,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct PlaylistPath {
+    playlist: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistFormat {
+    #[default]
+    Json,
+    M3u,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct PlaylistFormatQuery {
+    #[serde(default)]
+    format: PlaylistFormat,
+}
+
+pub async fn list_playlists(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+) -> impl IntoResponse {
+    (StatusCode::OK, Json(playlist_library.list()))
+}
+
+pub async fn get_playlist(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    match playlist_library.get(&path.playlist) {
+        Ok(playlist) => Ok((StatusCode::OK, Json(playlist))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SavePlaylistRequest {
+    entries: Vec<PlaylistEntry>,
+}
+
+pub async fn save_playlist(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+    Json(payload): Json<SavePlaylistRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received save_playlist request: {}", path.playlist);
+
+    match playlist_library.save(&path.playlist, payload.entries) {
+        Ok(playlist) => Ok((StatusCode::OK, Json(playlist))),
+        Err(err) => {
+            error!(
+                "unable to save playlist: {} with error: {}",
+                path.playlist, err
+            );
+            Err(err.into())
+        }
+    }
+}
+
+pub async fn delete_playlist(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received delete_playlist request: {}", path.playlist);
+
+    match playlist_library.delete(&path.playlist) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn export_playlist(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+    export_request: Query<PlaylistFormatQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let playlist = playlist_library.get(&path.playlist)?;
+
+    let (content_type, extension, body) = match export_request.format {
+        PlaylistFormat::Json => (
+            "application/json",
+            "json",
+            serde_json::to_string_pretty(&playlist).unwrap_or_default(),
+        ),
+        PlaylistFormat::M3u => ("audio/x-mpegurl", "m3u", playlist_to_m3u(&playlist)),
+        PlaylistFormat::Csv => ("text/csv", "csv", playlist_to_csv(&playlist)),
+    };
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}.{}\"",
+                    playlist.name.replace('"', ""),
+                    extension
+                ),
+            ),
+        ],
+        body,
+    ))
+}
+
+#[derive(Serialize)]
+struct ImportedPlaylist {
+    playlist: Playlist,
+    /// CSV rows without a URL that searching YouTube turned up nothing for.
+    unresolved: Vec<String>,
+}
+
+/// Imports a playlist from the request body, replacing any playlist with the same name.
+pub async fn import_playlist(
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    State(videosearcher_actor_handle): State<Arc<VideoSearcherActorHandle>>,
+    Path(path): Path<PlaylistPath>,
+    import_request: Query<PlaylistFormatQuery>,
+    body: String,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received import_playlist request: {}", path.playlist);
+
+    let mut unresolved = Vec::new();
+    let entries = match import_request.format {
+        PlaylistFormat::Json => match serde_json::from_str::<Playlist>(&body) {
+            Ok(playlist) => playlist.entries,
+            Err(err) => {
+                warn!("unable to parse imported playlist with error: {}", err);
+                return Err(ApiError::InvalidPlaylistFile(err.to_string()));
+            }
+        },
+        PlaylistFormat::M3u => playlist_from_m3u(&body),
+        PlaylistFormat::Csv => {
+            let rows = playlist_from_csv(&body);
+            let links = join_all(rows.iter().map(|row| async {
+                match &row.url {
+                    Some(url) => Some(url.clone()),
+                    None => videosearcher_actor_handle
+                        .search_videos(&row.search_query())
+                        .await
+                        .ok()
+                        .and_then(|results| results.into_iter().next())
+                        .map(|result| result.url),
+                }
+            }))
+            .await;
+
+            let mut entries = Vec::new();
+            for (row, link) in rows.into_iter().zip(links) {
+                match link {
+                    Some(link) => entries.push(row.into_entry(link)),
+                    None => unresolved.push(row.search_query()),
+                }
+            }
+            entries
+        }
+    };
+
+    match playlist_library.save(&path.playlist, entries) {
+        Ok(playlist) => Ok((
+            StatusCode::OK,
+            Json(ImportedPlaylist {
+                playlist,
+                unresolved,
+            }),
+        )),
+        Err(err) => {
+            error!(
+                "unable to import playlist: {} with error: {}",
+                path.playlist, err
+            );
+            Err(err.into())
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistSource {
+    Queue,
+    History,
+}
+
+#[derive(Deserialize)]
+pub struct SaveRoomPlaylistRequest {
+    source: PlaylistSource,
+}
+
+/// Saves the room's queue, or every song it has played so far, as a playlist.
+pub async fn save_room_playlist(
+    CurrentRoom(room): CurrentRoom,
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+    Json(payload): Json<SaveRoomPlaylistRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!(
+        "received save_room_playlist request: {} in room {}",
+        path.playlist, room.id
+    );
+
+    let entries: Vec<PlaylistEntry> = match payload.source {
+        PlaylistSource::Queue => room
+            .song_actor_handle
+            .get_queue()
+            .await?
+            .queue
+            .into_iter()
+            .map(|entry| PlaylistEntry {
+                title: entry.song.name.replace('_', " "),
+                artist: None,
+                yt_link: entry.song.yt_link,
+                is_key_changeable: entry.song.is_key_changeable,
+            })
+            .collect(),
+        PlaylistSource::History => {
+            let mut seen = HashSet::new();
+            room.song_actor_handle
+                .get_history()
+                .await?
+                .into_iter()
+                .filter(|played| seen.insert(played.yt_link.clone()))
+                .map(|played| PlaylistEntry {
+                    title: played.name.replace('_', " "),
+                    artist: None,
+                    yt_link: played.yt_link,
+                    is_key_changeable: false,
+                })
+                .collect()
+        }
+    };
+
+    match playlist_library.save(&path.playlist, entries) {
+        Ok(playlist) => Ok((StatusCode::OK, Json(playlist))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QueuePlaylistRequest {
+    /// Which songs to queue, by position in the playlist. All of them when left out.
+    #[serde(default)]
+    positions: Option<Vec<usize>>,
+    #[serde(default)]
+    singer: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RejectedEntry {
+    title: String,
+    message: String,
+}
+
+#[derive(Serialize, Default)]
+struct QueuedPlaylist {
+    queued: usize,
+    pending: usize,
+    rejected: Vec<RejectedEntry>,
+}
+
+/// Queues a playlist song by song, as if each had been requested on its own, so queue
+/// limits, duplicate checks and moderation all still apply.
+pub async fn queue_playlist(
+    CurrentRoom(room): CurrentRoom,
+    State(playlist_library): State<Arc<PlaylistLibrary>>,
+    Path(path): Path<PlaylistPath>,
+    KnownRequester(requester): KnownRequester,
+    Json(payload): Json<QueuePlaylistRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!(
+        "received queue_playlist request: {} in room {}",
+        path.playlist, room.id
+    );
+
+    let playlist = playlist_library.get(&path.playlist)?;
+    let entries: Vec<&PlaylistEntry> = match &payload.positions {
+        Some(positions) => positions
+            .iter()
+            .filter_map(|position| playlist.entries.get(*position))
+            .collect(),
+        None => playlist.entries.iter().collect(),
+    };
+    let singer = payload
+        .singer
+        .map(|singer| singer.trim().to_string())
+        .filter(|singer| !singer.is_empty());
+
+    let mut summary = QueuedPlaylist::default();
+    for entry in entries {
+        let song = Song::new(
+            entry.song_name(),
+            entry.yt_link.clone(),
+            QueuedSongStatus::InProgress,
+            entry.is_key_changeable,
+            singer.clone(),
+            Some(requester.clone()),
+        );
+
+        match room.song_actor_handle.queue_song(song.clone()).await {
+            Ok(outcome) => {
+                match outcome {
+                    QueueOutcome::Queued => summary.queued += 1,
+                    QueueOutcome::Pending => summary.pending += 1,
+                }
+                room.song_actor_handle.download_song(song);
+            }
+            Err(err) => {
+                warn!(
+                    "unable to queue playlist song: {} with error: {}",
+                    song, err
+                );
+                summary.rejected.push(RejectedEntry {
+                    title: entry.title.clone(),
+                    message: err.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok((StatusCode::OK, Json(summary)))
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::server::{rooms::RoomRegistry, routes::error::ApiError};
+
+pub async fn list_rooms(State(room_registry): State<Arc<RoomRegistry>>) -> impl IntoResponse {
+    (StatusCode::OK, Json(room_registry.list()))
+}
+
+#[derive(Deserialize)]
+pub struct CreateRoomRequest {
+    room: String,
+}
+
+pub async fn create_room(
+    State(room_registry): State<Arc<RoomRegistry>>,
+    Json(payload): Json<CreateRoomRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received create_room request: {}", payload.room);
+
+    match room_registry.create(&payload.room) {
+        Ok(room) => Ok((StatusCode::CREATED, Json(room.id.clone()))),
+        Err(err) => {
+            error!(
+                "unable to create room: {} with error: {}",
+                payload.room, err
+            );
+            Err(err.into())
+        }
+    }
+}
+
+pub async fn close_room(
+    State(room_registry): State<Arc<RoomRegistry>>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("received close_room request: {}", room_id);
+
+    match room_registry.close(&room_id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(err) => {
+            error!("unable to close room: {} with error: {}", room_id, err);
+            Err(err.into())
+        }
+    }
+}
@@ -1,39 +1,115 @@
-use std::{collections::VecDeque, convert::Infallible, sync::Arc};
+use std::{convert::Infallible, sync::Arc};
 
-use axum::{
-    extract::State,
-    response::{
-        sse::{Event, KeepAlive},
-        Sse,
-    },
+use axum::response::{
+    sse::{Event, KeepAlive},
+    Sse,
 };
 use futures_util::{stream, StreamExt};
-use tokio::sync;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tracing::warn;
 
-use crate::server::actors::song_coordinator::Song;
+use crate::server::{
+    actors::song_coordinator::{QueueEntry, RequestDecision, SkipVotes, SkippedSong, Song},
+    rooms::{CurrentRoom, Room},
+    utils::{
+        idle_mode::IdleSong, playback::PlaybackState, progress::ProcessingPhase, stage::StageState,
+    },
+};
 
 #[derive(Clone, serde::Serialize)]
 #[serde(tag = "type")]
 pub enum SseEvent {
-    QueueUpdated { queue: VecDeque<Song> },
-    KeyChange { current_key: i8 },
-    TogglePlayback,
+    QueueUpdated {
+        revision: u64,
+        queue: Vec<QueueEntry>,
+    },
+    KeyChange {
+        current_key: i8,
+    },
+    PlaybackChanged {
+        playback: PlaybackState,
+    },
     RestartSong,
+    SkipVotesUpdated {
+        skip_votes: SkipVotes,
+    },
+    SongsSkipped {
+        skipped: Vec<SkippedSong>,
+    },
+    PendingUpdated {
+        pending: Vec<Song>,
+    },
+    RequestDecided {
+        song: Song,
+        decision: RequestDecision,
+    },
+    /// A countdown or break started or ended, or its end moved.
+    StageChanged {
+        stage: StageState,
+    },
+    /// How far along a queued song's download is, sent every so often while it runs.
+    DownloadProgress {
+        song_uuid: String,
+        phase: ProcessingPhase,
+        percent: Option<f64>,
+    },
+    /// Idle mode moved on to another song, or stopped when `song` is `None`.
+    IdleSongChanged {
+        song: Option<IdleSong>,
+    },
+    RoomClosed,
 }
 
 pub async fn sse(
-    State(sse_broadcaster): State<Arc<sync::broadcast::Sender<SseEvent>>>,
+    CurrentRoom(room): CurrentRoom,
 ) -> Sse<impl stream::Stream<Item = Result<Event, Infallible>>> {
-    let stream = tokio_stream::wrappers::BroadcastStream::new(sse_broadcaster.subscribe())
-        .filter_map(|result| async move {
-            match result {
-                Ok(sse_event) => {
-                    let event_json = serde_json::to_string(&sse_event).ok()?;
-                    Some(Ok(Event::default().data(event_json)))
+    let receiver = room.sse_broadcaster.subscribe();
+    let stream = tokio_stream::wrappers::BroadcastStream::new(receiver)
+        .then(move |result| {
+            let room = room.clone();
+            async move {
+                match result {
+                    Ok(sse_event) => vec![sse_event],
+                    Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                        warn!("SSE client fell {} events behind, resyncing", missed);
+                        resync_events(&room).await
+                    }
                 }
-                Err(_) => None,
             }
+        })
+        .flat_map(stream::iter)
+        .filter_map(|sse_event| async move {
+            let event_json = serde_json::to_string(&sse_event).ok()?;
+            Some(Ok(Event::default().data(event_json)))
         });
 
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
+
+/// Events that bring a phone that missed some back up to date with the room, in place
+/// of the ones it missed.
+async fn resync_events(room: &Arc<Room>) -> Vec<SseEvent> {
+    let handle = &room.song_actor_handle;
+    let mut events = Vec::new();
+
+    if let Ok(queue_state) = handle.get_queue().await {
+        events.push(SseEvent::QueueUpdated {
+            revision: queue_state.revision,
+            queue: queue_state.queue,
+        });
+    }
+    if let Ok(playback) = handle.get_playback().await {
+        events.push(SseEvent::PlaybackChanged { playback });
+    }
+    if let Ok(stage) = handle.get_stage().await {
+        events.push(SseEvent::StageChanged { stage });
+    }
+    if let Ok(current_key) = handle.get_key().await {
+        events.push(SseEvent::KeyChange { current_key });
+    }
+    if let Ok(skip_votes) = handle.get_skip_votes().await {
+        events.push(SseEvent::SkipVotesUpdated { skip_votes });
+    }
+
+    events
+}
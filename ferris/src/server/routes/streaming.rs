@@ -3,33 +3,25 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use std::path::PathBuf;
+use std::{io, path::PathBuf};
 use tokio::{fs::File, io::AsyncReadExt};
 
-#[derive(Debug)]
-pub struct FileError(std::io::Error);
-
-impl IntoResponse for FileError {
-    fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("File error: {}", self.0),
-        )
-            .into_response()
-    }
-}
+use crate::server::routes::error::ApiError;
 
 pub async fn serve_dash_file(
-    Path((song_name, file)): Path<(String, String)>,
-) -> Result<Response, FileError> {
+    Path((song_name, file_name)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
     let path = PathBuf::from("./")
         .join("assets")
         .join(&song_name)
-        .join(&file);
+        .join(&file_name);
 
-    let mut file = File::open(&path).await.map_err(FileError)?;
+    let mut file = File::open(&path).await.map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => ApiError::FileNotFound(format!("{}/{}", song_name, file_name)),
+        _ => ApiError::FileUnreadable(err),
+    })?;
     let mut contents = vec![];
-    file.read_to_end(&mut contents).await.map_err(FileError)?;
+    file.read_to_end(&mut contents).await?;
 
     let content_type = match path.extension().and_then(|ext| ext.to_str()) {
         Some("mpd") => "application/dash+xml",
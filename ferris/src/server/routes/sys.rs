@@ -1,8 +1,14 @@
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use local_ip_address::local_ip;
 use serde::Serialize;
+use std::{path::Path, time::Duration};
+use tokio::process::Command;
 use tracing::debug;
-use std::path::Path;
+
+use crate::server::{routes::error::ApiError, utils::process_runner::run};
+
+/// systemctl and ps answer right away, unless something's badly wrong with the system.
+const SYSTEM_TOOL_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Serialize)]
 struct ServerIpResponse {
@@ -15,12 +21,12 @@ struct AutoApStatusResponse {
     web_server_port: Option<u16>,
 }
 
-pub async fn server_ip() -> Result<impl IntoResponse, StatusCode> {
+pub async fn server_ip() -> Result<impl IntoResponse, ApiError> {
     let my_local_ip = match local_ip() {
         Ok(ip) => ip,
         Err(_) => {
             debug!("Could not determine local IP address - likely no network connection");
-            return Err(StatusCode::SERVICE_UNAVAILABLE);
+            return Err(ApiError::NoNetwork);
         }
     };
 
@@ -34,13 +40,13 @@ pub async fn server_ip() -> Result<impl IntoResponse, StatusCode> {
     ))
 }
 
-pub async fn autoap_status() -> Result<impl IntoResponse, StatusCode> {    
+pub async fn autoap_status() -> Result<impl IntoResponse, ApiError> {
     // Check if autoap is running by looking for runtime indicators
     let is_running =
         // Check for autoap runtime files (lock files, service status, etc.)
         Path::new("/var/run/autoAP.locked").exists() ||
         Path::new("/var/run/autoAP.unlock").exists() ||
-        is_autoap_service_active();
+        is_autoap_service_active().await;
 
     // Try to detect the web server port if autoap is running
     let web_server_port = if is_running {
@@ -49,7 +55,10 @@ pub async fn autoap_status() -> Result<impl IntoResponse, StatusCode> {
         None
     };
 
-    debug!("AutoAP status - running: {}, port: {:?}", is_running, web_server_port);
+    debug!(
+        "AutoAP status - running: {}, port: {:?}",
+        is_running, web_server_port
+    );
 
     Ok((
         StatusCode::OK,
@@ -60,12 +69,12 @@ pub async fn autoap_status() -> Result<impl IntoResponse, StatusCode> {
     ))
 }
 
-fn is_autoap_service_active() -> bool {
+async fn is_autoap_service_active() -> bool {
     // Check if the wpa-autoap@wlan0 service is active
-    let output = std::process::Command::new("systemctl")
-        .args(["is-active", "wpa-autoap@wlan0"])
-        .output();
-    
+    let mut command = Command::new("systemctl");
+    command.args(["is-active", "wpa-autoap@wlan0"]);
+    let output = run(command, SYSTEM_TOOL_TIMEOUT).await;
+
     match output {
         Ok(result) => result.status.success(),
         Err(_) => false,
@@ -77,15 +86,13 @@ async fn detect_autoap_web_server_port() -> Option<u16> {
     if test_port_connectivity(8080).await {
         return Some(8080);
     }
-    
+
     // If not on default port, try to detect from process list
     // Look for autoap processes with port arguments
-    if let Ok(output) = std::process::Command::new("ps")
-        .args(["aux"])
-        .output()
-    {
-        let ps_output = String::from_utf8_lossy(&output.stdout);
-        for line in ps_output.lines() {
+    let mut command = Command::new("ps");
+    command.arg("aux");
+    if let Ok(output) = run(command, SYSTEM_TOOL_TIMEOUT).await {
+        for line in output.stdout.lines() {
             if line.contains("autoap") && (line.contains("start") || line.contains("web")) {
                 // Try to extract port from command line
                 if let Some(port) = extract_port_from_command_line(line) {
@@ -96,16 +103,15 @@ async fn detect_autoap_web_server_port() -> Option<u16> {
             }
         }
     }
-    
+
     // If we can't detect the port, return the default as a fallback
     Some(8080)
 }
 
 async fn test_port_connectivity(port: u16) -> bool {
-    use std::time::Duration;
     use tokio::net::TcpStream;
     use tokio::time::timeout;
-    
+
     let addr = format!("127.0.0.1:{}", port);
     match timeout(Duration::from_millis(500), TcpStream::connect(addr)).await {
         Ok(Ok(_)) => true,
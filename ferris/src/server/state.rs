@@ -1,43 +1,48 @@
 use std::sync::Arc;
 
 use axum::extract::FromRef;
-use tokio::sync;
+use axum_extra::extract::cookie::Key;
 
 use crate::server::{
-    actors::{
-        song_coordinator::SongActorHandle, video_downloader::VideoDlActorHandle,
-        video_searcher::VideoSearcherActorHandle,
-    },
-    routes::sse::SseEvent,
+    actors::{video_downloader::VideoDlActorHandle, video_searcher::VideoSearcherActorHandle},
+    media_cache::MediaCache,
+    playlist_library::PlaylistLibrary,
+    rooms::RoomRegistry,
 };
 
 #[derive(Clone)]
 pub struct AppState {
-    pub song_actor_handle: Arc<SongActorHandle>,
+    pub room_registry: Arc<RoomRegistry>,
     pub videodl_actor_handle: Arc<VideoDlActorHandle>,
     pub videosearcher_actor_handle: Arc<VideoSearcherActorHandle>,
-    pub sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+    pub playlist_library: Arc<PlaylistLibrary>,
+    pub media_cache: Arc<MediaCache>,
+    pub requester_key: Key,
 }
 
 impl AppState {
     pub fn new(
-        song_actor_handle: Arc<SongActorHandle>,
+        room_registry: Arc<RoomRegistry>,
         videodl_actor_handle: Arc<VideoDlActorHandle>,
         videosearcher_actor_handle: Arc<VideoSearcherActorHandle>,
-        sse_broadcaster: Arc<sync::broadcast::Sender<SseEvent>>,
+        playlist_library: Arc<PlaylistLibrary>,
+        media_cache: Arc<MediaCache>,
+        requester_key: Key,
     ) -> Self {
         AppState {
-            song_actor_handle,
+            room_registry,
             videodl_actor_handle,
             videosearcher_actor_handle,
-            sse_broadcaster,
+            playlist_library,
+            media_cache,
+            requester_key,
         }
     }
 }
 
-impl FromRef<AppState> for Arc<SongActorHandle> {
+impl FromRef<AppState> for Arc<RoomRegistry> {
     fn from_ref(app_state: &AppState) -> Self {
-        app_state.song_actor_handle.clone()
+        app_state.room_registry.clone()
     }
 }
 
@@ -53,8 +58,20 @@ impl FromRef<AppState> for Arc<VideoSearcherActorHandle> {
     }
 }
 
-impl FromRef<AppState> for Arc<sync::broadcast::Sender<SseEvent>> {
+impl FromRef<AppState> for Arc<PlaylistLibrary> {
     fn from_ref(app_state: &AppState) -> Self {
-        app_state.sse_broadcaster.clone()
+        app_state.playlist_library.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<MediaCache> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.media_cache.clone()
+    }
+}
+
+impl FromRef<AppState> for Key {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.requester_key.clone()
     }
 }
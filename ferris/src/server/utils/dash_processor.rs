@@ -1,7 +1,19 @@
-use std::process::Command;
+use std::time::Duration;
+
+use tokio::process::Command;
 use tracing::{debug, error};
 
-use crate::server::globals;
+use crate::server::{
+    globals,
+    utils::{
+        process_runner::{run_with_lines, RunError},
+        progress::{ProcessingPhase, ProgressReporter},
+        yt_downloader::VideoProcessError,
+    },
+};
+
+/// Pitch shifting into seven streams is slow on small machines, but not this slow.
+const PROCESSING_TIMEOUT: Duration = Duration::from_secs(30 * 60);
 
 #[derive(Debug)]
 pub enum ProcessingMode {
@@ -104,17 +116,42 @@ impl DashProcessor {
         encodings
     }
 
-    pub fn execute(
+    /// The video stream is copied as is, unless it has to be transcoded to H.264.
+    fn build_video_encoding(&self, transcode: bool) -> Vec<String> {
+        if !transcode {
+            return vec!["-c:v".to_string(), "copy".to_string()];
+        }
+
+        [
+            "-c:v", "libx264", "-preset", "veryfast", "-crf", "23", "-pix_fmt", "yuv420p",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    }
+
+    /// Runs ffmpeg over the downloaded video. Its `-progress` output says how far into
+    /// the video it is, which `duration_seconds` turns into a percentage.
+    pub async fn execute(
         &self,
         input_file: &str,
         output_file: &str,
         mode: &ProcessingMode,
-    ) -> std::io::Result<()> {
+        transcode: bool,
+        duration_seconds: f64,
+        progress: &mut ProgressReporter,
+    ) -> Result<(), VideoProcessError> {
         let ffmpeg_path = globals::get_binary_path("ffmpeg");
         debug!("Using FFmpeg from path: {}", ffmpeg_path.display());
 
         let mut command = Command::new(ffmpeg_path);
-        command.arg("-i").arg(input_file).arg("-c:v").arg("copy");
+        command
+            .arg("-nostats")
+            .arg("-progress")
+            .arg("pipe:1")
+            .arg("-i")
+            .arg(input_file)
+            .args(self.build_video_encoding(transcode));
 
         // Add filter complex if needed
         if let Some(filter_complex) = self.build_filter_complex(mode) {
@@ -134,13 +171,43 @@ impl DashProcessor {
 
         debug!("ffmpeg command: {:?}", command);
 
-        let output = command.output()?;
+        progress.report(ProcessingPhase::ProcessingAudio, Some(0.0));
+        let output = run_with_lines(command, PROCESSING_TIMEOUT, |line| {
+            // -progress prints bare key=value lines, anything else is ffmpeg's log
+            let Some((key, value)) = line
+                .split_once('=')
+                .filter(|(key, _)| key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            else {
+                return false;
+            };
+
+            match key {
+                "out_time_us" => {
+                    let percent = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|_| duration_seconds > 0.0)
+                        .map(|out_time_us| out_time_us / 1e6 / duration_seconds * 100.0);
+                    progress.report(ProcessingPhase::ProcessingAudio, percent);
+                }
+                // the whole input has been read, what's left is finishing the output
+                "progress" if value == "end" => progress.report(ProcessingPhase::Packaging, None),
+                _ => {}
+            }
+            true
+        })
+        .await
+        .map_err(|err| match err {
+            RunError::TimedOut(timeout) => VideoProcessError::ProcessingTimeout(timeout.as_secs()),
+            RunError::Io(err) => {
+                VideoProcessError::PitchShiftError(format!("Pitch shift failed: {}", err))
+            }
+        })?;
+
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("FFmpeg error: {}", error);
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "FFmpeg command failed",
+            error!("FFmpeg error: {}", output.stderr);
+            return Err(VideoProcessError::PitchShiftError(
+                "Pitch shift failed: FFmpeg command failed".to_string(),
             ));
         }
         Ok(())
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Background songs the room plays from the local cache once the queue has been empty
+/// for a while. They stop as soon as someone queues a song, and since they never go
+/// through the queue they don't end up in the history or count as anyone's turn.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IdleMode {
+    pub enabled: bool,
+    /// How long the queue has to be empty before the first idle song starts.
+    pub after_seconds: u32,
+    #[serde(default)]
+    pub source: IdleSource,
+}
+
+/// Which cached videos idle mode picks from.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum IdleSource {
+    #[default]
+    Random,
+    /// The room's most played songs, going by its history.
+    Popular,
+    /// The songs of a saved playlist that are in the cache, by playlist name.
+    Playlist(String),
+}
+
+/// A song playing in idle mode.
+#[derive(Clone, Debug, Serialize)]
+pub struct IdleSong {
+    /// Tells idle songs apart, e.g. the same video coming up twice in a row.
+    pub uuid: String,
+    /// The cache folder, which is what the display streams from.
+    pub folder: String,
+    pub name: String,
+    pub is_key_changeable: bool,
+    pub video_id: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub thumbnail_url: Option<String>,
+    pub uploader: Option<String>,
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The last key each song was sung in. Songs are keyed by their canonical ID (the
+/// YouTube video ID when there is one); singers who have sung a song also get their own
+/// entry, which wins over the song-wide one.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KeyMemory {
+    #[serde(default)]
+    songs: HashMap<String, i8>,
+    /// Singer name to song ID to key.
+    #[serde(default)]
+    singers: HashMap<String, HashMap<String, i8>>,
+}
+
+impl KeyMemory {
+    pub fn remember(&mut self, song_id: &str, singer: Option<&str>, key: i8) {
+        self.songs.insert(song_id.to_string(), key);
+        if let Some(singer) = singer {
+            self.singers
+                .entry(singer.to_string())
+                .or_default()
+                .insert(song_id.to_string(), key);
+        }
+    }
+
+    pub fn preferred(&self, song_id: &str, singer: Option<&str>) -> Option<i8> {
+        singer
+            .and_then(|singer| self.singers.get(singer))
+            .and_then(|songs| songs.get(song_id))
+            .or_else(|| self.songs.get(song_id))
+            .copied()
+    }
+
+    /// Forgets one song for everyone. Returns whether anything was remembered for it.
+    pub fn forget(&mut self, song_id: &str) -> bool {
+        let mut forgotten = self.songs.remove(song_id).is_some();
+        for songs in self.singers.values_mut() {
+            forgotten |= songs.remove(song_id).is_some();
+        }
+        self.singers.retain(|_, songs| !songs.is_empty());
+        forgotten
+    }
+
+    pub fn clear(&mut self) {
+        self.songs.clear();
+        self.singers.clear();
+    }
+}
@@ -1,3 +1,15 @@
+pub mod clock;
 pub mod dash_processor;
+pub mod idle_mode;
+pub mod key_memory;
+pub mod play_history;
+pub mod playback;
+pub mod playlist;
+pub mod process_runner;
+pub mod progress;
+pub mod snapshot_store;
+pub mod stage;
+pub mod undo_history;
+pub mod video_id;
 pub mod yt_downloader;
 pub mod yt_searcher;
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A song that made it to the front of the queue and was later popped off it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayedSong {
+    pub uuid: String,
+    #[serde(default)]
+    pub video_id: Option<String>,
+    pub name: String,
+    pub yt_link: String,
+    pub singer: Option<String>,
+    pub key: i8,
+    pub started_at: u64,
+    pub ended_at: u64,
+}
+
+impl PlayedSong {
+    pub fn seconds_sung(&self) -> u64 {
+        self.ended_at.saturating_sub(self.started_at)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SingerStats {
+    pub singer: String,
+    pub songs: usize,
+    pub minutes_sung: f64,
+}
+
+#[derive(Serialize)]
+pub struct SongPlayCount {
+    pub name: String,
+    pub yt_link: String,
+    pub plays: usize,
+}
+
+#[derive(Serialize)]
+pub struct SessionStats {
+    pub songs_played: usize,
+    pub total_minutes_sung: f64,
+    pub singers: Vec<SingerStats>,
+    pub most_played: Vec<SongPlayCount>,
+}
+
+const MOST_PLAYED_LIMIT: usize = 10;
+
+impl SessionStats {
+    pub fn from_history(history: &[PlayedSong]) -> Self {
+        let mut singers: HashMap<&str, (usize, u64)> = HashMap::new();
+        // keyed by video, so the same video queued from different links counts as one
+        let mut plays: HashMap<&str, (&PlayedSong, usize)> = HashMap::new();
+
+        for played in history {
+            if let Some(singer) = played.singer.as_deref() {
+                let (songs, seconds) = singers.entry(singer).or_insert((0, 0));
+                *songs += 1;
+                *seconds += played.seconds_sung();
+            }
+
+            plays
+                .entry(played.video_id.as_deref().unwrap_or(&played.yt_link))
+                .or_insert((played, 0))
+                .1 += 1;
+        }
+
+        let mut singers: Vec<SingerStats> = singers
+            .into_iter()
+            .map(|(singer, (songs, seconds))| SingerStats {
+                singer: singer.to_string(),
+                songs,
+                minutes_sung: seconds as f64 / 60.0,
+            })
+            .collect();
+        singers.sort_by(|a, b| b.songs.cmp(&a.songs).then(a.singer.cmp(&b.singer)));
+
+        let mut most_played: Vec<SongPlayCount> = plays
+            .into_values()
+            .map(|(played, plays)| SongPlayCount {
+                name: played.name.clone(),
+                yt_link: played.yt_link.clone(),
+                plays,
+            })
+            .collect();
+        most_played.sort_by(|a, b| b.plays.cmp(&a.plays).then(a.name.cmp(&b.name)));
+        most_played.truncate(MOST_PLAYED_LIMIT);
+
+        SessionStats {
+            songs_played: history.len(),
+            total_minutes_sung: history.iter().map(PlayedSong::seconds_sung).sum::<u64>() as f64
+                / 60.0,
+            singers,
+            most_played,
+        }
+    }
+}
+
+pub fn history_to_csv(history: &[PlayedSong]) -> String {
+    let mut csv = String::from("started_at,ended_at,name,singer,key,yt_link\n");
+
+    for played in history {
+        let row = [
+            played.started_at.to_string(),
+            played.ended_at.to_string(),
+            csv_field(&played.name),
+            csv_field(played.singer.as_deref().unwrap_or_default()),
+            played.key.to_string(),
+            csv_field(&played.yt_link),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use uuid::Uuid;
+
+use super::clock::unix_timestamp_millis;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Display)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+}
+
+/// What the display is doing with the current song. `position_seconds` is the position
+/// as of `updated_at` (unix milliseconds); while playing, the live position is
+/// extrapolated from it.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlaybackState {
+    pub song_uuid: Option<String>,
+    pub status: PlaybackStatus,
+    pub position_seconds: f64,
+    pub updated_at: u64,
+}
+
+impl PlaybackState {
+    /// Fresh state for a song that just reached the front of the queue. Displays start
+    /// new songs on their own, so it begins playing from the top.
+    pub fn new(song_uuid: Option<Uuid>) -> Self {
+        let status = match song_uuid {
+            Some(_) => PlaybackStatus::Playing,
+            None => PlaybackStatus::Paused,
+        };
+
+        PlaybackState {
+            song_uuid: song_uuid.map(|uuid| uuid.to_string()),
+            status,
+            position_seconds: 0.0,
+            updated_at: unix_timestamp_millis(),
+        }
+    }
+
+    pub fn belongs_to(&self, song_uuid: Uuid) -> bool {
+        self.song_uuid.as_deref() == Some(song_uuid.to_string().as_str())
+    }
+
+    pub fn position_now(&self) -> f64 {
+        match self.status {
+            PlaybackStatus::Playing => {
+                let elapsed = unix_timestamp_millis().saturating_sub(self.updated_at);
+                self.position_seconds + elapsed as f64 / 1000.0
+            }
+            PlaybackStatus::Paused => self.position_seconds,
+        }
+    }
+
+    /// The state as it stands right now, with the position brought up to date.
+    pub fn current(&self) -> Self {
+        PlaybackState {
+            song_uuid: self.song_uuid.clone(),
+            status: self.status,
+            position_seconds: self.position_now(),
+            updated_at: unix_timestamp_millis(),
+        }
+    }
+
+    /// Returns whether the status actually changed, so repeated commands are no-ops.
+    pub fn set_status(&mut self, status: PlaybackStatus) -> bool {
+        if self.status == status {
+            return false;
+        }
+
+        self.position_seconds = self.position_now();
+        self.status = status;
+        self.updated_at = unix_timestamp_millis();
+        true
+    }
+
+    /// Takes a report from the display, which knows the position better than we do.
+    /// Returns whether the status changed.
+    pub fn report(&mut self, status: PlaybackStatus, position_seconds: f64) -> bool {
+        let changed = self.status != status;
+        self.status = status;
+        self.position_seconds = position_seconds.max(0.0);
+        self.updated_at = unix_timestamp_millis();
+        changed
+    }
+
+    pub fn restart(&mut self) {
+        self.status = PlaybackStatus::Playing;
+        self.position_seconds = 0.0;
+        self.updated_at = unix_timestamp_millis();
+    }
+}
@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+
+use super::{play_history::csv_field, video_id::extract_video_id};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub title: String,
+    #[serde(default)]
+    pub artist: Option<String>,
+    pub yt_link: String,
+    #[serde(default)]
+    pub is_key_changeable: bool,
+}
+
+impl PlaylistEntry {
+    /// The name the song is queued under, cut down the same way the phones do it so
+    /// songs queued from a playlist look like any other. Titles with nothing left after
+    /// that, e.g. ones in another script, go by their video ID.
+    pub fn song_name(&self) -> String {
+        let display_name = match &self.artist {
+            Some(artist) => format!("{} {}", artist, self.title),
+            None => self.title.clone(),
+        };
+
+        let name: String = display_name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || c.is_whitespace())
+            .map(|c| if c.is_whitespace() { '_' } else { c })
+            .collect();
+        if name.chars().any(|c| c != '_') {
+            return name;
+        }
+
+        extract_video_id(&self.yt_link).unwrap_or_else(|| "untitled".to_string())
+    }
+
+    fn display_name(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{} - {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    pub name: String,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// A row of an imported CSV. Rows without a URL still need searching for.
+#[derive(Debug)]
+pub struct CsvRow {
+    pub artist: Option<String>,
+    pub title: String,
+    pub url: Option<String>,
+}
+
+impl CsvRow {
+    pub fn search_query(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{} {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    pub fn into_entry(self, yt_link: String) -> PlaylistEntry {
+        PlaylistEntry {
+            title: self.title,
+            artist: self.artist,
+            yt_link,
+            is_key_changeable: false,
+        }
+    }
+}
+
+pub fn playlist_to_m3u(playlist: &Playlist) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+
+    for entry in &playlist.entries {
+        m3u.push_str(&format!("#EXTINF:-1,{}\n", entry.display_name()));
+        m3u.push_str(&entry.yt_link);
+        m3u.push('\n');
+    }
+
+    m3u
+}
+
+/// Reads the entries of an extended M3U. Entries without an `#EXTINF` line are named
+/// after their video ID.
+pub fn playlist_from_m3u(m3u: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut display_name = None;
+
+    for line in m3u.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            display_name = info
+                .split_once(',')
+                .map(|(_, name)| name.trim().to_string());
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let display_name = display_name
+            .take()
+            .filter(|name| !name.is_empty())
+            .or_else(|| extract_video_id(line))
+            .unwrap_or_else(|| line.to_string());
+        let (artist, title) = match display_name.split_once(" - ") {
+            Some((artist, title)) => (Some(artist.trim().to_string()), title.trim().to_string()),
+            None => (None, display_name),
+        };
+
+        entries.push(PlaylistEntry {
+            title,
+            artist,
+            yt_link: line.to_string(),
+            is_key_changeable: false,
+        });
+    }
+
+    entries
+}
+
+pub fn playlist_to_csv(playlist: &Playlist) -> String {
+    let mut csv = String::from("artist,title,url\n");
+
+    for entry in &playlist.entries {
+        let row = [
+            csv_field(entry.artist.as_deref().unwrap_or_default()),
+            csv_field(&entry.title),
+            csv_field(&entry.yt_link),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Reads `artist,title,url` rows. The header is optional, and so are the artist and
+/// URL of each row; rows without a title are dropped.
+pub fn playlist_from_csv(csv: &str) -> Vec<CsvRow> {
+    let non_empty = |field: Option<&String>| {
+        field
+            .map(|field| field.trim())
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+    };
+
+    csv.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_csv_line)
+        .enumerate()
+        .filter(|(index, fields)| {
+            let is_header = fields
+                .get(1)
+                .is_some_and(|field| field.trim().eq_ignore_ascii_case("title"));
+            !(*index == 0 && is_header)
+        })
+        .filter_map(|(_, fields)| {
+            Some(CsvRow {
+                artist: non_empty(fields.first()),
+                title: non_empty(fields.get(1))?,
+                url: non_empty(fields.get(2)),
+            })
+        })
+        .collect()
+}
+
+/// Splits one CSV line into fields, undoing the quoting [`csv_field`] does. Quoted
+/// fields spanning several lines aren't supported.
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(artist: Option<&str>, title: &str, yt_link: &str) -> PlaylistEntry {
+        PlaylistEntry {
+            title: title.to_string(),
+            artist: artist.map(str::to_string),
+            yt_link: yt_link.to_string(),
+            is_key_changeable: false,
+        }
+    }
+
+    fn playlist(entries: Vec<PlaylistEntry>) -> Playlist {
+        Playlist {
+            name: "test".to_string(),
+            entries,
+        }
+    }
+
+    fn entry_fields(entry: &PlaylistEntry) -> (Option<&str>, &str, &str) {
+        (
+            entry.artist.as_deref(),
+            entry.title.as_str(),
+            entry.yt_link.as_str(),
+        )
+    }
+
+    fn row_fields(row: &CsvRow) -> (Option<&str>, &str, Option<&str>) {
+        (
+            row.artist.as_deref(),
+            row.title.as_str(),
+            row.url.as_deref(),
+        )
+    }
+
+    #[test]
+    fn csv_round_trips_quoted_fields() {
+        let exported = playlist(vec![
+            entry(Some("Simon, Garfunkel"), "The Boxer", "https://youtu.be/a"),
+            entry(Some("Queen"), "Say \"Hi\", World", "https://youtu.be/b"),
+            entry(None, "No Artist", "https://youtu.be/c?x=1,2"),
+        ]);
+
+        let rows = playlist_from_csv(&playlist_to_csv(&exported));
+
+        let rows: Vec<_> = rows.iter().map(row_fields).collect();
+        assert_eq!(
+            rows,
+            [
+                (
+                    Some("Simon, Garfunkel"),
+                    "The Boxer",
+                    Some("https://youtu.be/a")
+                ),
+                (
+                    Some("Queen"),
+                    "Say \"Hi\", World",
+                    Some("https://youtu.be/b")
+                ),
+                (None, "No Artist", Some("https://youtu.be/c?x=1,2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_csv_lines_on_unquoted_commas() {
+        assert_eq!(
+            parse_csv_line(r#"a,"b, c","say ""hi""","""",,"#),
+            ["a", "b, c", "say \"hi\"", "\"", "", ""]
+        );
+    }
+
+    #[test]
+    fn reads_csv_with_or_without_a_header() {
+        let with_header = playlist_from_csv("Artist,Title,URL\nQueen,Bohemian Rhapsody,u\n");
+        let without_header = playlist_from_csv("Queen,Bohemian Rhapsody,u\n");
+
+        for rows in [with_header, without_header] {
+            let rows: Vec<_> = rows.iter().map(row_fields).collect();
+            assert_eq!(rows, [(Some("Queen"), "Bohemian Rhapsody", Some("u"))]);
+        }
+    }
+
+    #[test]
+    fn keeps_csv_rows_without_a_url_and_drops_untitled_ones() {
+        let rows = playlist_from_csv("Queen,Bohemian Rhapsody\n, Yesterday ,\n\nABBA,,u\n");
+
+        let rows: Vec<_> = rows.iter().map(row_fields).collect();
+        assert_eq!(
+            rows,
+            [
+                (Some("Queen"), "Bohemian Rhapsody", None),
+                (None, "Yesterday", None),
+            ]
+        );
+        assert_eq!(
+            playlist_from_csv("Queen,Bohemian Rhapsody")[0].search_query(),
+            "Queen Bohemian Rhapsody"
+        );
+    }
+
+    #[test]
+    fn m3u_round_trips_entries() {
+        let exported = playlist(vec![
+            entry(Some("Queen"), "Bohemian Rhapsody", "https://youtu.be/a"),
+            entry(None, "Yesterday", "https://youtu.be/b"),
+        ]);
+
+        let entries = playlist_from_m3u(&playlist_to_m3u(&exported));
+
+        let entries: Vec<_> = entries.iter().map(entry_fields).collect();
+        assert_eq!(
+            entries,
+            [
+                (Some("Queen"), "Bohemian Rhapsody", "https://youtu.be/a"),
+                (None, "Yesterday", "https://youtu.be/b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_m3u_entries_without_extinf_after_their_video() {
+        let m3u = "#EXTM3U\n\
+            https://www.youtube.com/watch?v=dQw4w9WgXcQ\n\
+            #EXTINF:-1,Queen - Bohemian Rhapsody\n\
+            https://youtu.be/fJ9rUzIMcZQ\n\
+            https://example.com/song.mp4\n";
+
+        let entries = playlist_from_m3u(m3u);
+
+        let entries: Vec<_> = entries.iter().map(entry_fields).collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    None,
+                    "dQw4w9WgXcQ",
+                    "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+                ),
+                (
+                    Some("Queen"),
+                    "Bohemian Rhapsody",
+                    "https://youtu.be/fJ9rUzIMcZQ"
+                ),
+                (
+                    None,
+                    "https://example.com/song.mp4",
+                    "https://example.com/song.mp4"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::{io, process::ExitStatus, time::Duration};
+
+use thiserror::Error;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+};
+use tracing::warn;
+
+/// How much of each stream is kept. yt-dlp and ffmpeg only print a few lines worth
+/// keeping, so anything past this is noise and is read but dropped.
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Lines longer than this are cut short, so a tool printing without newlines can't make
+/// us buffer it all.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("timed out after {}s", .0.as_secs())]
+    TimedOut(Duration),
+}
+
+/// What a process printed once it exited, minus the lines that were handled as they came.
+#[derive(Debug)]
+pub struct ProcessOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs `command` to completion, killing it if it takes longer than `timeout`.
+pub async fn run(command: Command, timeout: Duration) -> Result<ProcessOutput, RunError> {
+    run_with_lines(command, timeout, |_| false).await
+}
+
+/// Runs `command` to completion and hands each line it prints, on either stream, to
+/// `on_line` as soon as it's there. Lines `on_line` returns true for are taken care of
+/// and don't show up in the output.
+///
+/// The process is killed, along with anything it started, if it takes longer than
+/// `timeout` or the returned future is dropped, e.g. when its download is cancelled.
+/// Anything it started that's still around once it exits is killed too.
+pub async fn run_with_lines(
+    mut command: Command,
+    timeout: Duration,
+    on_line: impl FnMut(&str) -> bool,
+) -> Result<ProcessOutput, RunError> {
+    command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = command.spawn()?;
+    let mut process_group = ProcessGroup::of(&child);
+
+    let waited = wait_with_lines(&mut child, &mut process_group, on_line);
+    match tokio::time::timeout(timeout, waited).await {
+        Ok(output) => Ok(output?),
+        Err(_) => Err(RunError::TimedOut(timeout)),
+    }
+}
+
+async fn wait_with_lines(
+    child: &mut Child,
+    process_group: &mut ProcessGroup,
+    mut on_line: impl FnMut(&str) -> bool,
+) -> io::Result<ProcessOutput> {
+    let mut stdout_lines = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut stderr_lines = BufReader::new(child.stderr.take().expect("stderr is piped"));
+
+    let (mut stdout, mut stderr) = (CappedOutput::default(), CappedOutput::default());
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    while stdout_open || stderr_open {
+        tokio::select! {
+            line = next_line(&mut stdout_lines), if stdout_open => match line? {
+                Some(line) if !on_line(&line) => stdout.push(&line),
+                Some(_) => {}
+                None => stdout_open = false,
+            },
+            line = next_line(&mut stderr_lines), if stderr_open => match line? {
+                Some(line) if !on_line(&line) => stderr.push(&line),
+                Some(_) => {}
+                None => stderr_open = false,
+            },
+        }
+    }
+
+    // both pipes closing means the child is exiting; whatever it started and left
+    // behind goes now, while the unreaped child still holds on to the group's ID
+    process_group.kill();
+
+    Ok(ProcessOutput {
+        status: child.wait().await?,
+        stdout: stdout.text,
+        stderr: stderr.text,
+    })
+}
+
+/// One stream's output, up to [`MAX_OUTPUT_BYTES`]. Only whole lines are kept, so what
+/// there is still parses.
+#[derive(Default)]
+struct CappedOutput {
+    text: String,
+    full: bool,
+}
+
+impl CappedOutput {
+    fn push(&mut self, line: &str) {
+        if self.full {
+            return;
+        }
+
+        if self.text.len() + line.len() + 1 > MAX_OUTPUT_BYTES {
+            warn!(
+                "process output over {} bytes, dropping the rest",
+                MAX_OUTPUT_BYTES
+            );
+            self.full = true;
+            return;
+        }
+
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+}
+
+/// The next line without its line ending, cut at [`MAX_LINE_BYTES`]. Tools print
+/// whatever bytes file names are made of, so anything that isn't UTF-8 is replaced
+/// rather than failing the read.
+async fn next_line(reader: &mut BufReader<impl AsyncRead + Unpin>) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let mut read_any = false;
+    loop {
+        let buffer = reader.fill_buf().await?;
+        if buffer.is_empty() {
+            break;
+        }
+        read_any = true;
+
+        let (chunk, found_end) = match buffer.iter().position(|byte| *byte == b'\n') {
+            Some(end) => (&buffer[..=end], true),
+            None => (buffer, false),
+        };
+        let room = MAX_LINE_BYTES.saturating_sub(line.len());
+        line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+
+        let consumed = chunk.len();
+        reader.consume(consumed);
+        if found_end {
+            break;
+        }
+    }
+
+    if !read_any {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        String::from_utf8_lossy(&line)
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+    ))
+}
+
+/// The process group a child was started in. `kill_on_drop` only takes down the child
+/// itself, so this kills whatever it started too (yt-dlp runs ffmpeg to merge streams),
+/// either once the child is done or when it's dropped before then. It must go before
+/// the child is waited on, as the group's ID is free to be reused after that.
+struct ProcessGroup {
+    #[cfg(unix)]
+    id: Option<i32>,
+}
+
+impl ProcessGroup {
+    fn of(child: &Child) -> Self {
+        #[cfg(unix)]
+        {
+            // the child leads its own group, see `process_group(0)`
+            ProcessGroup {
+                id: child.id().map(|id| id as i32),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = child;
+            ProcessGroup {}
+        }
+    }
+
+    fn kill(&mut self) {
+        #[cfg(unix)]
+        if let Some(id) = self.id.take() {
+            // SAFETY: killpg has no memory safety requirements, and the group can't
+            // have been reused while its leader hasn't been waited on
+            unsafe {
+                libc::killpg(id, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
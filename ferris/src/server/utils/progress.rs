@@ -0,0 +1,79 @@
+use serde::Serialize;
+use strum::Display;
+use tokio::{
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+/// How often a download's progress is passed on at most, so a fast download doesn't flood
+/// the SSE channel. Moving on to another phase is always passed on.
+const MIN_REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The steps a song goes through between being queued and being ready to play.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Display)]
+pub enum ProcessingPhase {
+    Downloading,
+    /// Normalizing the audio and, for key changeable songs, pitch shifting it.
+    ProcessingAudio,
+    /// Writing out the last segments and the DASH manifest.
+    Packaging,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub struct ProcessingProgress {
+    pub phase: ProcessingPhase,
+    /// 0 to 100, or `None` when there's no telling, e.g. YouTube not saying how big the
+    /// video is.
+    pub percent: Option<f64>,
+}
+
+/// What a download has to say while it's running.
+#[derive(Clone, Debug)]
+pub enum DownloadUpdate {
+    Progress(ProcessingProgress),
+    /// An attempt failed and attempt number `attempt` is next, possibly after a wait.
+    Retrying {
+        attempt: u32,
+        reason: String,
+    },
+}
+
+/// Passes progress from yt-dlp and ffmpeg on to whoever is waiting on the video.
+pub struct ProgressReporter {
+    sender: mpsc::UnboundedSender<DownloadUpdate>,
+    last_reported: Option<(ProcessingPhase, Instant)>,
+}
+
+impl ProgressReporter {
+    pub fn new(sender: mpsc::UnboundedSender<DownloadUpdate>) -> Self {
+        ProgressReporter {
+            sender,
+            last_reported: None,
+        }
+    }
+
+    pub fn report(&mut self, phase: ProcessingPhase, percent: Option<f64>) {
+        let now = Instant::now();
+        if let Some((last_phase, at)) = self.last_reported {
+            if last_phase == phase && now.duration_since(at) < MIN_REPORT_INTERVAL {
+                return;
+            }
+        }
+
+        self.last_reported = Some((phase, now));
+        let _ = self
+            .sender
+            .send(DownloadUpdate::Progress(ProcessingProgress {
+                phase,
+                percent: percent.map(|percent| percent.clamp(0.0, 100.0)),
+            }));
+    }
+
+    pub fn retrying(&mut self, attempt: u32, reason: String) {
+        // the next attempt starts over, its first report shouldn't be held back
+        self.last_reported = None;
+        let _ = self
+            .sender
+            .send(DownloadUpdate::Retrying { attempt, reason });
+    }
+}
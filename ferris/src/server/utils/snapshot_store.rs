@@ -0,0 +1,66 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::{trace, warn};
+
+/// Stores a single JSON document on disk. Saves go through a temporary file that is
+/// renamed over the old one, so a crash mid-write never leaves a half-written snapshot.
+#[derive(Clone)]
+pub struct SnapshotStore {
+    path: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(path: PathBuf) -> Self {
+        SnapshotStore { path }
+    }
+
+    /// Returns `None` when there is no snapshot yet or the file can't be parsed.
+    pub fn load<T: DeserializeOwned>(&self) -> Option<T> {
+        if !self.path.exists() {
+            trace!("no snapshot found at {}", self.path.display());
+            return None;
+        }
+
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("failed to open snapshot {}: {}", self.path.display(), e);
+                return None;
+            }
+        };
+
+        match serde_json::from_reader(BufReader::new(file)) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("failed to parse snapshot {}: {}", self.path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, value)?;
+        writer.flush()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        trace!("wrote snapshot to {}", self.path.display());
+        Ok(())
+    }
+
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
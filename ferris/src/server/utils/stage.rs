@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use super::clock::unix_timestamp_millis;
+
+/// What the room is doing: someone singing, or one of the pauses between songs.
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq, Display)]
+pub enum StagePhase {
+    #[default]
+    Singing,
+    /// The "up next" countdown that gives the next singer time to get to the mic.
+    UpNext,
+    Break,
+}
+
+/// The phase the room is in and, for the pauses, when it's over.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stage {
+    pub phase: StagePhase,
+    /// Unix milliseconds, like [`super::playback::PlaybackState::updated_at`].
+    pub ends_at: Option<u64>,
+}
+
+impl Stage {
+    pub fn lasting(phase: StagePhase, seconds: u64) -> Self {
+        Stage {
+            phase,
+            ends_at: Some(unix_timestamp_millis().saturating_add(seconds.saturating_mul(1000))),
+        }
+    }
+
+    /// Whether the song at the front of the queue has to wait for this phase to end.
+    pub fn holds_playback(&self) -> bool {
+        self.phase != StagePhase::Singing
+    }
+
+    /// Pushes the end of a pause back. Returns false while singing, there being nothing
+    /// to extend.
+    pub fn extend(&mut self, seconds: u64) -> bool {
+        if !self.holds_playback() {
+            return false;
+        }
+
+        match &mut self.ends_at {
+            Some(ends_at) => {
+                *ends_at = ends_at.saturating_add(seconds.saturating_mul(1000));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The song a pause is leading up to.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpNext {
+    pub song_uuid: String,
+    pub name: String,
+    pub performers: Vec<String>,
+}
+
+/// The stage as displays see it.
+#[derive(Clone, Debug, Serialize)]
+pub struct StageState {
+    pub phase: StagePhase,
+    pub ends_at: Option<u64>,
+    pub up_next: Option<UpNext>,
+}
+
+/// A break the host planned ahead, e.g. 15 minutes at 22:00. It starts once the song
+/// playing at that time is over, so nobody gets cut off mid-song.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledBreak {
+    pub id: String,
+    /// Unix seconds, like the rest of the server's timestamps.
+    pub starts_at: u64,
+    pub minutes: u32,
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+
+/// Bounded undo/redo stacks. Recording a new operation forgets everything that could
+/// have been redone, and the oldest undo entry falls off once `limit` is reached.
+pub struct UndoHistory<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    limit: usize,
+}
+
+impl<T> UndoHistory<T> {
+    pub fn new(limit: usize) -> Self {
+        UndoHistory {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    pub fn record(&mut self, operation: T) {
+        self.redo.clear();
+        self.push_undo(operation);
+    }
+
+    /// Puts a redone operation back on the undo stack without touching the redo stack.
+    pub fn push_undo(&mut self, operation: T) {
+        self.undo.push_back(operation);
+        if self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<T> {
+        self.undo.pop_back()
+    }
+
+    pub fn push_redo(&mut self, operation: T) {
+        self.redo.push(operation);
+    }
+
+    pub fn pop_redo(&mut self) -> Option<T> {
+        self.redo.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_the_oldest_operations_past_the_limit() {
+        let mut history = UndoHistory::new(2);
+        for operation in 1..=3 {
+            history.record(operation);
+        }
+
+        assert_eq!(history.pop_undo(), Some(3));
+        assert_eq!(history.pop_undo(), Some(2));
+        assert_eq!(history.pop_undo(), None);
+    }
+
+    #[test]
+    fn recording_clears_what_could_be_redone() {
+        let mut history = UndoHistory::new(10);
+        history.record(1);
+        history.record(2);
+
+        let undone = history.pop_undo().unwrap();
+        history.push_redo(undone);
+        history.record(3);
+
+        assert_eq!(history.pop_redo(), None);
+        assert_eq!(history.pop_undo(), Some(3));
+        assert_eq!(history.pop_undo(), Some(1));
+    }
+
+    #[test]
+    fn redoing_keeps_the_rest_of_the_redo_stack() {
+        let mut history = UndoHistory::new(10);
+        history.record(1);
+        history.record(2);
+        for _ in 0..2 {
+            let undone = history.pop_undo().unwrap();
+            history.push_redo(undone);
+        }
+
+        let redone = history.pop_redo().unwrap();
+        history.push_undo(redone);
+
+        assert_eq!(redone, 1);
+        assert_eq!(history.pop_redo(), Some(2));
+        assert_eq!(history.pop_undo(), Some(1));
+    }
+}
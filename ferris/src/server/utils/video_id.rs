@@ -0,0 +1,141 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static VIDEO_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_-]{11}$").unwrap());
+
+/// Pulls the 11 character YouTube video ID out of a link. Handles `watch?v=` links on
+/// www/m/music.youtube.com, `youtu.be` short links, `/shorts/`, `/embed/`, `/live/` and
+/// `/v/` paths, extra query parameters and fragments, as well as a bare video ID.
+pub fn extract_video_id(link: &str) -> Option<String> {
+    let link = link.trim();
+    if VIDEO_ID.is_match(link) {
+        return Some(link.to_string());
+    }
+
+    let without_scheme = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let (host, rest) = without_scheme
+        .split_once('/')
+        .unwrap_or((without_scheme, ""));
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let host = host.split(':').next().unwrap_or_default().to_lowercase();
+    let host = ["www.", "m.", "music."]
+        .iter()
+        .find_map(|prefix| host.strip_prefix(prefix))
+        .unwrap_or(&host);
+
+    let candidate = match host {
+        "youtu.be" => path.split('/').next(),
+        "youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+            match segments.next() {
+                Some("watch") => query.split('&').find_map(|param| param.strip_prefix("v=")),
+                Some("shorts" | "embed" | "live" | "v" | "e") => segments.next(),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    candidate
+        .filter(|id| VIDEO_ID.is_match(id))
+        .map(str::to_string)
+}
+
+/// The cache folder a video is processed into. Folders go by video ID rather than the
+/// name the song was queued under, so two songs only share one when they're the same
+/// video, and key-changeable videos get their own since they're processed differently.
+/// Links we can't pull an ID out of go by a hash of the link instead.
+pub fn video_folder(yt_link: &str, is_key_changeable: bool) -> String {
+    let id = extract_video_id(yt_link)
+        .unwrap_or_else(|| format!("link-{:016x}", fnv1a(yt_link.trim().as_bytes())));
+
+    if is_key_changeable {
+        format!("{}_keys", id)
+    } else {
+        id
+    }
+}
+
+/// 64-bit FNV-1a. Folder names have to come out the same on every build, which the
+/// standard library's hashers don't promise.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_video_id_from_supported_links() {
+        let links = [
+            "dQw4w9WgXcQ",
+            " dQw4w9WgXcQ ",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtube.com/watch?v=dQw4w9WgXcQ",
+            "http://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=RDAMVM",
+            "https://www.youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42s",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ#comments",
+            "www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://WWW.YouTube.com:443/watch?v=dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ?si=abcdef&t=10",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ?autoplay=1",
+            "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ",
+            "https://www.youtube.com/live/dQw4w9WgXcQ?feature=share",
+            "https://www.youtube.com/v/dQw4w9WgXcQ",
+            "https://www.youtube.com/e/dQw4w9WgXcQ",
+        ];
+
+        for link in links {
+            assert_eq!(
+                extract_video_id(link).as_deref(),
+                Some("dQw4w9WgXcQ"),
+                "{}",
+                link
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_links_without_a_video_id() {
+        let links = [
+            "",
+            "dQw4w9WgXc",
+            "https://www.youtube.com/",
+            "https://www.youtube.com/watch?list=RDAMVM",
+            "https://www.youtube.com/watch?v=short",
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "https://youtu.be/",
+            "https://vimeo.com/watch?v=dQw4w9WgXcQ",
+            "https://notyoutube.com/watch?v=dQw4w9WgXcQ",
+        ];
+
+        for link in links {
+            assert_eq!(extract_video_id(link), None, "{}", link);
+        }
+    }
+
+    #[test]
+    fn keys_folders_by_video_and_key_changeability() {
+        let watch = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+        let short = "https://youtu.be/dQw4w9WgXcQ";
+
+        assert_eq!(video_folder(watch, false), "dQw4w9WgXcQ");
+        assert_eq!(video_folder(short, false), video_folder(watch, false));
+        assert_eq!(video_folder(watch, true), "dQw4w9WgXcQ_keys");
+        assert_ne!(
+            video_folder("https://example.com/a", false),
+            video_folder("https://example.com/b", false)
+        );
+        assert!(video_folder("https://example.com/a", false).starts_with("link-"));
+        // cached folders are found by this name, so it can't change between builds
+        assert_eq!(video_folder(" a ", true), "link-af63dc4c8601ec8c_keys");
+    }
+}
@@ -1,10 +1,73 @@
-use std::process::Command;
+use std::time::Duration;
+
+use strum::IntoStaticStr;
 use thiserror::Error;
-use tracing::debug;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+use crate::server::{
+    globals,
+    utils::{
+        process_runner::{run_with_lines, RunError},
+        progress::{ProcessingPhase, ProgressReporter},
+    },
+};
+
+/// A format to ask yt-dlp for: the video and audio streams it downloads and merges into
+/// one file, and whether the video has to be turned into H.264 before the TV can play it.
+#[derive(Clone, Copy, Debug)]
+pub struct DownloadFormat {
+    pub selector: &'static str,
+    pub transcode: bool,
+}
+
+/// The formats a download works its way through, until one of them can be downloaded
+/// and processed. Transcoding is slow, so the H.264 ones come first.
+pub const DOWNLOAD_FORMATS: &[DownloadFormat] = &[
+    DownloadFormat {
+        selector: "bestvideo[height<=720][vcodec^=avc1]+bestaudio",
+        transcode: false,
+    },
+    DownloadFormat {
+        selector: "bestvideo[vcodec^=avc1]+bestaudio/best[vcodec^=avc1]",
+        transcode: false,
+    },
+    // VP9 or AV1
+    DownloadFormat {
+        selector: "bestvideo[height<=720]+bestaudio",
+        transcode: true,
+    },
+    DownloadFormat {
+        selector: "bestvideo[height<=480]+bestaudio/best[height<=480]/best",
+        transcode: true,
+    },
+];
+
+/// yt-dlp errors that no retry or other format is going to fix.
+const PERMANENT_ERRORS: &[&str] = &[
+    "video unavailable",
+    "private video",
+    "sign in to confirm",
+    "members-only",
+    "has been removed",
+    "copyright",
+    "not available in your country",
+    "live event will begin",
+    "unsupported url",
+];
+
+/// yt-dlp errors about the format rather than the video.
+const FORMAT_ERRORS: &[&str] = &["requested format is not available", "no video formats"];
+
+/// Long enough for a long video on a slow connection; a yt-dlp that's been at it longer
+/// than this is stuck, not slow.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(20 * 60);
 
-use crate::server::globals;
+/// Marks the lines `--progress-template` prints, so they can be told apart from `--print`.
+const PROGRESS_PREFIX: &str = "juicebox-progress:";
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum VideoProcessError {
     #[error("YouTube download failed: {0}")]
     DownloadError(String),
@@ -18,6 +81,65 @@ pub enum VideoProcessError {
     CommandError(#[from] std::io::Error),
     #[error("Failed to parse duration: {0}")]
     DurationParseError(String),
+    #[error("Download was cancelled")]
+    Cancelled,
+    #[error("YouTube download timed out after {0}s")]
+    DownloadTimeout(u64),
+    #[error("Video processing timed out after {0}s")]
+    ProcessingTimeout(u64),
+    #[error("Invalid folder name: {0:?}")]
+    InvalidFolderName(String),
+}
+
+/// What to do about a download that failed.
+#[derive(Debug, PartialEq)]
+pub enum Retry {
+    /// Try the same format again in a bit, e.g. after YouTube had a hiccup.
+    Later,
+    /// Move on to the next of [`DOWNLOAD_FORMATS`].
+    NextFormat,
+    /// Give up, e.g. when the video is private.
+    Never,
+}
+
+impl VideoProcessError {
+    pub fn retry(&self) -> Retry {
+        match self {
+            VideoProcessError::DownloadError(reason) => {
+                let reason = reason.to_lowercase();
+                if PERMANENT_ERRORS.iter().any(|error| reason.contains(error)) {
+                    Retry::Never
+                } else if FORMAT_ERRORS.iter().any(|error| reason.contains(error)) {
+                    Retry::NextFormat
+                } else {
+                    // network trouble, rate limiting and the like
+                    Retry::Later
+                }
+            }
+            // yt-dlp or ffmpeg missing, or the song left the queue
+            VideoProcessError::CommandError(_)
+            | VideoProcessError::Cancelled
+            | VideoProcessError::InvalidFolderName(_) => Retry::Never,
+            // a stuck download isn't worth waiting on again, a smaller format might not get stuck
+            VideoProcessError::DownloadTimeout(_)
+            | VideoProcessError::ProcessingTimeout(_)
+            | VideoProcessError::FilenameError(_)
+            | VideoProcessError::PitchShiftError(_)
+            | VideoProcessError::VideoExtractError(_)
+            | VideoProcessError::DurationParseError(_) => Retry::NextFormat,
+        }
+    }
+}
+
+/// The error yt-dlp ended on, without the warnings it printed along the way.
+fn error_summary(stderr: &str) -> String {
+    stderr
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("ERROR:"))
+        .unwrap_or(stderr)
+        .trim()
+        .to_string()
 }
 
 #[derive(Debug)]
@@ -26,6 +148,51 @@ pub struct VideoMetadata {
     pub filename: String,
     pub extension: String,
     pub duration_seconds: f64,
+    pub video_id: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub uploader: Option<String>,
+}
+
+/// Turns yt-dlp's progress lines into a percentage for the whole download. yt-dlp
+/// downloads each stream of the format on its own, starting over at 0% every time.
+struct DownloadProgress {
+    streams: usize,
+    finished_streams: usize,
+    current_format: Option<String>,
+}
+
+impl DownloadProgress {
+    fn new(format: &DownloadFormat) -> Self {
+        // yt-dlp falls back on the formats after a '/' only when the first isn't there
+        let first_choice = format.selector.split('/').next().unwrap_or_default();
+        DownloadProgress {
+            streams: first_choice.split('+').count(),
+            finished_streams: 0,
+            current_format: None,
+        }
+    }
+
+    /// Parses a `format_id:downloaded_bytes:total_bytes` line. Either byte count is
+    /// `NA` when yt-dlp doesn't know it.
+    fn percent(&mut self, line: &str) -> Option<f64> {
+        let mut fields = line.rsplitn(3, ':');
+        let total = fields.next()?.trim().parse::<f64>().ok();
+        let downloaded = fields.next()?.trim().parse::<f64>().ok();
+        let format_id = fields.next()?;
+
+        if self.current_format.as_deref() != Some(format_id) {
+            if self.current_format.is_some() {
+                self.finished_streams += 1;
+            }
+            self.current_format = Some(format_id.to_string());
+        }
+
+        let stream_fraction = match (downloaded, total) {
+            (Some(downloaded), Some(total)) if total > 0.0 => (downloaded / total).min(1.0),
+            _ => return None,
+        };
+        Some((self.finished_streams as f64 + stream_fraction) / self.streams as f64 * 100.0)
+    }
 }
 
 #[derive(Clone)]
@@ -37,20 +204,31 @@ impl YtDownloader {
         yt_link: &str,
         base_dir: &str,
         file_name: &str,
+        format: &DownloadFormat,
+        progress: &mut ProgressReporter,
     ) -> Result<VideoMetadata, VideoProcessError> {
         let ffmpeg_path = globals::get_binary_path("ffmpeg");
 
         let args = vec![
             "-f".to_string(),
-            "bestvideo[height<=720][vcodec^=avc1]+bestaudio".to_string(),
+            format.selector.to_string(),
             "-o".to_string(),
             format!("{}/{}/{}.%(ext)s", base_dir, file_name, file_name),
             "--merge-output-format".to_string(),
             "mp4".to_string(),
             "--restrict-filenames".to_string(),
             "--print".to_string(),
-            "filename,duration".to_string(), // Print both filename and duration
+            // One line each; the last three print "NA" when YouTube doesn't have them
+            "filename,duration,id,thumbnail,uploader".to_string(),
             "--no-simulate".to_string(),
+            // --print implies --quiet, which would hide the progress otherwise
+            "--progress".to_string(),
+            "--newline".to_string(),
+            "--progress-template".to_string(),
+            format!(
+                "download:{}%(info.format_id)s:%(progress.downloaded_bytes)s:%(progress.total_bytes,progress.total_bytes_estimate)s",
+                PROGRESS_PREFIX
+            ),
             "--ffmpeg-location".to_string(),
             ffmpeg_path.to_string_lossy().to_string(),
             "--".to_string(),
@@ -62,17 +240,37 @@ impl YtDownloader {
         let ytdlp_path = globals::get_binary_path("yt-dlp");
         debug!("Using yt-dlp from path: {}", ytdlp_path.display());
 
-        let output = Command::new(ytdlp_path)
-            .args(&args)
-            .output()
-            .map_err(VideoProcessError::CommandError)?;
+        let mut command = Command::new(ytdlp_path);
+        command.args(&args);
+
+        progress.report(ProcessingPhase::Downloading, Some(0.0));
+        let mut download_progress = DownloadProgress::new(format);
+        let output = run_with_lines(command, DOWNLOAD_TIMEOUT, |line| {
+            match line.strip_prefix(PROGRESS_PREFIX) {
+                Some(line) => {
+                    progress.report(
+                        ProcessingPhase::Downloading,
+                        download_progress.percent(line),
+                    );
+                    true
+                }
+                None => false,
+            }
+        })
+        .await
+        .map_err(|err| match err {
+            RunError::TimedOut(timeout) => VideoProcessError::DownloadTimeout(timeout.as_secs()),
+            RunError::Io(err) => VideoProcessError::CommandError(err),
+        })?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoProcessError::DownloadError(stderr.to_string()));
+            warn!("yt-dlp failed for {}: {}", yt_link, output.stderr);
+            return Err(VideoProcessError::DownloadError(error_summary(
+                &output.stderr,
+            )));
         }
 
-        let parsed = self.parse_output(&output.stdout);
+        let parsed = self.parse_output(output.stdout.as_bytes());
         debug!("parseed {:?}", parsed);
 
         parsed
@@ -84,14 +282,18 @@ impl YtDownloader {
 
         // Split the output into lines
         let lines: Vec<&str> = output_str.lines().collect();
-        if lines.len() != 2 {
+        if lines.len() != 5 {
             return Err(VideoProcessError::FilenameError(
-                "Expected filename and duration output".to_string(),
+                "Expected filename, duration, id, thumbnail and uploader output".to_string(),
             ));
         }
 
         let filename = lines[0].trim();
         let duration_str = lines[1].trim();
+        let optional_field = |line: &str| {
+            let line = line.trim();
+            (!line.is_empty() && line != "NA").then(|| line.to_string())
+        };
 
         // Parse the duration (convert from string to f64)
         let duration_seconds = duration_str
@@ -120,6 +322,9 @@ impl YtDownloader {
             filename: name.to_string(),
             extension: ext.to_string(),
             duration_seconds,
+            video_id: optional_field(lines[2]),
+            thumbnail_url: optional_field(lines[3]),
+            uploader: optional_field(lines[4]),
         })
     }
 }
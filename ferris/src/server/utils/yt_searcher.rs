@@ -1,9 +1,19 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use strum::IntoStaticStr;
 use thiserror::Error;
+use tokio::process::Command;
 use tracing::{debug, info};
 use unidecode::unidecode;
 
-use crate::server::globals;
+use crate::server::{
+    globals,
+    utils::process_runner::{run, RunError},
+};
+
+/// Someone is waiting on their phone for these, so a search that takes longer is given up.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -12,7 +22,8 @@ pub struct SearchResult {
     pub id: String,
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum SearchError {
     #[error("Failed to execute youtube-dl: {0}")]
     ExecutionError(#[from] std::io::Error),
@@ -20,6 +31,8 @@ pub enum SearchError {
     JsonParseError(#[from] serde_json::Error),
     #[error("Missing required fields in response")]
     MissingFields,
+    #[error("YouTube search timed out after {0}s")]
+    SearchTimeout(u64),
 }
 
 pub struct YtSearcher {}
@@ -45,14 +58,20 @@ impl YtSearcher {
         let ytdlp_path = globals::get_binary_path("yt-dlp");
         debug!("Using yt-dlp from path: {}", ytdlp_path.display());
 
-        let output = std::process::Command::new(ytdlp_path)
-            .args(&args)
-            .output()?;
+        let mut command = Command::new(ytdlp_path);
+        command.args(args);
+
+        let output = run(command, SEARCH_TIMEOUT)
+            .await
+            .map_err(|err| match err {
+                RunError::TimedOut(timeout) => SearchError::SearchTimeout(timeout.as_secs()),
+                RunError::Io(err) => SearchError::ExecutionError(err),
+            })?;
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        debug!("search results: {}", output_str);
+        debug!("search results: {}", output.stdout);
 
-        output_str
+        output
+            .stdout
             .lines()
             .filter(|line| !line.trim().is_empty())
             .map(|line| {
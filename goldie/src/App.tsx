@@ -1,7 +1,10 @@
 import { useState, useEffect } from "react";
 import { useCurrentSong } from "./api/queries/useCurrentSong";
+import { useIdleSong } from "./api/queries/useIdleSong";
 import { useEventSource } from "./api/sse/useEventSource";
 import { ErrorScreen } from "./components/error/component";
+import { IdlePlayer } from "./components/idle-player/component";
+import { Intermission } from "./components/intermission/component";
 import QRCodeBanner from "./components/qr-code/component";
 import AutoApQRCode from "./components/autoap-qr-code/component";
 import { Queue } from "./components/queue/component";
@@ -38,6 +41,7 @@ function App() {
 
 function Home() {
   const currentSong = useCurrentSong();
+  const idleSong = useIdleSong();
   const { error } = useEventSource();
 
   if (error) {
@@ -46,8 +50,9 @@ function Home() {
 
   return (
     <div className="w-full h-full">
-      {!currentSong?.name && <Splash />}
+      {!currentSong?.name && (idleSong ? <IdlePlayer /> : <Splash />)}
       {currentSong?.name && <VideoPlayer />}
+      <Intermission />
       <QRCodeBanner />
       <Queue />
     </div>
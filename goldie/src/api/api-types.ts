@@ -7,14 +7,81 @@ export enum Status {
 
 export interface Song {
   name: string;
+  // the cache folder the video streams from
+  folder: string;
   uuid: string;
   status: Status;
+  duration_seconds?: number | null;
+  thumbnail_url?: string | null;
+  uploader?: string | null;
+  // unix milliseconds, as of the last playback update
+  starts_at?: number | null;
+  performers?: string[];
+  progress?: ProcessingProgress | null;
+  // counting retries and fallback formats
+  download_attempts?: number;
+  // why the last failed attempt failed
+  download_error?: string | null;
+}
+
+export enum ProcessingPhase {
+  Downloading = "Downloading",
+  ProcessingAudio = "ProcessingAudio",
+  Packaging = "Packaging",
+}
+
+export interface ProcessingProgress {
+  phase: ProcessingPhase;
+  // 0 to 100, null when the server can't tell
+  percent: number | null;
 }
 
 export interface FormattedSong extends Song {
   formattedName: string;
 }
 
+export enum PlaybackStatus {
+  Playing = "Playing",
+  Paused = "Paused",
+}
+
+export interface PlaybackState {
+  song_uuid: string | null;
+  status: PlaybackStatus;
+  position_seconds: number;
+  updated_at: number;
+}
+
+export enum StagePhase {
+  Singing = "Singing",
+  UpNext = "UpNext",
+  Break = "Break",
+}
+
+export interface StageState {
+  phase: StagePhase;
+  // unix milliseconds, null while singing
+  ends_at: number | null;
+  up_next: {
+    song_uuid: string;
+    name: string;
+    performers: string[];
+  } | null;
+}
+
+// a song from the cache that plays while the queue is empty
+export interface IdleSong {
+  uuid: string;
+  // the cache folder the video streams from
+  folder: string;
+  name: string;
+  is_key_changeable: boolean;
+  video_id: string | null;
+  duration_seconds: number | null;
+  thumbnail_url: string | null;
+  uploader: string | null;
+}
+
 export interface ServerIpResponse {
   ip: string;
 }
@@ -1,9 +1,9 @@
 import axios from "axios";
-import { API_URL } from "./sse/eventSource";
+import { ROOM_URL } from "./sse/eventSource";
 
 // Create axios instance with default config
 const axiosClient = axios.create({
-  baseURL: API_URL,
+  baseURL: ROOM_URL,
   timeout: 10000,
   headers: {
     "Content-Type": "application/json",
@@ -0,0 +1,25 @@
+import { useMutation, useQueryClient } from "@tanstack/react-query";
+import { IdleSong } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+// like play_next, passing the song that just finished makes a late call harmless
+async function nextIdleSong(songUuid?: string) {
+  const response = await axiosClient.post<IdleSong | null>(
+    "/idle_song/next",
+    null,
+    { params: { song_uuid: songUuid } }
+  );
+  return response;
+}
+
+export function useNextIdleSong() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: nextIdleSong,
+    onSuccess: (response) => {
+      queryClient.setQueryData(QUERY_KEYS.idleSong, response.data);
+    },
+  });
+}
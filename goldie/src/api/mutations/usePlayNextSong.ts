@@ -2,8 +2,10 @@ import { useMutation, useQueryClient } from "@tanstack/react-query";
 import { QUERY_KEYS } from "../queryKeys";
 import axiosClient from "../axios";
 
-async function playNextSong() {
-  const data = await axiosClient.post("/play_next", {
+// passing the song that just finished makes this a no-op if the server already moved on
+async function playNextSong(songUuid?: string) {
+  const data = await axiosClient.post("/play_next", null, {
+    params: { song_uuid: songUuid },
     headers: { "Content-Type": "application/json", Accept: "*" },
   });
   return data;
@@ -0,0 +1,20 @@
+import { useMutation } from "@tanstack/react-query";
+import { PlaybackStatus } from "../api-types";
+import axiosClient from "../axios";
+
+export type ReportPlaybackParams = {
+  song_uuid: string;
+  status: PlaybackStatus;
+  position_seconds: number;
+};
+
+async function reportPlayback(report: ReportPlaybackParams) {
+  const data = await axiosClient.post("/playback", report);
+  return data;
+}
+
+export function useReportPlayback() {
+  return useMutation({
+    mutationFn: reportPlayback,
+  });
+}
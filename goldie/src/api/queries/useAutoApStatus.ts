@@ -1,10 +1,12 @@
 import { useQuery } from "@tanstack/react-query";
 import axiosClient from "../axios";
+import { API_URL } from "../sse/eventSource";
 import { QUERY_KEYS } from "../queryKeys";
 import { AutoApStatusResponse } from "../api-types";
 
 async function getAutoApStatus() {
   const { data } = await axiosClient.get<AutoApStatusResponse>("/autoap_status", {
+    baseURL: API_URL,
     headers: { "Content-Type": "application/json", Accept: "*" },
   });
 
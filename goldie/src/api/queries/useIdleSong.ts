@@ -0,0 +1,19 @@
+import { useQuery } from "@tanstack/react-query";
+import { IdleSong } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchIdleSong = async () => {
+  const response = await axiosClient.get<IdleSong | null>("idle_song");
+  return response.data;
+};
+
+export const useIdleSong = () => {
+  const { data: idleSong } = useQuery<IdleSong | null>({
+    queryFn: fetchIdleSong,
+    queryKey: QUERY_KEYS.idleSong,
+    enabled: true,
+  });
+
+  return idleSong;
+};
@@ -1,11 +1,18 @@
 import { useQuery } from "@tanstack/react-query";
+import { PlaybackState } from "../api-types";
 import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchPlayback = async () => {
+  const response = await axiosClient.get<PlaybackState>("playback");
+  return response.data;
+};
 
 export const usePlayback = () => {
-  const { data: playbackState } = useQuery<boolean>({
+  const { data: playbackState } = useQuery<PlaybackState>({
+    queryFn: fetchPlayback,
     queryKey: QUERY_KEYS.playback,
     enabled: true,
-    initialData: true,
   });
 
   return playbackState;
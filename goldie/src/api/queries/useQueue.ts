@@ -5,11 +5,14 @@ import { Song } from "../api-types";
 import { formatSong } from "../../utils/format";
 
 async function getQueue() {
-  const { data } = await axiosClient.get<Array<Song>>("/song_list", {
-    headers: { "Content-Type": "application/json", Accept: "*" },
-  });
+  const { data } = await axiosClient.get<{ revision: number; queue: Song[] }>(
+    "/song_list",
+    {
+      headers: { "Content-Type": "application/json", Accept: "*" },
+    }
+  );
 
-  const formattedQueue = data.map(formatSong);
+  const formattedQueue = data.queue.map(formatSong);
   return formattedQueue;
 }
 
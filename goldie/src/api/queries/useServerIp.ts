@@ -1,10 +1,12 @@
 import { useQuery } from "@tanstack/react-query";
 import axiosClient from "../axios";
+import { API_URL } from "../sse/eventSource";
 import { QUERY_KEYS } from "../queryKeys";
 import { ServerIpResponse } from "../api-types";
 
 async function getServerIp() {
   const { data } = await axiosClient.get<ServerIpResponse>("/server_ip", {
+    baseURL: API_URL,
     headers: { "Content-Type": "application/json", Accept: "*" },
   });
 
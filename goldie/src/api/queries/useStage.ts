@@ -0,0 +1,19 @@
+import { useQuery } from "@tanstack/react-query";
+import { StageState } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchStage = async () => {
+  const response = await axiosClient.get<StageState>("stage");
+  return response.data;
+};
+
+export const useStage = () => {
+  const { data: stage } = useQuery<StageState>({
+    queryFn: fetchStage,
+    queryKey: QUERY_KEYS.stage,
+    enabled: true,
+  });
+
+  return stage;
+};
@@ -6,6 +6,8 @@ export const QUERY_KEYS = {
   key: ["key"] as const,
   playback: ["playback"] as const,
   restart: ["restart"] as const,
+  stage: ["stage"] as const,
+  idleSong: ["idleSong"] as const,
   queue: ["sse", EventType.QueueChangeEvent] as const,
   autoApStatus: ["autoApStatus"] as const,
 };
@@ -1,2 +1,8 @@
-export const SSE_URL = `${import.meta.env.VITE_API_URL}/sse`;
+// the TV joins a room with ?room=<id>; without it everything goes to the default room
+export const ROOM = new URLSearchParams(window.location.search).get("room");
+
 export const API_URL = `${import.meta.env.VITE_API_URL}`;
+export const ROOM_URL = ROOM
+  ? `${API_URL}/rooms/${encodeURIComponent(ROOM)}`
+  : API_URL;
+export const SSE_URL = `${ROOM_URL}/sse`;
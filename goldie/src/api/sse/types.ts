@@ -1,14 +1,24 @@
-import { FormattedSong } from "../api-types";
+import {
+  FormattedSong,
+  IdleSong,
+  PlaybackState,
+  ProcessingPhase,
+  StageState,
+} from "../api-types";
 
 export enum EventType {
   QueueChangeEvent = "QueueUpdated",
   KeyChange = "KeyChange",
-  TogglePlayback = "TogglePlayback",
+  PlaybackChanged = "PlaybackChanged",
   RestartSong = "RestartSong",
+  StageChanged = "StageChanged",
+  IdleSongChanged = "IdleSongChanged",
+  DownloadProgress = "DownloadProgress",
 }
 
 export type QueueUpdatedEvent = {
   type: EventType.QueueChangeEvent;
+  revision: number;
   queue: FormattedSong[];
 };
 
@@ -17,16 +27,37 @@ export type KeyChangeEvent = {
   current_key: number;
 };
 
-export type TogglePlaybackEvent = {
-  type: EventType.TogglePlayback;
+export type PlaybackChangedEvent = {
+  type: EventType.PlaybackChanged;
+  playback: PlaybackState;
 };
 
 export type RestartSongEvent = {
   type: EventType.RestartSong;
 };
 
+export type StageChangedEvent = {
+  type: EventType.StageChanged;
+  stage: StageState;
+};
+
+export type IdleSongChangedEvent = {
+  type: EventType.IdleSongChanged;
+  song: IdleSong | null;
+};
+
+export type DownloadProgressEvent = {
+  type: EventType.DownloadProgress;
+  song_uuid: string;
+  phase: ProcessingPhase;
+  percent: number | null;
+};
+
 export type SSEEvent =
   | QueueUpdatedEvent
-  | TogglePlaybackEvent
+  | PlaybackChangedEvent
   | KeyChangeEvent
-  | RestartSongEvent;
+  | RestartSongEvent
+  | StageChangedEvent
+  | IdleSongChangedEvent
+  | DownloadProgressEvent;
@@ -1,7 +1,7 @@
 import { useQuery, useQueryClient } from "@tanstack/react-query";
 import { useEffect } from "react";
 import { formatSong } from "../../utils/format";
-import { Song } from "../api-types";
+import { IdleSong, PlaybackState, Song, StageState } from "../api-types";
 import { useQueue } from "../queries/useQueue";
 import { QUERY_KEYS } from "../queryKeys";
 import { SSE_URL } from "./eventSource";
@@ -40,13 +40,41 @@ export const useEventSource = () => {
                     data.current_key
                   );
                   break;
-                case EventType.TogglePlayback: {
-                  const oldQueryData = queryClient.getQueryData<boolean>(
-                    QUERY_KEYS.playback
+                case EventType.PlaybackChanged:
+                  queryClient.setQueryData<PlaybackState>(
+                    QUERY_KEYS.playback,
+                    data.playback
+                  );
+                  break;
+                case EventType.StageChanged:
+                  queryClient.setQueryData<StageState>(
+                    QUERY_KEYS.stage,
+                    data.stage
+                  );
+                  break;
+                case EventType.IdleSongChanged:
+                  queryClient.setQueryData<IdleSong | null>(
+                    QUERY_KEYS.idleSong,
+                    data.song
+                  );
+                  break;
+                case EventType.DownloadProgress:
+                  queryClient.setQueryData<Song[]>(
+                    QUERY_KEYS.queue,
+                    (queue) =>
+                      queue?.map((song) =>
+                        song.uuid === data.song_uuid
+                          ? {
+                              ...song,
+                              progress: {
+                                phase: data.phase,
+                                percent: data.percent,
+                              },
+                            }
+                          : song
+                      )
                   );
-                  queryClient.setQueryData(QUERY_KEYS.playback, !oldQueryData);
                   break;
-                }
                 case EventType.RestartSong: {
                   const oldQueryData = queryClient.getQueryData<boolean>(
                     QUERY_KEYS.restart
@@ -0,0 +1,63 @@
+import dashjs from "dashjs";
+import { useEffect, useRef } from "react";
+import { useNextIdleSong } from "../../api/mutations/useNextIdleSong";
+import { useIdleSong } from "../../api/queries/useIdleSong";
+import { API_URL } from "../../api/sse/eventSource";
+
+// the pitch shifted videos have one audio track per key, -3 to +3
+const ORIGINAL_KEY_TRACK = "4";
+
+// Plays songs from the cache while nobody has anything queued. Nothing is reported back
+// beyond finishing a song, the server doesn't track idle songs like queued ones.
+export const IdlePlayer = () => {
+  const idleSong = useIdleSong();
+  const vidRef = useRef<HTMLVideoElement>(null);
+  const { mutate: nextIdleSong } = useNextIdleSong();
+
+  useEffect(() => {
+    if (!idleSong || !vidRef.current) {
+      return;
+    }
+
+    const player = dashjs.MediaPlayer().create();
+    player.initialize(
+      vidRef.current,
+      `${API_URL}/dash/${idleSong.folder}/${idleSong.folder}.mpd`,
+      true
+    );
+    player.on(dashjs.MediaPlayer.events.STREAM_INITIALIZED, () => {
+      if (!idleSong.is_key_changeable) return;
+
+      const track = player
+        .getTracksFor("audio")
+        .find((track) => track.id?.toString() === ORIGINAL_KEY_TRACK);
+      if (track) {
+        player.setCurrentTrack(track);
+      }
+    });
+    player.on(dashjs.MediaPlayer.events.PLAYBACK_ENDED, () =>
+      nextIdleSong(idleSong.uuid)
+    );
+    player.on(dashjs.MediaPlayer.events.ERROR, (e: any) => {
+      console.error("idle song error", e);
+      nextIdleSong(idleSong.uuid);
+    });
+
+    return () => player.destroy();
+  }, [idleSong, nextIdleSong]);
+
+  if (!idleSong) {
+    return null;
+  }
+
+  return (
+    <div className="relative w-full h-full bg-black">
+      <video className="w-full h-full" ref={vidRef} />
+      <div className="absolute top-4 left-1/2 -translate-x-1/2 px-6 py-2 rounded-full bg-black/60 text-center">
+        <p className="text-white text-lg font-medium">
+          queue a song from your phone to take the mic
+        </p>
+      </div>
+    </div>
+  );
+};
@@ -0,0 +1,76 @@
+import { useEffect, useState } from "react";
+import { useStage } from "../../api/queries/useStage";
+import { StagePhase } from "../../api/api-types";
+import { formatPerformers } from "../../utils/format";
+
+const secondsUntil = (endsAt: number) =>
+  Math.max(0, Math.ceil((endsAt - Date.now()) / 1000));
+
+const formatCountdown = (seconds: number) => {
+  const minutes = Math.floor(seconds / 60);
+  const rest = seconds % 60;
+  return minutes > 0
+    ? `${minutes}:${rest.toString().padStart(2, "0")}`
+    : `${rest}`;
+};
+
+// Covers the video while the room waits between songs: the "up next" countdown, or a
+// break the host scheduled.
+export const Intermission = () => {
+  const stage = useStage();
+  const [, setNow] = useState(Date.now());
+
+  useEffect(() => {
+    if (!stage?.ends_at) return;
+
+    const interval = setInterval(() => setNow(Date.now()), 1000);
+    return () => clearInterval(interval);
+  }, [stage?.ends_at]);
+
+  if (!stage || stage.phase === StagePhase.Singing) return null;
+
+  const countdown = stage.ends_at
+    ? formatCountdown(secondsUntil(stage.ends_at))
+    : null;
+  const upNext = stage.up_next;
+  const performers = formatPerformers(upNext?.performers);
+  const songName = upNext?.name.replace("./assets/", "").replaceAll("_", " ");
+
+  return (
+    <div className="absolute inset-0 flex flex-col items-center justify-center bg-gradient-to-br from-purple-900 via-indigo-900 to-blue-900 text-center">
+      {stage.phase === StagePhase.Break ? (
+        <>
+          <h1 className="text-8xl text-white font-bold mb-8">
+            taking a break
+          </h1>
+          {countdown && (
+            <h2 className="text-6xl text-purple-200 font-bold mb-8 tabular-nums">
+              back in {countdown}
+            </h2>
+          )}
+          {upNext && (
+            <p className="text-purple-200 text-2xl">
+              up next: {performers ? `${performers} – ` : ""}
+              {songName}
+            </p>
+          )}
+        </>
+      ) : (
+        <>
+          <p className="text-purple-200 text-3xl mb-4">up next</p>
+          {performers && (
+            <h1 className="text-8xl text-white font-bold mb-4">
+              {performers}
+            </h1>
+          )}
+          <h2 className="text-4xl text-white mb-12">{songName}</h2>
+          {countdown && (
+            <p className="text-9xl text-white font-bold tabular-nums animate-pulse">
+              {countdown}
+            </p>
+          )}
+        </>
+      )}
+    </div>
+  );
+};
@@ -1,12 +1,14 @@
 import { useCurrentSong } from "../../api/queries/useCurrentSong";
 import { useQueueChanges } from "../../api/sse/hooks";
 import { Status } from "../../api/api-types";
+import { formatPerformers, formatProgress } from "../../utils/format";
 
 export const Queue = () => {
   const queue = useQueueChanges();
   const currentSong = useCurrentSong();
 
   const nextSongs = queue?.slice(1, 3) || [];
+  const nowSinging = formatPerformers(queue?.[0]?.performers);
 
   return (
     <div className="absolute bottom-4 right-4 w-64 bg-white rounded-lg shadow-xl overflow-hidden">
@@ -20,9 +22,16 @@ export const Queue = () => {
                 <div className="w-1.5 h-1.5 rounded-full bg-purple-500 animate-pulse" />
               </div>
             )}
-            <p className="text-purple-900 text-xs font-medium truncate">
-              {currentSong?.formattedName}
-            </p>
+            <div className="flex-1 min-w-0">
+              <p className="text-purple-900 text-xs font-medium truncate">
+                {currentSong?.formattedName}
+              </p>
+              {nowSinging && (
+                <p className="text-purple-700 text-xs truncate">
+                  {nowSinging}
+                </p>
+              )}
+            </div>
           </div>
         </div>
       )}
@@ -50,6 +59,20 @@ export const Queue = () => {
                   <p className="text-gray-900 text-sm truncate">
                     {song.formattedName}
                   </p>
+                  {(song.performers?.length ||
+                    song.uploader ||
+                    song.progress) && (
+                    <p className="text-gray-500 text-xs truncate">
+                      {[
+                        formatPerformers(song.performers),
+                        song.uploader,
+                        song.status === Status.InProgress &&
+                          formatProgress(song.progress),
+                      ]
+                        .filter(Boolean)
+                        .join(" · ")}
+                    </p>
+                  )}
                 </div>
               </div>
             </div>
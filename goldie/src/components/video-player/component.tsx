@@ -1,12 +1,17 @@
 import dashjs from "dashjs";
 import { useCallback, useEffect, useRef, useState } from "react";
-import { Status } from "../../api/api-types";
+import { PlaybackStatus, Status } from "../../api/api-types";
 import { usePlayNextSong } from "../../api/mutations/usePlayNextSong";
+import { useReportPlayback } from "../../api/mutations/useReportPlayback";
 import { useCurrentSong } from "../../api/queries/useCurrentSong";
 import { useKey } from "../../api/queries/useKey";
 import { usePlayback } from "../../api/queries/usePlayback";
 import { API_URL } from "../../api/sse/eventSource";
 import { useRestart } from "../../api/queries/useRestart";
+import { formatProgress } from "../../utils/format";
+
+// how often the display tells the server where it is in the song
+const PLAYBACK_REPORT_INTERVAL_MS = 5000;
 
 function VideoPlayer() {
   const currentSong = useCurrentSong();
@@ -15,13 +20,16 @@ function VideoPlayer() {
   const { mutate: playNextSong } = usePlayNextSong();
   const [progress, setProgress] = useState(0);
   const playbackState = usePlayback();
+  const playbackRef = useRef(playbackState);
+  playbackRef.current = playbackState;
+  const { mutate: reportPlayback } = useReportPlayback();
   const key = useKey();
   const [countdown, setCountdown] = useState(5);
   const restart = useRestart();
 
   const handleEnded = useCallback(() => {
-    playNextSong();
-  }, [playNextSong]);
+    playNextSong(currentSong?.uuid);
+  }, [playNextSong, currentSong?.uuid]);
 
   const handleError = useCallback((e: any) => {
     console.error("error", e);
@@ -47,13 +55,43 @@ function VideoPlayer() {
     }
   }, [restart]);
 
+  const handlePlaybackChange = useCallback(() => {
+    const player = playerRef.current;
+    if (!player || !currentSong?.uuid) {
+      return;
+    }
+
+    reportPlayback({
+      song_uuid: currentSong.uuid,
+      status: player.isPaused() ? PlaybackStatus.Paused : PlaybackStatus.Playing,
+      position_seconds: player.time(),
+    });
+  }, [currentSong?.uuid, reportPlayback]);
+
   useEffect(() => {
-    if (playbackState) {
+    // ignore state left over from the previous song until the server catches up
+    if (!playbackState || playbackState.song_uuid !== currentSong?.uuid) {
+      return;
+    }
+
+    if (playbackState.status === PlaybackStatus.Playing) {
       playerRef.current?.play();
     } else {
       playerRef.current?.pause();
     }
-  }, [playbackState]);
+  }, [playbackState, currentSong?.uuid]);
+
+  useEffect(() => {
+    if (currentSong?.status !== Status.Success) {
+      return;
+    }
+
+    const interval = setInterval(
+      handlePlaybackChange,
+      PLAYBACK_REPORT_INTERVAL_MS
+    );
+    return () => clearInterval(interval);
+  }, [currentSong?.status, handlePlaybackChange]);
 
   useEffect(() => {
     if (playerRef.current) {
@@ -78,10 +116,30 @@ function VideoPlayer() {
       // TODO: the first segment is the lowest key.
       player.initialize(
         vidRef.current,
-        `${API_URL}/dash/${currentSong.name}/${currentSong.name}.mpd`,
+        `${API_URL}/dash/${currentSong.folder}/${currentSong.folder}.mpd`,
         true
       );
+      player.on(dashjs.MediaPlayer.events.STREAM_INITIALIZED, () => {
+        // a display that reconnects mid-song picks up where the server says it was
+        const playback = playbackRef.current;
+        if (playback?.song_uuid === currentSong.uuid) {
+          if (playback.position_seconds > 1) {
+            player.seek(playback.position_seconds);
+          }
+          if (playback.status === PlaybackStatus.Paused) {
+            player.pause();
+          }
+        }
+      });
       player.on(dashjs.MediaPlayer.events.PLAYBACK_ENDED, handleEnded);
+      player.on(
+        dashjs.MediaPlayer.events.PLAYBACK_PLAYING,
+        handlePlaybackChange
+      );
+      player.on(
+        dashjs.MediaPlayer.events.PLAYBACK_PAUSED,
+        handlePlaybackChange
+      );
       player.on(
         dashjs.MediaPlayer.events.PLAYBACK_TIME_UPDATED,
         handleTimeUpdate
@@ -118,7 +176,13 @@ function VideoPlayer() {
         playerRef.current = null;
       }
     };
-  }, [currentSong, handleEnded, handleError, switchToTrack]);
+  }, [
+    currentSong,
+    handleEnded,
+    handleError,
+    handlePlaybackChange,
+    switchToTrack,
+  ]);
 
   const handleTimeUpdate = () => {
     if (playerRef.current) {
@@ -134,7 +198,7 @@ function VideoPlayer() {
     if (currentSong?.status === Status.Failed) {
       setCountdown(10);
       const timer = setTimeout(() => {
-        playNextSong();
+        playNextSong(currentSong.uuid);
       }, 10000);
 
       const countdownInterval = setInterval(() => {
@@ -161,6 +225,16 @@ function VideoPlayer() {
             <p className="text-white text-lg font-medium mt-1">
               downloading {currentSong.formattedName}...
             </p>
+            {currentSong.progress && (
+              <p className="text-purple-200 text-sm mt-1">
+                {formatProgress(currentSong.progress)}
+              </p>
+            )}
+            {(currentSong.download_attempts ?? 0) > 1 && (
+              <p className="text-purple-300/70 text-xs mt-1">
+                attempt {currentSong.download_attempts}
+              </p>
+            )}
           </div>
         </div>
       )}
@@ -173,6 +247,11 @@ function VideoPlayer() {
             <p className="text-gray-300 text-lg mb-2">
               Unable to download {currentSong.formattedName}
             </p>
+            {currentSong.download_error && (
+              <p className="text-gray-400 text-sm mb-2">
+                {currentSong.download_error}
+              </p>
+            )}
             <p className="text-red-400 text-sm">
               Skipping to next song in {countdown} seconds...
             </p>
@@ -1,7 +1,35 @@
-import { Song } from "../api/api-types";
+import {
+  ProcessingPhase,
+  ProcessingProgress,
+  Song,
+} from "../api/api-types";
 
 export const formatSong = (song: Song) => {
   const rawName = song.name;
   const formattedName = rawName.replace("./assets/", "").replaceAll("_", " ");
   return { ...song, formattedName };
 };
+
+const PROCESSING_PHASES: Record<ProcessingPhase, string> = {
+  [ProcessingPhase.Downloading]: "downloading",
+  [ProcessingPhase.ProcessingAudio]: "processing audio",
+  [ProcessingPhase.Packaging]: "packaging",
+};
+
+// "downloading 42%" while a song is getting ready
+export const formatProgress = (progress?: ProcessingProgress | null) => {
+  if (!progress) return null;
+
+  const phase = PROCESSING_PHASES[progress.phase];
+  return progress.percent == null
+    ? phase
+    : `${phase} ${Math.floor(progress.percent)}%`;
+};
+
+// "Ann, Bob & Cat" for everyone singing a song
+export const formatPerformers = (performers?: string[]) => {
+  if (!performers || performers.length === 0) return null;
+  if (performers.length === 1) return performers[0];
+
+  return `${performers.slice(0, -1).join(", ")} & ${performers.at(-1)}`;
+};
@@ -6,15 +6,157 @@ export enum Status {
 
 export interface Song {
   name: string;
+  folder: string;
   uuid: string;
   status: Status;
   is_key_changeable: boolean;
+  video_id?: string | null;
+  duration_seconds?: number | null;
+  thumbnail_url?: string | null;
+  uploader?: string | null;
+  joined?: Performer[];
+  // only on songs from song_list and QueueUpdated
+  // starts_at is in unix milliseconds, as of the last playback update
+  starts_at?: number | null;
+  performers?: string[];
+  progress?: ProcessingProgress | null;
+  // counting retries and fallback formats
+  download_attempts?: number;
+  // why the last failed attempt failed
+  download_error?: string | null;
+}
+
+export enum ProcessingPhase {
+  Downloading = "Downloading",
+  ProcessingAudio = "ProcessingAudio",
+  Packaging = "Packaging",
+}
+
+export interface ProcessingProgress {
+  phase: ProcessingPhase;
+  // 0 to 100, null when the server can't tell
+  percent: number | null;
+}
+
+// someone singing along on a song another phone queued
+export interface Performer {
+  name: string;
 }
 
 export interface FormattedSong extends Song {
   formattedName: string;
 }
 
+export interface QueueState {
+  revision: number;
+  queue: Song[];
+}
+
+export enum PlaybackStatus {
+  Playing = "Playing",
+  Paused = "Paused",
+}
+
+export interface PlaybackState {
+  song_uuid: string | null;
+  status: PlaybackStatus;
+  position_seconds: number;
+  updated_at: number;
+}
+
+export interface SkipVotes {
+  song_uuid: string | null;
+  votes: number;
+  needed: number;
+}
+
+// the songs this phone queued or joined, the queue doesn't say who's behind a song
+export interface OwnSongs {
+  requested: string[];
+  joined: string[];
+}
+
+export enum SkipReason {
+  DroppedFailed = "DroppedFailed",
+  ParkedFailed = "ParkedFailed",
+  StillDownloading = "StillDownloading",
+}
+
+export interface SkippedSong {
+  song: Song;
+  reason: SkipReason;
+}
+
+export enum QueueOutcome {
+  Queued = "Queued",
+  Pending = "Pending",
+}
+
+export enum RequestDecision {
+  Approved = "Approved",
+  Rejected = "Rejected",
+}
+
+export interface DecidedRequest {
+  song: Song;
+  decision: RequestDecision;
+}
+
+export interface PlaylistSummary {
+  name: string;
+  songs: number;
+}
+
+export interface QueuedPlaylist {
+  queued: number;
+  pending: number;
+  rejected: { title: string; message: string }[];
+}
+
+export enum StagePhase {
+  Singing = "Singing",
+  UpNext = "UpNext",
+  Break = "Break",
+}
+
+export interface StageState {
+  phase: StagePhase;
+  // unix milliseconds, null while singing
+  ends_at: number | null;
+  up_next: {
+    song_uuid: string;
+    name: string;
+    performers: string[];
+  } | null;
+}
+
+export interface Interstitial {
+  seconds: number;
+}
+
+export interface ScheduledBreak {
+  id: string;
+  // unix seconds
+  starts_at: number;
+  minutes: number;
+}
+
+// which cached songs play while the queue is empty
+export type IdleSource = "Random" | "Popular" | { Playlist: string };
+
+export interface IdleMode {
+  enabled: boolean;
+  after_seconds: number;
+  source: IdleSource;
+}
+
 export interface ServerIpResponse {
   ip: string;
 }
+
+// every failed request responds with this body
+export interface ApiErrorBody {
+  code: string;
+  message: string;
+  details?: Record<string, unknown>;
+}
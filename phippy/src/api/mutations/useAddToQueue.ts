@@ -1,4 +1,6 @@
 import { useMutation } from "@tanstack/react-query";
+import { isAxiosError } from "axios";
+import { ApiErrorBody, QueueOutcome } from "../api-types";
 import axios from "../axios";
 import queryClient from "../queryClient";
 import { QUERY_KEYS } from "../queryKeys";
@@ -9,8 +11,21 @@ export type AddToQueueParams = {
   is_key_changeable: boolean;
 };
 
+export type QueueSongAccepted = {
+  outcome: QueueOutcome;
+};
+
+// the server explains why it turned a song down, fall back to a generic message
+export const rejectionMessage = (error: Error) => {
+  if (isAxiosError<ApiErrorBody>(error) && error.response?.data?.message) {
+    return error.response.data.message;
+  }
+
+  return "couldn't add that song";
+};
+
 const addToQueue = async (song: AddToQueueParams) => {
-  const response = await axios.post("/queue_song", song);
+  const response = await axios.post<QueueSongAccepted>("/queue_song", song);
   console.log(song);
 
   return response;
@@ -21,6 +36,8 @@ export const useAddToQueue = () => {
     mutationFn: addToQueue,
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.pendingSongs });
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.ownSongs });
     },
   });
 };
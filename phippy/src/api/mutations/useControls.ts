@@ -1,4 +1,5 @@
 import { useMutation } from "@tanstack/react-query";
+import { PlaybackState } from "../api-types";
 import axios from "../axios";
 import queryClient from "../queryClient";
 import { QUERY_KEYS } from "../queryKeys";
@@ -8,8 +9,13 @@ const skip = async () => {
   return response;
 };
 
-const togglePlayback = async () => {
-  const response = await axios.post("/toggle_playback");
+const play = async () => {
+  const response = await axios.post<PlaybackState>("/play");
+  return response;
+};
+
+const pause = async () => {
+  const response = await axios.post<PlaybackState>("/pause");
   return response;
 };
 
@@ -22,8 +28,20 @@ export const useSkip = () => {
   });
 };
 
-export const useTogglePlayback = () => {
+export const usePlay = () => {
   return useMutation({
-    mutationFn: togglePlayback,
+    mutationFn: play,
+    onSuccess: (response) => {
+      queryClient.setQueryData(QUERY_KEYS.playback, response.data);
+    },
+  });
+};
+
+export const usePause = () => {
+  return useMutation({
+    mutationFn: pause,
+    onSuccess: (response) => {
+      queryClient.setQueryData(QUERY_KEYS.playback, response.data);
+    },
   });
 };
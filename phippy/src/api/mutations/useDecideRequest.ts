@@ -0,0 +1,42 @@
+import { useMutation } from "@tanstack/react-query";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+
+export type ApproveSongParams = {
+  song_uuid: string;
+  position?: number;
+};
+
+export type RejectSongParams = {
+  song_uuid: string;
+};
+
+const approveSong = async (params: ApproveSongParams) => {
+  const response = await axios.post("/approve_song", params);
+  return response;
+};
+
+const rejectSong = async (params: RejectSongParams) => {
+  const response = await axios.post("/reject_song", params);
+  return response;
+};
+
+const onDecided = () => {
+  queryClient.invalidateQueries({ queryKey: QUERY_KEYS.pendingSongs });
+  queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+};
+
+export const useApproveSong = () => {
+  return useMutation({
+    mutationFn: approveSong,
+    onSuccess: onDecided,
+  });
+};
+
+export const useRejectSong = () => {
+  return useMutation({
+    mutationFn: rejectSong,
+    onSuccess: onDecided,
+  });
+};
@@ -2,13 +2,16 @@ import { useMutation } from "@tanstack/react-query";
 import axios from "../axios";
 import queryClient from "../queryClient";
 import { QUERY_KEYS } from "../queryKeys";
+import { catchUpOnStaleQueue, ifMatchQueueRevision } from "../queueRevision";
 
 export type DeleteSongParams = {
   song_uuid: string;
 };
 
 const deleteSong = async (song: DeleteSongParams) => {
-  const response = await axios.post("/remove_song", song);
+  const response = await axios.post("/remove_song", song, {
+    headers: ifMatchQueueRevision(),
+  });
   return response;
 };
 
@@ -18,5 +21,6 @@ export const useDeleteSong = () => {
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
     },
+    onError: catchUpOnStaleQueue,
   });
 };
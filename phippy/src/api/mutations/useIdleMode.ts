@@ -0,0 +1,19 @@
+import { useMutation } from "@tanstack/react-query";
+import { IdleMode } from "../api-types";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+
+const setIdleMode = async (idleMode: IdleMode) => {
+  const response = await axios.post("/idle_mode", idleMode);
+  return response;
+};
+
+export const useSetIdleMode = () => {
+  return useMutation({
+    mutationFn: setIdleMode,
+    onSuccess: (_, idleMode) => {
+      queryClient.setQueryData(QUERY_KEYS.idleMode, idleMode);
+    },
+  });
+};
@@ -0,0 +1,43 @@
+import { useMutation } from "@tanstack/react-query";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+
+export type JoinSongParams = {
+  song_uuid: string;
+  singer: string;
+};
+
+export type LeaveSongParams = {
+  song_uuid: string;
+};
+
+const joinSong = async (params: JoinSongParams) => {
+  const response = await axios.post("/join_song", params);
+  return response;
+};
+
+const leaveSong = async (params: LeaveSongParams) => {
+  const response = await axios.post("/leave_song", params);
+  return response;
+};
+
+export const useJoinSong = () => {
+  return useMutation({
+    mutationFn: joinSong,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.ownSongs });
+    },
+  });
+};
+
+export const useLeaveSong = () => {
+  return useMutation({
+    mutationFn: leaveSong,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.ownSongs });
+    },
+  });
+};
@@ -0,0 +1,64 @@
+import { useMutation } from "@tanstack/react-query";
+import { QueuedPlaylist } from "../api-types";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+import { API_URL } from "../sse/eventSource";
+
+const playlistPath = (name: string) =>
+  `/playlists/${encodeURIComponent(name)}`;
+
+export const playlistExportUrl = (
+  name: string,
+  format: "json" | "m3u" | "csv"
+) => `${API_URL}${playlistPath(name)}/export?format=${format}`;
+
+export type SavePlaylistParams = {
+  name: string;
+  source: "queue" | "history";
+};
+
+const savePlaylist = async ({ name, source }: SavePlaylistParams) => {
+  const response = await axios.post(`${playlistPath(name)}/save`, { source });
+  return response;
+};
+
+const queuePlaylist = async (name: string) => {
+  const response = await axios.post<QueuedPlaylist>(
+    `${playlistPath(name)}/queue`,
+    {}
+  );
+  return response;
+};
+
+const deletePlaylist = async (name: string) => {
+  const response = await axios.delete(`${API_URL}${playlistPath(name)}`);
+  return response;
+};
+
+export const useSavePlaylist = () => {
+  return useMutation({
+    mutationFn: savePlaylist,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.playlists });
+    },
+  });
+};
+
+export const useQueuePlaylist = () => {
+  return useMutation({
+    mutationFn: queuePlaylist,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+    },
+  });
+};
+
+export const useDeletePlaylist = () => {
+  return useMutation({
+    mutationFn: deletePlaylist,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.playlists });
+    },
+  });
+};
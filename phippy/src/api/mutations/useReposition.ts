@@ -3,6 +3,7 @@ import axios from "../axios";
 import queryClient from "../queryClient";
 import { QUERY_KEYS } from "../queryKeys";
 import { FormattedSong } from "../api-types";
+import { catchUpOnStaleQueue, ifMatchQueueRevision } from "../queueRevision";
 
 export type RepositionParams = {
   position: number;
@@ -10,7 +11,9 @@ export type RepositionParams = {
 };
 
 const reposition = async (repositionData: RepositionParams) => {
-  const response = await axios.post("/reposition_song", repositionData);
+  const response = await axios.post("/reposition_song", repositionData, {
+    headers: ifMatchQueueRevision(),
+  });
   return response;
 };
 
@@ -35,6 +38,13 @@ export const useReposition = () => {
       queryClient.setQueryData(QUERY_KEYS.queue, newQueue);
       return { previousQueue };
     },
+    onError(error, _variables, context) {
+      if (catchUpOnStaleQueue(error)) return;
+
+      if (context?.previousQueue) {
+        queryClient.setQueryData(QUERY_KEYS.queue, context.previousQueue);
+      }
+    },
     // onSuccess: () => {
     //   queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
     // },
@@ -0,0 +1,27 @@
+import { useMutation } from "@tanstack/react-query";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+import { catchUpOnStaleQueue, ifMatchQueueRevision } from "../queueRevision";
+
+export type RetrySongParams = {
+  song_uuid: string;
+};
+
+const retrySong = async (params: RetrySongParams) => {
+  const response = await axios.post("/retry_song", params, {
+    headers: ifMatchQueueRevision(),
+  });
+  return response;
+};
+
+export const useRetrySong = () => {
+  return useMutation({
+    mutationFn: retrySong,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.queue });
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.parkedSongs });
+    },
+    onError: catchUpOnStaleQueue,
+  });
+};
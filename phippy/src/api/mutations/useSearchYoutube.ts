@@ -1,5 +1,6 @@
 import { useQuery } from "@tanstack/react-query";
 import axios from "../axios";
+import { API_URL } from "../sse/eventSource";
 
 export type SearchResult = {
   title: string;
@@ -13,6 +14,7 @@ export type SearchSongParams = {
 
 const searchYoutube = async (query: string) => {
   const response = await axios.get<Array<SearchResult>>("/search", {
+    baseURL: API_URL,
     params: {
       query: `${query} karaoke`,
     },
@@ -0,0 +1,81 @@
+import { useMutation } from "@tanstack/react-query";
+import { Interstitial, ScheduledBreak, StageState } from "../api-types";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+
+const skipStage = async () => {
+  const response = await axios.post<StageState>("/stage/skip");
+  return response;
+};
+
+const extendStage = async (seconds: number) => {
+  const response = await axios.post<StageState>("/stage/extend", { seconds });
+  return response;
+};
+
+const setInterstitial = async (interstitial: Interstitial) => {
+  const response = await axios.post("/interstitial", interstitial);
+  return response;
+};
+
+export type ScheduleBreakParams = {
+  // unix seconds, or after the current song when left out
+  starts_at?: number;
+  minutes: number;
+};
+
+const scheduleBreak = async (params: ScheduleBreakParams) => {
+  const response = await axios.post<ScheduledBreak>("/breaks", params);
+  return response;
+};
+
+const cancelBreak = async (id: string) => {
+  const response = await axios.delete(`/breaks/${encodeURIComponent(id)}`);
+  return response;
+};
+
+export const useSkipStage = () => {
+  return useMutation({
+    mutationFn: skipStage,
+    onSuccess: (response) => {
+      queryClient.setQueryData(QUERY_KEYS.stage, response.data);
+    },
+  });
+};
+
+export const useExtendStage = () => {
+  return useMutation({
+    mutationFn: extendStage,
+    onSuccess: (response) => {
+      queryClient.setQueryData(QUERY_KEYS.stage, response.data);
+    },
+  });
+};
+
+export const useSetInterstitial = () => {
+  return useMutation({
+    mutationFn: setInterstitial,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.interstitial });
+    },
+  });
+};
+
+export const useScheduleBreak = () => {
+  return useMutation({
+    mutationFn: scheduleBreak,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.breaks });
+    },
+  });
+};
+
+export const useCancelBreak = () => {
+  return useMutation({
+    mutationFn: cancelBreak,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.breaks });
+    },
+  });
+};
@@ -0,0 +1,19 @@
+import { useMutation } from "@tanstack/react-query";
+import { SkipVotes } from "../api-types";
+import axios from "../axios";
+import queryClient from "../queryClient";
+import { QUERY_KEYS } from "../queryKeys";
+
+const voteSkip = async () => {
+  const response = await axios.post<SkipVotes>("/vote_skip");
+  return response;
+};
+
+export const useVoteSkip = () => {
+  return useMutation({
+    mutationFn: voteSkip,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: QUERY_KEYS.skipVotes });
+    },
+  });
+};
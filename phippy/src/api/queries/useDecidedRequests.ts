@@ -0,0 +1,14 @@
+import { useQuery } from "@tanstack/react-query";
+import { DecidedRequest } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+
+// only ever filled in by the RequestDecided sse event, newest first
+export const useDecidedRequests = () => {
+  const { data: decidedRequests } = useQuery<DecidedRequest[]>({
+    queryKey: QUERY_KEYS.decidedRequests,
+    enabled: true,
+    initialData: [],
+  });
+
+  return decidedRequests;
+};
@@ -0,0 +1,19 @@
+import { useQuery } from "@tanstack/react-query";
+import { IdleMode } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchIdleMode = async () => {
+  const response = await axiosClient.get<IdleMode>("idle_mode");
+  return response.data;
+};
+
+export const useIdleMode = () => {
+  const { data: idleMode } = useQuery<IdleMode>({
+    queryFn: fetchIdleMode,
+    queryKey: QUERY_KEYS.idleMode,
+    enabled: true,
+  });
+
+  return idleMode;
+};
@@ -0,0 +1,23 @@
+import { useQuery } from "@tanstack/react-query";
+import { OwnSongs } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchOwnSongs = async () => {
+  const response = await axiosClient.get<OwnSongs>("own_songs");
+
+  return response.data;
+};
+
+const NO_SONGS: OwnSongs = { requested: [], joined: [] };
+
+// the server knows this phone by its cookie, so it's the one to ask
+export const useOwnSongs = () => {
+  const { data: ownSongs } = useQuery<OwnSongs>({
+    queryFn: fetchOwnSongs,
+    queryKey: QUERY_KEYS.ownSongs,
+    enabled: true,
+  });
+
+  return ownSongs ?? NO_SONGS;
+};
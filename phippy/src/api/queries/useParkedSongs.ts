@@ -0,0 +1,23 @@
+import { useQuery } from "@tanstack/react-query";
+import { FormattedSong, Song } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+import { formatSong } from "../../utils/format";
+
+const fetchParkedSongs = async () => {
+  const response = await axiosClient.get<Song[]>("parked_songs");
+
+  return response.data.map(formatSong);
+};
+
+// failed downloads taken out of the queue, refetched when the SongsSkipped sse event
+// says more may have been parked
+export const useParkedSongs = () => {
+  const { data: parkedSongs } = useQuery<FormattedSong[]>({
+    queryFn: fetchParkedSongs,
+    queryKey: QUERY_KEYS.parkedSongs,
+    enabled: true,
+  });
+
+  return parkedSongs ?? [];
+};
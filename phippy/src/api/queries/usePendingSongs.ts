@@ -0,0 +1,22 @@
+import { useQuery } from "@tanstack/react-query";
+import { FormattedSong, Song } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+import { formatSong } from "../../utils/format";
+
+const fetchPendingSongs = async () => {
+  const response = await axiosClient.get<Song[]>("pending_songs");
+
+  return response.data.map(formatSong);
+};
+
+// requests waiting on the host, kept fresh by the PendingUpdated sse event
+export const usePendingSongs = () => {
+  const { data: pendingSongs } = useQuery<FormattedSong[]>({
+    queryFn: fetchPendingSongs,
+    queryKey: QUERY_KEYS.pendingSongs,
+    enabled: true,
+  });
+
+  return pendingSongs ?? [];
+};
@@ -0,0 +1,24 @@
+import { useQuery } from "@tanstack/react-query";
+import { PlaylistSummary } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+import { API_URL } from "../sse/eventSource";
+
+// playlists are shared by every room, so they live outside the room's url
+const fetchPlaylists = async () => {
+  const response = await axiosClient.get<PlaylistSummary[]>(
+    `${API_URL}/playlists`
+  );
+
+  return response.data;
+};
+
+export const usePlaylists = () => {
+  const { data: playlists } = useQuery<PlaylistSummary[]>({
+    queryFn: fetchPlaylists,
+    queryKey: QUERY_KEYS.playlists,
+    enabled: true,
+  });
+
+  return playlists ?? [];
+};
@@ -1,12 +1,14 @@
 import { useQuery } from "@tanstack/react-query";
 import { formatSong } from "../../utils/format";
-import { FormattedSong, Song } from "../api-types";
+import { FormattedSong, QueueState } from "../api-types";
 import axiosClient from "../axios";
 import { QUERY_KEYS } from "../queryKeys";
+import { setQueueRevision } from "../queueRevision";
 
 async function getQueue(): Promise<Array<FormattedSong>> {
-  const { data } = await axiosClient.get<Array<Song>>("/song_list");
-  return data.map(formatSong);
+  const { data } = await axiosClient.get<QueueState>("/song_list");
+  setQueueRevision(data.revision);
+  return data.queue.map(formatSong);
 }
 
 export function useQueue() {
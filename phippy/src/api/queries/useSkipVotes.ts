@@ -0,0 +1,20 @@
+import { useQuery } from "@tanstack/react-query";
+import { SkipVotes } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchSkipVotes = async () => {
+  const response = await axiosClient.get<SkipVotes>("skip_votes");
+
+  return response.data;
+};
+
+export const useSkipVotes = () => {
+  const { data: skipVotes } = useQuery<SkipVotes>({
+    queryFn: fetchSkipVotes,
+    queryKey: QUERY_KEYS.skipVotes,
+    enabled: true,
+  });
+
+  return skipVotes;
+};
@@ -0,0 +1,14 @@
+import { useQuery } from "@tanstack/react-query";
+import { SkippedSong } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+
+// only ever filled in by the SongsSkipped sse event
+export const useSkippedSongs = () => {
+  const { data: skippedSongs } = useQuery<SkippedSong[]>({
+    queryKey: QUERY_KEYS.skippedSongs,
+    enabled: true,
+    initialData: [],
+  });
+
+  return skippedSongs;
+};
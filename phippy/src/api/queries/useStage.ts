@@ -0,0 +1,49 @@
+import { useQuery } from "@tanstack/react-query";
+import { Interstitial, ScheduledBreak, StageState } from "../api-types";
+import { QUERY_KEYS } from "../queryKeys";
+import axiosClient from "../axios";
+
+const fetchStage = async () => {
+  const response = await axiosClient.get<StageState>("stage");
+  return response.data;
+};
+
+const fetchInterstitial = async () => {
+  const response = await axiosClient.get<Interstitial>("interstitial");
+  return response.data;
+};
+
+const fetchBreaks = async () => {
+  const response = await axiosClient.get<ScheduledBreak[]>("breaks");
+  return response.data;
+};
+
+export const useStage = () => {
+  const { data: stage } = useQuery<StageState>({
+    queryFn: fetchStage,
+    queryKey: QUERY_KEYS.stage,
+    enabled: true,
+  });
+
+  return stage;
+};
+
+export const useInterstitial = () => {
+  const { data: interstitial } = useQuery<Interstitial>({
+    queryFn: fetchInterstitial,
+    queryKey: QUERY_KEYS.interstitial,
+    enabled: true,
+  });
+
+  return interstitial;
+};
+
+export const useBreaks = () => {
+  const { data: breaks } = useQuery<ScheduledBreak[]>({
+    queryFn: fetchBreaks,
+    queryKey: QUERY_KEYS.breaks,
+    enabled: true,
+  });
+
+  return breaks ?? [];
+};
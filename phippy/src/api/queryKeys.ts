@@ -4,5 +4,17 @@ export const QUERY_KEYS = {
   playNextSong: ["playNextSong"] as const,
   queue: ["sse", EventType.QueueChange] as const,
   key: ["key"] as const,
+  playback: ["playback"] as const,
+  skipVotes: ["skipVotes"] as const,
+  ownSongs: ["ownSongs"] as const,
+  skippedSongs: ["skippedSongs"] as const,
+  pendingSongs: ["pendingSongs"] as const,
+  parkedSongs: ["parkedSongs"] as const,
+  decidedRequests: ["decidedRequests"] as const,
+  playlists: ["playlists"] as const,
+  stage: ["stage"] as const,
+  interstitial: ["interstitial"] as const,
+  breaks: ["breaks"] as const,
+  idleMode: ["idleMode"] as const,
   auth: ["auth"] as const,
 };
@@ -0,0 +1,35 @@
+import { isAxiosError } from "axios";
+import { ApiErrorBody, FormattedSong, QueueState } from "./api-types";
+import queryClient from "./queryClient";
+import { QUERY_KEYS } from "./queryKeys";
+import { formatSong } from "../utils/format";
+
+// the revision of the queue this phone last saw, sent along with queue changes so
+// the server can turn down ones made against a queue that has moved on since
+let queueRevision: number | null = null;
+
+export const setQueueRevision = (revision: number) => {
+  queueRevision = revision;
+};
+
+export const ifMatchQueueRevision = () =>
+  queueRevision === null ? {} : { "If-Match": `"${queueRevision}"` };
+
+// when someone else changed the queue first, the server answers with the queue as
+// it is now, so show that instead of what this phone thought it would look like
+export const catchUpOnStaleQueue = (error: Error) => {
+  if (
+    !isAxiosError<ApiErrorBody>(error) ||
+    error.response?.data?.code !== "stale_queue_revision"
+  ) {
+    return false;
+  }
+
+  const current = error.response.data.details as unknown as QueueState;
+  setQueueRevision(current.revision);
+  queryClient.setQueryData<FormattedSong[]>(
+    QUERY_KEYS.queue,
+    current.queue.map(formatSong)
+  );
+  return true;
+};
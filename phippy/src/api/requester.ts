@@ -0,0 +1,7 @@
+const SINGER_NAME_KEY = "singer_name";
+
+// the name this phone last joined a song under, offered again next time
+export const getSingerName = () => localStorage.getItem(SINGER_NAME_KEY) ?? "";
+
+export const setSingerName = (name: string) =>
+  localStorage.setItem(SINGER_NAME_KEY, name);
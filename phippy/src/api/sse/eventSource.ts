@@ -1,2 +1,8 @@
-export const SSE_URL = `${window.location.origin}/sse`;
+// phones join a room with ?room=<id>; without it everything goes to the default room
+export const ROOM = new URLSearchParams(window.location.search).get("room");
+
 export const API_URL = `${window.location.origin}`;
+export const ROOM_URL = ROOM
+  ? `${API_URL}/rooms/${encodeURIComponent(ROOM)}`
+  : API_URL;
+export const SSE_URL = `${ROOM_URL}/sse`;
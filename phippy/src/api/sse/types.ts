@@ -1,13 +1,28 @@
-import { Song } from "../api-types";
+import {
+  PlaybackState,
+  ProcessingPhase,
+  RequestDecision,
+  SkippedSong,
+  SkipVotes,
+  Song,
+  StageState,
+} from "../api-types";
 
 export enum EventType {
   QueueChange = "QueueUpdated",
   KeyChange = "KeyChange",
-  TogglePlayback = "TogglePlayback",
+  PlaybackChanged = "PlaybackChanged",
+  SkipVotesUpdated = "SkipVotesUpdated",
+  SongsSkipped = "SongsSkipped",
+  PendingUpdated = "PendingUpdated",
+  RequestDecided = "RequestDecided",
+  StageChanged = "StageChanged",
+  DownloadProgress = "DownloadProgress",
 }
 
 export type QueueUpdatedEvent = {
   type: EventType.QueueChange;
+  revision: number;
   queue: Song[];
 };
 
@@ -16,8 +31,51 @@ export type KeyChangeEvent = {
   current_key: number;
 };
 
-export type TogglePlaybackEvent = {
-  type: EventType.TogglePlayback;
+export type PlaybackChangedEvent = {
+  type: EventType.PlaybackChanged;
+  playback: PlaybackState;
 };
 
-export type SSEEvent = QueueUpdatedEvent | TogglePlaybackEvent | KeyChangeEvent;
+export type SkipVotesUpdatedEvent = {
+  type: EventType.SkipVotesUpdated;
+  skip_votes: SkipVotes;
+};
+
+export type SongsSkippedEvent = {
+  type: EventType.SongsSkipped;
+  skipped: SkippedSong[];
+};
+
+export type PendingUpdatedEvent = {
+  type: EventType.PendingUpdated;
+  pending: Song[];
+};
+
+export type RequestDecidedEvent = {
+  type: EventType.RequestDecided;
+  song: Song;
+  decision: RequestDecision;
+};
+
+export type StageChangedEvent = {
+  type: EventType.StageChanged;
+  stage: StageState;
+};
+
+export type DownloadProgressEvent = {
+  type: EventType.DownloadProgress;
+  song_uuid: string;
+  phase: ProcessingPhase;
+  percent: number | null;
+};
+
+export type SSEEvent =
+  | QueueUpdatedEvent
+  | PlaybackChangedEvent
+  | KeyChangeEvent
+  | SkipVotesUpdatedEvent
+  | SongsSkippedEvent
+  | PendingUpdatedEvent
+  | RequestDecidedEvent
+  | StageChangedEvent
+  | DownloadProgressEvent;
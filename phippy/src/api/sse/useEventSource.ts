@@ -1,7 +1,16 @@
 import { useQuery, useQueryClient } from "@tanstack/react-query";
-import { Song } from "../api-types";
+import {
+  DecidedRequest,
+  OwnSongs,
+  PlaybackState,
+  SkippedSong,
+  SkipVotes,
+  Song,
+  StageState,
+} from "../api-types";
 import { useQueue } from "../queries/useQueue";
 import { QUERY_KEYS } from "../queryKeys";
+import { setQueueRevision } from "../queueRevision";
 import { SSE_URL } from "./eventSource";
 import { EventType, type SSEEvent } from "./types";
 import { formatSong } from "../../utils/format";
@@ -27,6 +36,7 @@ export const useEventSource = () => {
               const data = JSON.parse(event.data) as SSEEvent;
               switch (data.type) {
                 case EventType.QueueChange:
+                  setQueueRevision(data.revision);
                   queryClient.setQueryData<Song[]>(
                     QUERY_KEYS.queue,
                     data.queue.map(formatSong)
@@ -38,7 +48,74 @@ export const useEventSource = () => {
                     data.current_key
                   );
                   break;
-                case EventType.TogglePlayback:
+                case EventType.PlaybackChanged:
+                  queryClient.setQueryData<PlaybackState>(
+                    QUERY_KEYS.playback,
+                    data.playback
+                  );
+                  break;
+                case EventType.SkipVotesUpdated:
+                  queryClient.setQueryData<SkipVotes>(
+                    QUERY_KEYS.skipVotes,
+                    data.skip_votes
+                  );
+                  break;
+                case EventType.SongsSkipped:
+                  queryClient.setQueryData<SkippedSong[]>(
+                    QUERY_KEYS.skippedSongs,
+                    data.skipped
+                  );
+                  // some of them may have been parked
+                  queryClient.invalidateQueries({
+                    queryKey: QUERY_KEYS.parkedSongs,
+                  });
+                  break;
+                case EventType.PendingUpdated:
+                  queryClient.setQueryData<Song[]>(
+                    QUERY_KEYS.pendingSongs,
+                    data.pending.map(formatSong)
+                  );
+                  break;
+                case EventType.RequestDecided:
+                  // everyone hears about every decision, only keep the ones for this phone
+                  if (
+                    queryClient
+                      .getQueryData<OwnSongs>(QUERY_KEYS.ownSongs)
+                      ?.requested.includes(data.song.uuid)
+                  ) {
+                    queryClient.setQueryData<DecidedRequest[]>(
+                      QUERY_KEYS.decidedRequests,
+                      (decided = []) => [
+                        { song: data.song, decision: data.decision },
+                        ...decided,
+                      ]
+                    );
+                  }
+                  break;
+                case EventType.DownloadProgress:
+                  queryClient.setQueryData<Song[]>(
+                    QUERY_KEYS.queue,
+                    (queue) =>
+                      queue?.map((song) =>
+                        song.uuid === data.song_uuid
+                          ? {
+                              ...song,
+                              progress: {
+                                phase: data.phase,
+                                percent: data.percent,
+                              },
+                            }
+                          : song
+                      )
+                  );
+                  break;
+                case EventType.StageChanged:
+                  queryClient.setQueryData<StageState>(
+                    QUERY_KEYS.stage,
+                    data.stage
+                  );
+                  // a scheduled break that just started is no longer scheduled
+                  queryClient.invalidateQueries({ queryKey: QUERY_KEYS.breaks });
                   break;
                 default:
                   console.error("invalid event type", data);
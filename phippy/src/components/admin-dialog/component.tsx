@@ -1,11 +1,30 @@
 import { useState } from "react";
 import { BsSkipForwardFill } from "react-icons/bs";
-import { PiPlayPauseBold } from "react-icons/pi";
+import { PiPauseBold, PiPlayBold } from "react-icons/pi";
 import { TbMinus, TbPlus } from "react-icons/tb";
-import { useSkip, useTogglePlayback } from "../../api/mutations/useControls";
+import { PlaybackStatus, SkipReason } from "../../api/api-types";
+import { usePause, usePlay, useSkip } from "../../api/mutations/useControls";
 import { useKeyDown, useKeyUp } from "../../api/mutations/usePitch";
 import { useKey } from "../../api/queries/useKey";
+import { usePlayback } from "../../api/queries/usePlayback";
+import { useSkippedSongs } from "../../api/queries/useSkippedSongs";
+import { usePendingSongs } from "../../api/queries/usePendingSongs";
+import {
+  useApproveSong,
+  useRejectSong,
+} from "../../api/mutations/useDecideRequest";
+import { formatSong } from "../../utils/format";
+
+const SKIP_REASONS: Record<SkipReason, string> = {
+  [SkipReason.DroppedFailed]: "download failed, removed",
+  [SkipReason.ParkedFailed]: "download failed, parked",
+  [SkipReason.StillDownloading]: "still downloading, plays later",
+};
 import { useAuth } from "../../api/queries/useAuth";
+import { Playlists } from "../playlists/component";
+import { StageControls } from "../stage-controls/component";
+import { IdleModeSettings } from "../idle-mode/component";
+import { FailedDownloads } from "../failed-downloads/component";
 import { VscDebugRestart } from "react-icons/vsc";
 import { useCurrentSong } from "../../api/queries/useCurrentSong";
 import { useRestartSong } from "../../api/mutations/useRestart";
@@ -14,7 +33,14 @@ export const AdminDialog = ({ className }: { className?: string }) => {
   const [password, setPassword] = useState("");
   const [error, setError] = useState(false);
   const { mutate: skip } = useSkip();
-  const { mutate: togglePlayback } = useTogglePlayback();
+  const { mutate: play } = usePlay();
+  const { mutate: pause } = usePause();
+  const playback = usePlayback();
+  const isPlaying = playback?.status === PlaybackStatus.Playing;
+  const skippedSongs = useSkippedSongs();
+  const pendingSongs = usePendingSongs();
+  const { mutate: approveSong } = useApproveSong();
+  const { mutate: rejectSong } = useRejectSong();
   const { mutate: keyUp } = useKeyUp();
   const { mutate: keyDown } = useKeyDown();
   const key = useKey();
@@ -149,10 +175,14 @@ export const AdminDialog = ({ className }: { className?: string }) => {
 
                     <button className="group flex flex-col items-center">
                       <div
-                        onClick={() => togglePlayback()}
+                        onClick={() => (isPlaying ? pause() : play())}
                         className="p-3 bg-black/20 text-white/80 rounded-lg transition-all duration-200 group-active:scale-95"
                       >
-                        <PiPlayPauseBold className="text-2xl" />
+                        {isPlaying ? (
+                          <PiPauseBold className="text-2xl" />
+                        ) : (
+                          <PiPlayBold className="text-2xl" />
+                        )}
                       </div>
                     </button>
 
@@ -166,6 +196,55 @@ export const AdminDialog = ({ className }: { className?: string }) => {
                     </button>
                   </div>
                 </div>
+                {skippedSongs.length > 0 && (
+                  <div className="mt-4 space-y-1">
+                    {skippedSongs.map(({ song, reason }) => (
+                      <p key={song.uuid} className="text-xs text-white/50">
+                        skipped {formatSong(song).formattedName}:{" "}
+                        {SKIP_REASONS[reason]}
+                      </p>
+                    ))}
+                  </div>
+                )}
+                {pendingSongs.length > 0 && (
+                  <div className="mt-4 space-y-2">
+                    <p className="text-xs uppercase tracking-wider text-white/40">
+                      waiting for approval
+                    </p>
+                    {pendingSongs.map((song) => (
+                      <div
+                        key={song.uuid}
+                        className="flex items-center justify-between gap-2"
+                      >
+                        <p className="text-sm text-white/80 truncate">
+                          {song.formattedName}
+                        </p>
+                        <div className="flex gap-2 shrink-0">
+                          <button
+                            onClick={() =>
+                              approveSong({ song_uuid: song.uuid })
+                            }
+                            className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+                          >
+                            approve
+                          </button>
+                          <button
+                            onClick={() =>
+                              rejectSong({ song_uuid: song.uuid })
+                            }
+                            className="px-3 py-1 text-xs rounded-full bg-red-500/10 text-red-400 active:scale-95"
+                          >
+                            reject
+                          </button>
+                        </div>
+                      </div>
+                    ))}
+                  </div>
+                )}
+                <FailedDownloads />
+                <StageControls />
+                <IdleModeSettings />
+                <Playlists />
               </div>
             </div>
           </div>
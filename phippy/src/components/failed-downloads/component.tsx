@@ -0,0 +1,47 @@
+import { Status } from "../../api/api-types";
+import { useRetrySong } from "../../api/mutations/useRetrySong";
+import { useParkedSongs } from "../../api/queries/useParkedSongs";
+import { useQueue } from "../../api/queries/useQueue";
+import { formatDownloadFailure } from "../../utils/format";
+
+export const FailedDownloads = () => {
+  const { data: queue } = useQueue();
+  const parkedSongs = useParkedSongs();
+  const { mutate: retrySong } = useRetrySong();
+
+  const failedSongs = [
+    ...(queue ?? []).filter((song) => song.status === Status.Failed),
+    ...parkedSongs,
+  ];
+
+  if (failedSongs.length === 0) return null;
+
+  return (
+    <div className="mt-4 space-y-2">
+      <p className="text-xs uppercase tracking-wider text-white/40">
+        failed downloads
+      </p>
+      {failedSongs.map((song) => (
+        <div
+          key={song.uuid}
+          className="flex items-center justify-between gap-2"
+        >
+          <div className="min-w-0">
+            <p className="text-sm text-white/80 truncate">
+              {song.formattedName}
+            </p>
+            <p className="text-xs text-white/50 truncate">
+              {formatDownloadFailure(song)}
+            </p>
+          </div>
+          <button
+            onClick={() => retrySong({ song_uuid: song.uuid })}
+            className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95 shrink-0"
+          >
+            retry
+          </button>
+        </div>
+      ))}
+    </div>
+  );
+};
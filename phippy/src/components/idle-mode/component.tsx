@@ -0,0 +1,86 @@
+import { useEffect, useState } from "react";
+import { IdleSource } from "../../api/api-types";
+import { useSetIdleMode } from "../../api/mutations/useIdleMode";
+import { useIdleMode } from "../../api/queries/useIdleMode";
+import { usePlaylists } from "../../api/queries/usePlaylists";
+
+// the select works on plain strings, playlists are "playlist:<name>"
+const toOption = (source: IdleSource) =>
+  typeof source === "string" ? source : `playlist:${source.Playlist}`;
+
+const fromOption = (option: string): IdleSource =>
+  option.startsWith("playlist:")
+    ? { Playlist: option.slice("playlist:".length) }
+    : (option as IdleSource);
+
+export const IdleModeSettings = () => {
+  const idleMode = useIdleMode();
+  const playlists = usePlaylists();
+  const { mutate: setIdleMode } = useSetIdleMode();
+  const [afterSeconds, setAfterSeconds] = useState("");
+
+  useEffect(() => {
+    if (idleMode) setAfterSeconds(String(idleMode.after_seconds));
+  }, [idleMode]);
+
+  if (!idleMode) {
+    return null;
+  }
+
+  const saveAfterSeconds = () => {
+    const value = Number(afterSeconds);
+    if (!Number.isInteger(value) || value < 0) return;
+    setIdleMode({ ...idleMode, after_seconds: value });
+  };
+
+  return (
+    <div className="mt-4 space-y-2">
+      <p className="text-xs uppercase tracking-wider text-white/40">
+        idle mode
+      </p>
+      <label className="flex items-center justify-between gap-2">
+        <span className="text-sm text-white/80">
+          play cached songs when the queue is empty
+        </span>
+        <input
+          type="checkbox"
+          checked={idleMode.enabled}
+          onChange={(e) =>
+            setIdleMode({ ...idleMode, enabled: e.target.checked })
+          }
+        />
+      </label>
+      <div className="flex items-center gap-2">
+        <p className="flex-1 text-sm text-white/80">after seconds</p>
+        <input
+          type="number"
+          min={0}
+          value={afterSeconds}
+          onChange={(e) => setAfterSeconds(e.target.value)}
+          className="w-16 px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white focus:outline-none"
+        />
+        <button
+          onClick={saveAfterSeconds}
+          className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+        >
+          save
+        </button>
+      </div>
+      <select
+        value={toOption(idleMode.source)}
+        onChange={(e) =>
+          setIdleMode({ ...idleMode, source: fromOption(e.target.value) })
+        }
+        className="w-full px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white focus:outline-none"
+      >
+        <option value="Random">random</option>
+        <option value="Popular">most popular</option>
+        {playlists.map((playlist) => (
+          <option key={playlist.name} value={`playlist:${playlist.name}`}>
+            playlist: {playlist.name}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+};
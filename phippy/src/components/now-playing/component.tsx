@@ -1,6 +1,19 @@
+import { useEffect } from "react";
 import { FormattedSong } from "../../api/api-types";
+import { useVoteSkip } from "../../api/mutations/useVoteSkip";
+import { useSkipVotes } from "../../api/queries/useSkipVotes";
 
 function NowPlaying({ currentSong }: { currentSong: FormattedSong }) {
+  const skipVotes = useSkipVotes();
+  const { mutate: voteSkip, isSuccess: hasVoted, reset } = useVoteSkip();
+  const votesForThisSong =
+    skipVotes?.song_uuid === currentSong.uuid ? skipVotes : undefined;
+
+  // votes are per song, so a new song gets a fresh button
+  useEffect(() => {
+    reset();
+  }, [currentSong.uuid, reset]);
+
   return (
     <div className="w-full mb-6 p-6 sm:p-8 bg-black/30 backdrop-blur-lg rounded-2xl border border-white/10 shadow-xl">
       <div className="flex items-center gap-2 mb-4">
@@ -39,6 +52,19 @@ function NowPlaying({ currentSong }: { currentSong: FormattedSong }) {
           )}
         </div>
       </div>
+      <div className="flex items-center justify-between mt-4">
+        <p className="text-sm text-white/60">
+          {votesForThisSong?.votes ?? 0}/{votesForThisSong?.needed ?? "-"} votes
+          to skip
+        </p>
+        <button
+          onClick={() => voteSkip()}
+          disabled={hasVoted}
+          className="px-3 py-1.5 text-sm font-medium rounded-full bg-white/10 text-white/80 transition-all duration-200 active:scale-95 disabled:opacity-40"
+        >
+          {hasVoted ? "voted" : "vote skip"}
+        </button>
+      </div>
     </div>
   );
 }
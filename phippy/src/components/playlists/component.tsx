@@ -0,0 +1,91 @@
+import { useState } from "react";
+import {
+  playlistExportUrl,
+  useDeletePlaylist,
+  useQueuePlaylist,
+  useSavePlaylist,
+} from "../../api/mutations/usePlaylists";
+import { usePlaylists } from "../../api/queries/usePlaylists";
+
+export const Playlists = () => {
+  const [name, setName] = useState("");
+  const playlists = usePlaylists();
+  const { mutate: savePlaylist } = useSavePlaylist();
+  const { mutate: queuePlaylist, data: queued } = useQueuePlaylist();
+  const { mutate: deletePlaylist } = useDeletePlaylist();
+
+  const save = (source: "queue" | "history") => {
+    if (!name.trim()) return;
+    savePlaylist(
+      { name: name.trim(), source },
+      { onSuccess: () => setName("") }
+    );
+  };
+
+  return (
+    <div className="mt-4 space-y-2">
+      <p className="text-xs uppercase tracking-wider text-white/40">
+        playlists
+      </p>
+      <div className="flex gap-2">
+        <input
+          type="text"
+          value={name}
+          onChange={(e) => setName(e.target.value)}
+          placeholder="playlist name"
+          autoComplete="off"
+          className="flex-1 min-w-0 px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white placeholder-white/30 focus:outline-none"
+        />
+        <button
+          onClick={() => save("queue")}
+          className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+        >
+          save queue
+        </button>
+        <button
+          onClick={() => save("history")}
+          className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+        >
+          save history
+        </button>
+      </div>
+      {playlists.map((playlist) => (
+        <div
+          key={playlist.name}
+          className="flex items-center justify-between gap-2"
+        >
+          <p className="text-sm text-white/80 truncate">
+            {playlist.name}{" "}
+            <span className="text-white/40">({playlist.songs})</span>
+          </p>
+          <div className="flex gap-2 shrink-0">
+            <button
+              onClick={() => queuePlaylist(playlist.name)}
+              className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+            >
+              queue
+            </button>
+            <a
+              href={playlistExportUrl(playlist.name, "m3u")}
+              className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+            >
+              m3u
+            </a>
+            <button
+              onClick={() => deletePlaylist(playlist.name)}
+              className="px-3 py-1 text-xs rounded-full bg-red-500/10 text-red-400 active:scale-95"
+            >
+              delete
+            </button>
+          </div>
+        </div>
+      ))}
+      {queued && queued.data.rejected.length > 0 && (
+        <p className="text-xs text-white/50">
+          queued {queued.data.queued + queued.data.pending}, skipped{" "}
+          {queued.data.rejected.map(({ title }) => title).join(", ")}
+        </p>
+      )}
+    </div>
+  );
+};
@@ -19,7 +19,12 @@ import { useMemo, useState } from "react";
 import { useReposition } from "../../api/mutations/useReposition";
 import { useAuth } from "../../api/queries/useAuth";
 import { useCurrentSong } from "../../api/queries/useCurrentSong";
+import { useDecidedRequests } from "../../api/queries/useDecidedRequests";
+import { useOwnSongs } from "../../api/queries/useOwnSongs";
+import { usePendingSongs } from "../../api/queries/usePendingSongs";
 import { useQueue } from "../../api/queries/useQueue";
+import { RequestDecision } from "../../api/api-types";
+import { formatSong } from "../../utils/format";
 import NowPlaying from "../now-playing/component";
 import QueueSkeleton from "../queue-skeleton/component";
 import { SearchDialog } from "../search-dialog/component";
@@ -31,6 +36,14 @@ export const Queue = () => {
   const currentSong = useCurrentSong();
   const [isSearchOpen, setIsSearchOpen] = useState(false);
   const { isAuthenticated } = useAuth();
+  const pendingSongs = usePendingSongs();
+  const { requested } = useOwnSongs();
+  const myPendingSongs = useMemo(() => {
+    return pendingSongs.filter((song) => requested.includes(song.uuid));
+  }, [pendingSongs, requested]);
+  const rejectedRequests = useDecidedRequests().filter(
+    ({ decision }) => decision === RequestDecision.Rejected
+  );
   const nextSongs = useMemo(() => {
     return queue?.slice(1, 100) ?? [];
   }, [queue]);
@@ -67,6 +80,22 @@ export const Queue = () => {
               <NowPlaying currentSong={currentSong} />
             )}
 
+            {(myPendingSongs.length > 0 || rejectedRequests.length > 0) && (
+              <div className="w-full mb-6 space-y-1">
+                {myPendingSongs.map((song) => (
+                  <p key={song.uuid} className="text-sm text-white/60">
+                    {song.formattedName}: waiting for host approval
+                  </p>
+                ))}
+                {rejectedRequests.map(({ song }) => (
+                  <p key={song.uuid} className="text-sm text-red-400">
+                    {formatSong(song).formattedName}: the host passed on this
+                    one
+                  </p>
+                ))}
+              </div>
+            )}
+
             <div className="w-full space-y-3">
               {nextSongs.length > 0 ? (
                 <DndContext
@@ -1,6 +1,9 @@
 import { useState } from "react";
 import { useDebouncedCallback } from "use-debounce";
-import { useAddToQueue } from "../../api/mutations/useAddToQueue";
+import {
+  rejectionMessage,
+  useAddToQueue,
+} from "../../api/mutations/useAddToQueue";
 import { useSearchYoutube } from "../../api/mutations/useSearchYoutube";
 
 export const SearchDialog = ({
@@ -19,7 +22,11 @@ export const SearchDialog = ({
     error,
   } = useSearchYoutube(debouncedQuery);
 
-  const { mutate: addToQueue } = useAddToQueue();
+  const {
+    mutate: addToQueue,
+    error: addToQueueError,
+    reset: resetAddToQueue,
+  } = useAddToQueue();
   const [shouldPitchShift, setShouldPitchShift] = useState(false);
 
   const debouncedSetQuery = useDebouncedCallback((value: string) => {
@@ -40,6 +47,7 @@ export const SearchDialog = ({
     <div
       onClick={(e) => {
         e.stopPropagation();
+        resetAddToQueue();
         onClose();
       }}
       className="z-50 fixed inset-0 bg-gradient-to-br from-purple-900/80 via-indigo-900/80 to-blue-900/80 backdrop-blur-sm flex items-center justify-center p-8"
@@ -86,6 +94,7 @@ export const SearchDialog = ({
               value={displayQuery}
               onChange={(e) => {
                 setDisplayQuery(e.target.value);
+                resetAddToQueue();
                 debouncedSetQuery(e.target.value);
               }}
               className={`w-full px-4 py-3 bg-gray-800/90 border border-white/10 rounded-xl focus:outline-none focus:ring-2 focus:ring-purple-500/50 ${
@@ -105,6 +114,12 @@ export const SearchDialog = ({
           <div className="max-h-[60vh] overflow-y-auto">
             {isPending && debouncedQuery && <Spinner />}
 
+            {addToQueueError && (
+              <div className="flex justify-center items-center pb-4 text-red-400">
+                {rejectionMessage(addToQueueError)}
+              </div>
+            )}
+
             {isError && (
               <div className="flex justify-center items-center py-8 text-red-400">
                 {error?.message || "something went wrong fr"}
@@ -126,14 +141,16 @@ export const SearchDialog = ({
                   key={result.url}
                   className="p-4 bg-gray-800/80 backdrop-blur-sm rounded-xl hover:bg-gray-700/80 transition-colors cursor-pointer flex gap-4 items-center mb-3"
                   onClick={() => {
-                    addToQueue({
-                      yt_link: result.url,
-                      name: result.title
-                        .replace(/[^a-zA-Z0-9\s]/g, "")
-                        .replace(/\s/g, "_"),
-                      is_key_changeable: shouldPitchShift,
-                    });
-                    onClose();
+                    addToQueue(
+                      {
+                        yt_link: result.url,
+                        name: result.title
+                          .replace(/[^a-zA-Z0-9\s]/g, "")
+                          .replace(/\s/g, "_"),
+                        is_key_changeable: shouldPitchShift,
+                      },
+                      { onSuccess: onClose }
+                    );
                   }}
                 >
                   <img
@@ -4,6 +4,18 @@ import { CSS } from "@dnd-kit/utilities";
 import { useAuth } from "../../api/queries/useAuth";
 import { motion, PanInfo, useMotionValue, useTransform } from "framer-motion";
 import { useDeleteSong } from "../../api/mutations/useDeleteSong";
+import { useJoinSong, useLeaveSong } from "../../api/mutations/useJoinSong";
+import { useOwnSongs } from "../../api/queries/useOwnSongs";
+import { usePlayback } from "../../api/queries/usePlayback";
+import { getSingerName, setSingerName } from "../../api/requester";
+import { rejectionMessage } from "../../api/mutations/useAddToQueue";
+import {
+  formatDuration,
+  formatPerformers,
+  formatProgress,
+  formatStartsIn,
+} from "../../utils/format";
+import { useNow } from "../../utils/useNow";
 
 export default function SongItem({
   song,
@@ -14,6 +26,23 @@ export default function SongItem({
 }) {
   const { isAuthenticated } = useAuth();
   const { mutate: deleteSong } = useDeleteSong();
+  const { mutate: joinSong } = useJoinSong();
+  const { mutate: leaveSong } = useLeaveSong();
+  const { requested, joined } = useOwnSongs();
+  const playback = usePlayback();
+  const now = useNow();
+  const isOwnSong = requested.includes(song.uuid);
+  const hasJoined = joined.includes(song.uuid);
+  const details = [
+    formatPerformers(song.performers),
+    song.uploader,
+    formatDuration(song.duration_seconds),
+    formatStartsIn(song.starts_at, playback, now),
+    song.status === Status.InProgress && formatProgress(song.progress),
+    song.status === Status.InProgress &&
+      (song.download_attempts ?? 0) > 1 &&
+      `attempt ${song.download_attempts}`,
+  ].filter(Boolean);
 
   const {
     attributes,
@@ -34,6 +63,22 @@ export default function SongItem({
     }
   };
 
+  const handleJoin = () => {
+    if (hasJoined) {
+      leaveSong({ song_uuid: song.uuid });
+      return;
+    }
+
+    const singer = window.prompt("Who's joining?", getSingerName())?.trim();
+    if (!singer) return;
+
+    setSingerName(singer);
+    joinSong(
+      { song_uuid: song.uuid, singer },
+      { onError: (error) => window.alert(rejectionMessage(error)) }
+    );
+  };
+
   const style = {
     transform: CSS.Transform.toString(transform),
     transition,
@@ -75,6 +120,13 @@ export default function SongItem({
             <div className="animate-spin h-4 w-4 border-2 border-purple-200/90 rounded-full border-t-transparent" />
           )}
           <span className="text-sm text-purple-200/90">#{i + 1}</span>
+          {song.thumbnail_url && (
+            <img
+              src={song.thumbnail_url}
+              alt=""
+              className="w-12 h-9 object-cover rounded"
+            />
+          )}
           <div className="flex-1 min-w-0">
             <p
               className={`text-base sm:text-lg truncate text-white ${
@@ -83,7 +135,20 @@ export default function SongItem({
             >
               {song.formattedName}
             </p>
+            {details.length > 0 && (
+              <p className="text-xs truncate text-white/50">
+                {details.join(" · ")}
+              </p>
+            )}
           </div>
+          {!isOwnSong && song.status !== "Failed" && (
+            <button
+              onClick={handleJoin}
+              className="text-xs px-2 py-1 rounded-full border border-purple-200/40 text-purple-200/90 hover:bg-white/10"
+            >
+              {hasJoined ? "Leave" : "Join"}
+            </button>
+          )}
           {song.status === "Failed" && (
             <div className="text-red-400 flex items-center gap-2">
               <svg
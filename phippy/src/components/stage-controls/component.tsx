@@ -0,0 +1,153 @@
+import { useEffect, useState } from "react";
+import { StagePhase } from "../../api/api-types";
+import {
+  useCancelBreak,
+  useExtendStage,
+  useScheduleBreak,
+  useSetInterstitial,
+  useSkipStage,
+} from "../../api/mutations/useStage";
+import {
+  useBreaks,
+  useInterstitial,
+  useStage,
+} from "../../api/queries/useStage";
+
+// "22:00" as the next time the clock shows it, in unix seconds
+const nextOccurrence = (time: string) => {
+  const [hours, minutes] = time.split(":").map(Number);
+  const startsAt = new Date();
+  startsAt.setHours(hours, minutes, 0, 0);
+  if (startsAt.getTime() < Date.now()) {
+    startsAt.setDate(startsAt.getDate() + 1);
+  }
+  return Math.floor(startsAt.getTime() / 1000);
+};
+
+const formatTime = (unixSeconds: number) =>
+  new Date(unixSeconds * 1000).toLocaleTimeString([], {
+    hour: "2-digit",
+    minute: "2-digit",
+  });
+
+export const StageControls = () => {
+  const stage = useStage();
+  const interstitial = useInterstitial();
+  const breaks = useBreaks();
+  const { mutate: skipStage } = useSkipStage();
+  const { mutate: extendStage } = useExtendStage();
+  const { mutate: setInterstitial } = useSetInterstitial();
+  const { mutate: scheduleBreak } = useScheduleBreak();
+  const { mutate: cancelBreak } = useCancelBreak();
+  const [seconds, setSeconds] = useState("");
+  const [breakTime, setBreakTime] = useState("");
+  const [breakMinutes, setBreakMinutes] = useState("15");
+
+  useEffect(() => {
+    if (interstitial) setSeconds(String(interstitial.seconds));
+  }, [interstitial]);
+
+  const betweenSongs = !!stage && stage.phase !== StagePhase.Singing;
+
+  const saveInterstitial = () => {
+    const value = Number(seconds);
+    if (!Number.isInteger(value) || value < 0) return;
+    setInterstitial({ seconds: value });
+  };
+
+  const addBreak = () => {
+    const minutes = Number(breakMinutes);
+    if (!Number.isInteger(minutes) || minutes <= 0) return;
+    scheduleBreak(
+      {
+        starts_at: breakTime ? nextOccurrence(breakTime) : undefined,
+        minutes,
+      },
+      { onSuccess: () => setBreakTime("") }
+    );
+  };
+
+  return (
+    <div className="mt-4 space-y-2">
+      <p className="text-xs uppercase tracking-wider text-white/40">
+        between songs
+      </p>
+      {betweenSongs && (
+        <div className="flex items-center justify-between gap-2">
+          <p className="text-sm text-white/80">
+            {stage.phase === StagePhase.Break
+              ? "on a break"
+              : "up next countdown"}
+          </p>
+          <div className="flex gap-2 shrink-0">
+            <button
+              onClick={() => extendStage(30)}
+              className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+            >
+              +30s
+            </button>
+            <button
+              onClick={() => skipStage()}
+              className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+            >
+              skip
+            </button>
+          </div>
+        </div>
+      )}
+      <div className="flex items-center gap-2">
+        <p className="flex-1 text-sm text-white/80">countdown seconds</p>
+        <input
+          type="number"
+          min={0}
+          value={seconds}
+          onChange={(e) => setSeconds(e.target.value)}
+          className="w-16 px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white focus:outline-none"
+        />
+        <button
+          onClick={saveInterstitial}
+          className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+        >
+          save
+        </button>
+      </div>
+      <div className="flex items-center gap-2">
+        <input
+          type="time"
+          value={breakTime}
+          onChange={(e) => setBreakTime(e.target.value)}
+          className="flex-1 min-w-0 px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white focus:outline-none"
+        />
+        <input
+          type="number"
+          min={1}
+          value={breakMinutes}
+          onChange={(e) => setBreakMinutes(e.target.value)}
+          className="w-16 px-3 py-1 bg-black/20 border border-white/10 rounded-lg text-sm text-white focus:outline-none"
+        />
+        <button
+          onClick={addBreak}
+          className="px-3 py-1 text-xs rounded-full bg-white/10 text-white/80 active:scale-95"
+        >
+          {breakTime ? "schedule break" : "break after song"}
+        </button>
+      </div>
+      {breaks.map((scheduled) => (
+        <div
+          key={scheduled.id}
+          className="flex items-center justify-between gap-2"
+        >
+          <p className="text-sm text-white/80">
+            {scheduled.minutes} min break at {formatTime(scheduled.starts_at)}
+          </p>
+          <button
+            onClick={() => cancelBreak(scheduled.id)}
+            className="px-3 py-1 text-xs rounded-full bg-red-500/10 text-red-400 active:scale-95 shrink-0"
+          >
+            cancel
+          </button>
+        </div>
+      ))}
+    </div>
+  );
+};
@@ -1,7 +1,69 @@
-import { Song } from "../api/api-types";
+import {
+  PlaybackState,
+  PlaybackStatus,
+  ProcessingPhase,
+  ProcessingProgress,
+  Song,
+} from "../api/api-types";
 
 export const formatSong = (song: Song) => {
   const rawName = song.name;
   const formattedName = rawName.replace("./assets/", "").replaceAll("_", " ");
   return { ...song, formattedName };
 };
+
+// "in ~12 min" for a queue entry, nothing when the server can't tell yet. The
+// server's estimate is as of the last playback update, so a pause pushes it back.
+export const formatStartsIn = (
+  startsAt: number | null | undefined,
+  playback: PlaybackState | undefined,
+  now: number
+) => {
+  if (startsAt == null) return null;
+
+  const pausedFor =
+    playback?.status === PlaybackStatus.Paused
+      ? Math.max(now - playback.updated_at, 0)
+      : 0;
+  const minutes = Math.round((startsAt + pausedFor - now) / 60000);
+  return minutes < 1 ? "up next" : `in ~${minutes} min`;
+};
+
+export const formatDuration = (durationSeconds?: number | null) => {
+  if (durationSeconds == null) return null;
+
+  const seconds = Math.round(durationSeconds);
+  return `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, "0")}`;
+};
+
+const PROCESSING_PHASES: Record<ProcessingPhase, string> = {
+  [ProcessingPhase.Downloading]: "downloading",
+  [ProcessingPhase.ProcessingAudio]: "processing audio",
+  [ProcessingPhase.Packaging]: "packaging",
+};
+
+// "downloading 42%" while a song is getting ready
+export const formatProgress = (progress?: ProcessingProgress | null) => {
+  if (!progress) return null;
+
+  const phase = PROCESSING_PHASES[progress.phase];
+  return progress.percent == null
+    ? phase
+    : `${phase} ${Math.floor(progress.percent)}%`;
+};
+
+// "failed after 3 attempts: Private video" for the host
+export const formatDownloadFailure = (song: Song) => {
+  const attempts = song.download_attempts ?? 0;
+  const failed =
+    attempts > 1 ? `failed after ${attempts} attempts` : "download failed";
+  return song.download_error ? `${failed}: ${song.download_error}` : failed;
+};
+
+// "Ann, Bob & Cat" for everyone singing a song
+export const formatPerformers = (performers?: string[]) => {
+  if (!performers || performers.length === 0) return null;
+  if (performers.length === 1) return performers[0];
+
+  return `${performers.slice(0, -1).join(", ")} & ${performers.at(-1)}`;
+};
@@ -0,0 +1,13 @@
+import { useEffect, useState } from "react";
+
+// the current time in unix milliseconds, ticking over every so often so estimates
+// counting down to it stay current
+export const useNow = (intervalMs = 15000) => {
+  const [now, setNow] = useState(Date.now());
+  useEffect(() => {
+    const interval = setInterval(() => setNow(Date.now()), intervalMs);
+    return () => clearInterval(interval);
+  }, [intervalMs]);
+
+  return now;
+};